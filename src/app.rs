@@ -1,20 +1,36 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use std::process::Command;
+use std::time::{Instant, SystemTime};
 
 use anyhow::Result;
 use tokio::sync::mpsc;
 
+use crate::clipboard;
+use crate::config::Config;
+use crate::editor;
+use crate::export::{self, ExportFormat};
+use crate::input::{Key, KeyAction, Scope};
 use crate::logs::{
-    Agent, DisplayEntry, ParseResult, Project, Session, SessionWatcher, discover_agents,
-    discover_projects, discover_sessions, merge_tool_results, parse_jsonl_file_async,
-    parse_jsonl_from_position_async,
+    Agent, ConversationTree, DisplayEntry, FileIndex, ParseResult, Project, SearchHit, Session,
+    SessionStats, SessionWatcher, UsageTotals, discover_agents, discover_projects,
+    discover_sessions, file_operation, merge_tool_results, parse_jsonl_file_async,
+    parse_jsonl_from_position_async, parse_jsonl_to_position_async, scan_usage, search_projects,
+    session_cwd, slowest_tools,
+};
+use crate::pricing::PriceTable;
+use crate::ui::{
+    AgentListState, AppLayout, BranchPickerState, ConversationState, FileIndexState,
+    GLOBAL_SEARCH_LIMIT, GlobalSearchState, ProjectListState, SLOWEST_TOOLS_LIMIT, SearchDirection,
+    SearchState, SessionListState, SlowestToolsState, Theme, is_expandable,
 };
-use crate::ui::{AgentListState, ConversationState, ProjectListState, SessionListState, Theme};
 
-/// Maximum number of conversation entries to keep in memory.
+/// Default maximum number of conversation entries to keep in memory.
 /// When exceeded, oldest entries are dropped.
-const MAX_CONVERSATION_ENTRIES: usize = 10_000;
+pub const MAX_CONVERSATION_ENTRIES: usize = 10_000;
+
+/// How a session is resumed unless configured otherwise
+pub const DEFAULT_RESUME_COMMAND: &str = "claude --resume {id}";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FocusPane {
@@ -30,6 +46,65 @@ pub enum ParseMessage {
         path: PathBuf,
         result: Result<ParseResult>,
     },
+    /// The log parsed again to lay out a branch of its conversation tree
+    Branch {
+        path: PathBuf,
+        result: Result<ParseResult>,
+        /// Start from the top of the branch rather than keep the scroll position
+        reset_view: bool,
+    },
+}
+
+/// True for a tool call that read, wrote or edited `path`
+fn touches_file(entry: &DisplayEntry, path: &str) -> bool {
+    let DisplayEntry::ToolCall { name, input, .. } = entry else {
+        return false;
+    };
+    serde_json::from_str::<serde_json::Value>(input)
+        .ok()
+        .as_ref()
+        .and_then(|input| file_operation(name, input))
+        .is_some_and(|(_, touched)| touched == path)
+}
+
+/// Builds the command that resumes a session from a template like `claude --resume {id}`,
+/// appending the ID if the template doesn't mention it. Words are split on whitespace.
+fn resume_command(template: &str, session_id: &str) -> Option<Command> {
+    let mut words: Vec<String> = template
+        .split_whitespace()
+        .map(|word| word.replace("{id}", session_id))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    if !template.contains("{id}") {
+        words.push(session_id.to_string());
+    }
+    let (program, args) = words.split_first()?;
+    let mut command = Command::new(program);
+    command.args(args);
+    Some(command)
+}
+
+/// Where the entry at `idx` of `old` is in `new`, the same conversation laid out again. Branch
+/// markers can come and go at forks, so entries are matched by their position among the
+/// other entries, and only if they still agree on kind and time.
+fn carried_over_index(
+    old: &VecDeque<DisplayEntry>,
+    new: &VecDeque<DisplayEntry>,
+    idx: usize,
+) -> Option<usize> {
+    let is_marker = |entry: &DisplayEntry| matches!(entry, DisplayEntry::BranchPoint { .. });
+    let entry = old.get(idx).filter(|entry| !is_marker(entry))?;
+    let rank = old.range(..idx).filter(|e| !is_marker(e)).count();
+    let (new_idx, moved) = new
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !is_marker(e))
+        .nth(rank)?;
+    (std::mem::discriminant(moved) == std::mem::discriminant(entry)
+        && moved.timestamp() == entry.timestamp())
+    .then_some(new_idx)
 }
 
 /// Message sent when async project/session discovery completes
@@ -39,6 +114,23 @@ pub enum DiscoveryMessage {
         project_path: PathBuf,
         result: Result<Vec<Session>>,
     },
+    UsageScanned(Vec<SessionUsageScan>),
+    GlobalSearchComplete {
+        query: String,
+        hits: Vec<SearchHit>,
+    },
+    FilesIndexed {
+        session_log_path: PathBuf,
+        index: FileIndex,
+    },
+}
+
+/// Token usage scanned in the background for every agent log of one session
+pub struct SessionUsageScan {
+    pub session_log_path: PathBuf,
+    /// (agent log path, modification time, totals). Totals are None when the cached
+    /// value for that modification time is still current.
+    pub agents: Vec<(PathBuf, SystemTime, Option<UsageTotals>)>,
 }
 
 pub struct App {
@@ -55,10 +147,22 @@ pub struct App {
     pub watcher: SessionWatcher,
     pub show_thinking: bool,
     pub expand_tools: bool,
+    /// Show Edit/MultiEdit diffs in full instead of a preview
+    pub full_diffs: bool,
+    /// Show working directory, git branch and version changes in the conversation
+    pub show_metadata: bool,
+    /// Show statistics for the loaded conversation in place of the conversation itself
+    pub show_stats: bool,
+    /// Statistics for the loaded conversation, kept up to date while they are shown
+    pub stats: SessionStats,
     pub show_help: bool,
     pub viewport_height: Option<usize>,
+    /// Pane areas as of the last draw, for mapping mouse clicks to panes and rows
+    pub layout: Option<AppLayout>,
     pub error_message: Option<String>,
-    /// Number of entries dropped from the front due to MAX_CONVERSATION_ENTRIES limit
+    /// One-off notice for the status bar (e.g. where an export was written)
+    pub status_message: Option<String>,
+    /// Number of entries dropped from the front due to the `max_conversation_entries` limit
     pub entries_truncated: usize,
     /// Parse errors encountered (line number and error message)
     pub parse_errors: Vec<String>,
@@ -72,6 +176,8 @@ pub struct App {
     pub parsing_path: Option<PathBuf>,
     /// Whether a refresh operation is currently in progress
     pub is_refreshing: bool,
+    /// Whether the log is being parsed again to lay out a branch
+    is_rebuilding: bool,
     /// Channel receiver for async discovery results
     pub discovery_rx: mpsc::UnboundedReceiver<DiscoveryMessage>,
     /// Channel sender for async discovery results
@@ -86,10 +192,38 @@ pub struct App {
     cached_agent_width: Option<u16>,
     /// Last time we refreshed the conversation from file watcher
     last_conversation_refresh: Option<Instant>,
+    /// Per-model prices for cost estimates
+    pub prices: PriceTable,
+    /// Running token totals for the loaded conversation
+    pub agent_usage: UsageTotals,
+    /// Scanned usage per agent log, keyed by path with the modification time it was scanned at
+    usage_cache: HashMap<PathBuf, (SystemTime, UsageTotals)>,
+    /// Agent log paths per session log path, from the last usage scan
+    session_agent_logs: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Vim-style search over the loaded conversation
+    pub search: SearchState,
+    /// Full-text search over every session on disk
+    pub global_search: GlobalSearchState,
+    /// Entry index (before truncation) to scroll to once the loading conversation is parsed
+    pending_entry_jump: Option<usize>,
+    /// Every branch of the loaded conversation, from the log's parent links
+    pub tree: ConversationTree,
+    /// Leaf of the branch being shown; None follows the latest branch
+    pub selected_branch: Option<usize>,
+    /// Overlay for switching between branches
+    pub branch_picker: BranchPickerState,
+    /// Overlay listing the tool calls that took the longest
+    pub slowest_tools: SlowestToolsState,
+    /// Overlay listing every file the session touched
+    pub file_index: FileIndexState,
+    /// Settings from the config file and command line
+    pub config: Config,
+    /// Keys of a chord typed so far
+    pub pending_keys: Vec<Key>,
 }
 
 impl App {
-    pub fn new(theme: Theme, super_follow_enabled: bool) -> Result<Self> {
+    pub fn new(theme: Theme, prices: PriceTable, config: &Config) -> Result<Self> {
         let projects = discover_projects().unwrap_or_default();
         let sessions = if !projects.is_empty() {
             discover_sessions(&projects[0]).unwrap_or_default()
@@ -112,11 +246,17 @@ impl App {
             conversation_state: ConversationState::new(),
             theme,
             watcher: SessionWatcher::new(),
-            show_thinking: false,
-            expand_tools: true,
+            show_thinking: config.display.show_thinking,
+            expand_tools: config.display.expand_tools,
+            full_diffs: config.display.full_diffs,
+            show_metadata: config.display.show_metadata,
+            show_stats: false,
+            stats: SessionStats::default(),
             show_help: false,
             viewport_height: None,
+            layout: None,
             error_message: None,
+            status_message: None,
             entries_truncated: 0,
             parse_errors: Vec::new(),
             parse_rx,
@@ -124,18 +264,34 @@ impl App {
             is_parsing: false,
             parsing_path: None,
             is_refreshing: false,
+            is_rebuilding: false,
             discovery_rx,
             discovery_tx,
-            super_follow_enabled,
+            super_follow_enabled: config.super_follow,
             cached_project_width: None,
             cached_session_width: None,
             cached_agent_width: None,
             last_conversation_refresh: None,
+            prices,
+            agent_usage: UsageTotals::new(),
+            usage_cache: HashMap::new(),
+            session_agent_logs: HashMap::new(),
+            search: SearchState::new(),
+            global_search: GlobalSearchState::new(),
+            pending_entry_jump: None,
+            tree: ConversationTree::new(),
+            selected_branch: None,
+            branch_picker: BranchPickerState::new(),
+            slowest_tools: SlowestToolsState::new(),
+            file_index: FileIndexState::new(),
+            config: config.clone(),
+            pending_keys: Vec::new(),
         };
 
         // Load initial agents and conversation if there's a session
         app.load_agents_for_selected_session();
         app.load_conversation_for_selected_agent();
+        app.refresh_usage();
 
         Ok(app)
     }
@@ -158,12 +314,161 @@ impl App {
         };
     }
 
+    /// Show or hide every thinking block, including ones expanded or collapsed individually
     pub fn toggle_thinking(&mut self) {
         self.show_thinking = !self.show_thinking;
+        let conversation = &self.conversation;
+        self.conversation_state
+            .toggled_entries
+            .retain(|&idx| !matches!(conversation.get(idx), Some(DisplayEntry::Thinking { .. })));
     }
 
+    /// Expand or collapse every tool call, including ones toggled individually
     pub fn toggle_tool_expansion(&mut self) {
         self.expand_tools = !self.expand_tools;
+        let conversation = &self.conversation;
+        self.conversation_state
+            .toggled_entries
+            .retain(|&idx| matches!(conversation.get(idx), Some(DisplayEntry::Thinking { .. })));
+    }
+
+    /// Copy the entry under the conversation cursor to the system clipboard: the whole entry,
+    /// or with `target_only` just its Bash command or file path
+    pub fn yank_selected_entry(&mut self, target_only: bool) {
+        let Some(entry) = self
+            .conversation_state
+            .selected_entry
+            .and_then(|idx| self.conversation.get(idx))
+        else {
+            self.status_message = Some(format!(
+                "Select an entry with {} to copy it",
+                self.entry_cursor_keys()
+            ));
+            return;
+        };
+        let text = if target_only {
+            clipboard::entry_target(entry)
+        } else {
+            clipboard::entry_text(entry)
+        };
+        let Some(text) = text else {
+            self.status_message = Some(if target_only {
+                "No command or file path to copy".to_string()
+            } else {
+                "Nothing to copy".to_string()
+            });
+            return;
+        };
+        match clipboard::copy(&text) {
+            Ok(()) => {
+                let lines = text.lines().count();
+                self.status_message = Some(format!(
+                    "Copied {} line{} to the clipboard",
+                    lines,
+                    if lines == 1 { "" } else { "s" }
+                ));
+            }
+            Err(e) => self.error_message = Some(format!("Failed to copy: {}", e)),
+        }
+    }
+
+    /// Keys that move the conversation's entry cursor, for hints
+    fn entry_cursor_keys(&self) -> String {
+        let keys: Vec<String> = [KeyAction::NextEntry, KeyAction::PreviousEntry]
+            .into_iter()
+            .filter_map(|action| self.config.keys.key_for(Scope::Conversation, action))
+            .collect();
+        if keys.is_empty() {
+            "a click".to_string()
+        } else {
+            keys.join("/")
+        }
+    }
+
+    /// Command that resumes the selected session in the directory it ran in: the `cwd` its log
+    /// records, or the project directory for logs without one
+    pub fn resume_command_for_selected_session(&mut self) -> Option<Command> {
+        let session = self
+            .session_state
+            .selected()
+            .and_then(|idx| self.sessions.get(idx))?;
+        let dir = match session_cwd(&session.log_path) {
+            Some(cwd) => cwd,
+            None => {
+                let Some(project) = self
+                    .projects
+                    .iter()
+                    .find(|project| project.path == session.project_path)
+                else {
+                    self.error_message = Some("Can't find the session's project".to_string());
+                    return None;
+                };
+                project.original_path.clone()
+            }
+        };
+        if !dir.is_dir() {
+            self.error_message = Some(format!(
+                "Project directory {} no longer exists",
+                dir.display()
+            ));
+            return None;
+        }
+        let Some(mut command) = resume_command(&self.config.resume_command, &session.id) else {
+            self.error_message = Some("The resume command is empty".to_string());
+            return None;
+        };
+        command.current_dir(&dir);
+        Some(command)
+    }
+
+    /// Command that opens the file of the tool call under the conversation cursor in the
+    /// user's editor, at the line it touched where that is known
+    pub fn editor_for_selected_entry(&mut self) -> Option<Command> {
+        let Some(entry) = self
+            .conversation_state
+            .selected_entry
+            .and_then(|idx| self.conversation.get(idx))
+        else {
+            self.status_message = Some(format!(
+                "Select a tool call with {} to open its file",
+                self.entry_cursor_keys()
+            ));
+            return None;
+        };
+        let Some((path, line)) = editor::entry_location(entry) else {
+            self.status_message = Some("No file to open for this entry".to_string());
+            return None;
+        };
+        Some(editor::editor_command(&path, line))
+    }
+
+    /// Expand or collapse the entry under the conversation cursor
+    pub fn toggle_selected_entry(&mut self) {
+        if let Some(idx) = self.conversation_state.selected_entry
+            && self.conversation.get(idx).is_some_and(is_expandable)
+        {
+            self.conversation_state.toggle_entry(idx);
+        }
+    }
+
+    pub fn toggle_full_diffs(&mut self) {
+        self.full_diffs = !self.full_diffs;
+    }
+
+    pub fn toggle_metadata(&mut self) {
+        self.show_metadata = !self.show_metadata;
+    }
+
+    pub fn toggle_stats(&mut self) {
+        self.show_stats = !self.show_stats;
+        self.update_stats();
+    }
+
+    /// Recompute the statistics after the conversation changed, if they are being shown
+    fn update_stats(&mut self) {
+        if self.show_stats {
+            self.stats = SessionStats::from_entries(&self.conversation);
+        }
     }
 
     pub fn load_sessions_for_selected_project(&mut self) {
@@ -177,6 +482,7 @@ impl App {
                     self.session_state = SessionListState::new();
                     self.load_agents_for_selected_session();
                     self.load_conversation_for_selected_agent();
+                    self.refresh_usage();
                 }
                 Err(e) => {
                     self.error_message = Some(format!("Failed to load sessions: {}", e));
@@ -325,6 +631,108 @@ impl App {
 
         // Also refresh agents for the selected session
         self.refresh_agents();
+        self.refresh_usage();
+    }
+
+    /// Scan token usage for every listed session in the background.
+    /// Agent logs whose modification time matches the cache are not re-read.
+    pub fn refresh_usage(&mut self) {
+        if self.sessions.is_empty() {
+            return;
+        }
+
+        let sessions = self.sessions.clone();
+        let known: HashMap<PathBuf, SystemTime> = self
+            .usage_cache
+            .iter()
+            .map(|(path, (modified, _))| (path.clone(), *modified))
+            .collect();
+        // The loaded conversation is tracked live, so its log never needs a rescan once cached
+        let live_path = self.watcher.current_path().cloned();
+        let tx = self.discovery_tx.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let scans = sessions
+                .iter()
+                .map(|session| {
+                    let agents = discover_agents(session)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|agent| {
+                            let modified = std::fs::metadata(&agent.log_path)
+                                .and_then(|m| m.modified())
+                                .unwrap_or(SystemTime::UNIX_EPOCH);
+                            let cached = known.get(&agent.log_path);
+                            let is_live = live_path.as_ref() == Some(&agent.log_path);
+                            let totals =
+                                if cached == Some(&modified) || (is_live && cached.is_some()) {
+                                    None
+                                } else {
+                                    Some(scan_usage(&agent.log_path).unwrap_or_default())
+                                };
+                            (agent.log_path, modified, totals)
+                        })
+                        .collect();
+                    SessionUsageScan {
+                        session_log_path: session.log_path.clone(),
+                        agents,
+                    }
+                })
+                .collect();
+            let _ = tx.send(DiscoveryMessage::UsageScanned(scans));
+        });
+    }
+
+    /// Handle completion of a background usage scan
+    pub fn handle_usage_scanned(&mut self, scans: Vec<SessionUsageScan>) {
+        for scan in scans {
+            let mut agent_logs = Vec::with_capacity(scan.agents.len());
+            for (path, modified, totals) in scan.agents {
+                if let Some(totals) = totals {
+                    self.usage_cache.insert(path.clone(), (modified, totals));
+                }
+                agent_logs.push(path);
+            }
+            self.session_agent_logs
+                .insert(scan.session_log_path, agent_logs);
+        }
+        self.cached_session_width = None; // Cost column may have appeared
+    }
+
+    /// Token totals for a session across its main agent and sub-agents.
+    /// The loaded conversation contributes its live totals instead of the last scan.
+    pub fn session_usage(&self, session: &Session) -> Option<UsageTotals> {
+        let agent_logs = self.session_agent_logs.get(&session.log_path)?;
+        let live_path = self.watcher.current_path();
+
+        let mut totals = UsageTotals::new();
+        for path in agent_logs {
+            if live_path == Some(path) {
+                totals.merge(&self.agent_usage);
+            } else if let Some((_, cached)) = self.usage_cache.get(path) {
+                totals.merge(cached);
+            }
+        }
+        Some(totals)
+    }
+
+    /// Estimated dollar cost for each listed session, in list order
+    pub fn session_costs(&self) -> Vec<Option<f64>> {
+        self.sessions
+            .iter()
+            .map(|session| {
+                self.session_usage(session)
+                    .and_then(|totals| self.prices.cost(&totals))
+            })
+            .collect()
+    }
+
+    /// Token totals for the selected session
+    pub fn selected_session_usage(&self) -> Option<UsageTotals> {
+        self.session_state
+            .selected()
+            .and_then(|idx| self.sessions.get(idx))
+            .and_then(|session| self.session_usage(session))
     }
 
     /// Refresh agents list for current session, preserving selection if possible
@@ -374,8 +782,17 @@ impl App {
         }
 
         self.watcher.stop();
+        // A refresh or rebuild still in flight belongs to the previous log
+        self.is_refreshing = false;
+        self.is_rebuilding = false;
+        self.pending_entry_jump = None;
         self.entries_truncated = 0;
         self.parse_errors.clear();
+        self.agent_usage = UsageTotals::new();
+        self.tree = ConversationTree::new();
+        self.selected_branch = None;
+        self.branch_picker.close();
+        self.slowest_tools.close();
 
         // Clone the path early to avoid borrow issues
         let log_path = self
@@ -389,6 +806,7 @@ impl App {
             self.is_parsing = true;
             self.parsing_path = Some(path.clone());
             self.conversation.clear();
+            self.update_stats();
             self.error_message = None;
 
             // Spawn async parsing task
@@ -399,6 +817,7 @@ impl App {
             });
         } else {
             self.conversation.clear();
+            self.update_stats();
             self.is_parsing = false;
             self.parsing_path = None;
         }
@@ -430,18 +849,38 @@ impl App {
         match result {
             Ok(ParseResult {
                 entries,
+                nodes,
                 errors,
                 bytes_read,
             }) => {
                 if is_initial {
-                    // Initial load: replace conversation entirely
-                    let merged = merge_tool_results(entries);
+                    // Initial load: replace conversation entirely with the latest branch
+                    self.agent_usage = UsageTotals::from_entries(&entries);
+                    self.tree.extend(&entries, &nodes);
+                    let merged =
+                        merge_tool_results(self.tree.branch_entries(None, entries, &nodes));
                     self.conversation = VecDeque::from(merged);
                     self.parse_errors = errors;
                     self.apply_conversation_limit();
+                    self.update_stats();
                     self.conversation_state = ConversationState::new();
                     self.error_message = None;
 
+                    // Keep the last query active across conversations, but not its position
+                    self.search.current = None;
+                    self.search.update_matches(&self.conversation, 0);
+
+                    // Opened from a search result: scroll to the matching entry
+                    if let Some(entry_idx) = self
+                        .pending_entry_jump
+                        .take()
+                        .and_then(|idx| idx.checked_sub(self.entries_truncated))
+                    {
+                        self.conversation_state.jump_to_entry(entry_idx);
+                        self.search.current =
+                            self.search.matches.iter().position(|&m| m == entry_idx);
+                    }
+
                     // Start watching from where we left off
                     if let Err(e) = self.watcher.watch(path) {
                         self.error_message = Some(format!("Failed to watch file: {}", e));
@@ -454,8 +893,51 @@ impl App {
                     self.parse_errors.extend(errors);
 
                     if !entries.is_empty() {
+                        self.agent_usage.record_entries(&entries);
+                        let forked = self.tree.extend(&entries, &nodes);
+                        if forked || self.selected_branch.is_some() {
+                            // A new fork adds markers to earlier entries, and new lines don't
+                            // belong to a pinned branch: lay the conversation out again
+                            if forked {
+                                self.rebuild_conversation(false);
+                            }
+                            return;
+                        }
+                        let truncated_before = self.entries_truncated;
+
                         // Merge new entries (handles results within the new batch)
-                        let merged_new = merge_tool_results(entries);
+                        let mut merged_new = merge_tool_results(entries);
+
+                        // A response streamed across the refresh boundary repeats its usage:
+                        // update the existing Usage entry rather than showing it twice
+                        if let Some(DisplayEntry::Usage {
+                            message_id: Some(new_id),
+                            ..
+                        }) = merged_new.first()
+                            && let Some(idx) = self
+                                .conversation
+                                .iter()
+                                .rposition(|e| matches!(e, DisplayEntry::Usage { .. }))
+                            && matches!(&self.conversation[idx], DisplayEntry::Usage { message_id: Some(id), .. } if id == new_id)
+                        {
+                            self.conversation[idx] = merged_new.remove(0);
+                            self.conversation_state.entries_changed(idx);
+                        }
+
+                        // Each batch starts with the metadata of its first line; keep only changes
+                        let mut last_metadata =
+                            self.conversation.iter().rev().find_map(|e| match e {
+                                DisplayEntry::Metadata { metadata, .. } => Some(metadata.clone()),
+                                _ => None,
+                            });
+                        merged_new.retain(|e| match e {
+                            DisplayEntry::Metadata { metadata, .. } => {
+                                let changed = last_metadata.as_ref() != Some(metadata);
+                                last_metadata = Some(metadata.clone());
+                                changed
+                            }
+                            _ => true,
+                        });
 
                         // Check if last existing entry is a ToolCall that needs its result
                         // merged from the first new entry
@@ -466,7 +948,7 @@ impl App {
                                 tool_use_id,
                                 content,
                                 is_error,
-                                ..
+                                timestamp,
                             }) = merged_new.first()
                             && tool_use_id == id
                         {
@@ -474,7 +956,10 @@ impl App {
                             *result = Some(crate::logs::ToolCallResult {
                                 content: content.clone(),
                                 is_error: *is_error,
+                                timestamp: *timestamp,
                             });
+                            self.conversation_state
+                                .entries_changed(self.conversation.len() - 1);
                             // Skip the first entry since we merged it
                             self.conversation.extend(merged_new.into_iter().skip(1));
                         } else {
@@ -482,6 +967,11 @@ impl App {
                         }
 
                         self.apply_conversation_limit();
+                        self.update_stats();
+                        self.search.update_matches(
+                            &self.conversation,
+                            self.entries_truncated - truncated_before,
+                        );
                     }
                 }
             }
@@ -489,6 +979,7 @@ impl App {
                 if is_initial {
                     self.error_message = Some(format!("Failed to load conversation: {}", e));
                     self.conversation.clear();
+                    self.update_stats();
                 } else if is_refresh {
                     self.error_message = Some(format!("Failed to refresh: {}", e));
                 }
@@ -496,25 +987,138 @@ impl App {
         }
     }
 
-    /// Enforce MAX_CONVERSATION_ENTRIES limit by dropping oldest entries
+    /// Parse the log again to lay out the selected branch of the tree. The tree keeps only the
+    /// links between lines, so the entries come from the file rather than from memory.
+    fn rebuild_conversation(&mut self, reset_view: bool) {
+        let Some(path) = self.watcher.current_path().cloned() else {
+            return;
+        };
+        // Hold off refreshes until the branch is laid out, so no new line is missed or doubled
+        let end = self.watcher.file_position();
+        self.is_rebuilding = true;
+        let tx = self.parse_tx.clone();
+        tokio::spawn(async move {
+            let result = parse_jsonl_to_position_async(path.clone(), end).await;
+            let _ = tx.send(ParseMessage::Branch {
+                path,
+                result,
+                reset_view,
+            });
+        });
+    }
+
+    /// Replace the conversation with the selected branch of the re-parsed log. Unless
+    /// `reset_view` is set, keep the scroll position, the cursor and individually expanded
+    /// entries.
+    pub fn handle_branch_parsed(
+        &mut self,
+        path: PathBuf,
+        result: Result<ParseResult>,
+        reset_view: bool,
+    ) {
+        if !self.is_rebuilding || self.watcher.current_path() != Some(&path) {
+            return;
+        }
+        self.is_rebuilding = false;
+        let ParseResult { entries, nodes, .. } = match result {
+            Ok(result) => result,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to load branch: {}", e));
+                return;
+            }
+        };
+        // A refresh that was already running added lines after the parsed ones: start over
+        if nodes.len() < self.tree.len() {
+            self.rebuild_conversation(reset_view);
+            return;
+        }
+
+        let follow_mode = self.conversation_state.follow_mode;
+        let scroll_offset = self.conversation_state.scroll_offset;
+        let merged = merge_tool_results(self.tree.branch_entries(
+            self.selected_branch,
+            entries,
+            &nodes,
+        ));
+        let old = std::mem::replace(&mut self.conversation, VecDeque::from(merged));
+        self.entries_truncated = 0;
+        self.apply_conversation_limit();
+        self.update_stats();
+        let old_state = std::mem::take(&mut self.conversation_state);
+        self.search.current = None;
+        self.search.update_matches(&self.conversation, 0);
+        if reset_view {
+            return;
+        }
+        self.conversation_state.follow_mode = follow_mode;
+        self.conversation_state.scroll_offset = scroll_offset;
+        let moved = |idx| carried_over_index(&old, &self.conversation, idx);
+        self.conversation_state.toggled_entries = old_state
+            .toggled_entries
+            .iter()
+            .filter_map(|&idx| moved(idx))
+            .collect();
+        self.conversation_state.selected_entry = old_state.selected_entry.and_then(moved);
+    }
+
+    /// Open the branch picker, or explain why there is nothing to pick
+    pub fn open_branch_picker(&mut self) {
+        if !self.tree.has_forks() {
+            self.status_message = Some("This conversation has no branches".to_string());
+            return;
+        }
+        let current = self.selected_branch.or_else(|| self.tree.latest_leaf());
+        self.branch_picker.show(self.tree.branches(), current);
+    }
+
+    /// Show the branch selected in the picker
+    pub fn switch_to_selected_branch(&mut self) {
+        let Some(leaf) = self.branch_picker.selected_leaf() else {
+            return;
+        };
+        self.branch_picker.close();
+        // Picking the latest branch goes back to following new lines as they arrive
+        self.selected_branch = Some(leaf).filter(|&leaf| Some(leaf) != self.tree.latest_leaf());
+        self.rebuild_conversation(true);
+    }
+
+    /// Open the overlay of the loaded conversation's slowest tool calls
+    pub fn open_slowest_tools(&mut self) {
+        self.slowest_tools
+            .show(slowest_tools(&self.conversation, SLOWEST_TOOLS_LIMIT));
+    }
+
+    /// Scroll the conversation to the call selected in the slowest-tools overlay
+    pub fn jump_to_selected_slow_tool(&mut self) {
+        if let Some(entry_idx) = self.slowest_tools.selected_entry() {
+            self.slowest_tools.close();
+            self.conversation_state.jump_to_entry(entry_idx);
+        }
+    }
+
+    /// Enforce the `max_conversation_entries` limit by dropping oldest entries
     fn apply_conversation_limit(&mut self) {
-        while self.conversation.len() > MAX_CONVERSATION_ENTRIES {
+        let limit = self.config.max_conversation_entries.max(1);
+        let mut dropped = 0;
+        while self.conversation.len() > limit {
             self.conversation.pop_front();
             self.entries_truncated += 1;
+            dropped += 1;
         }
+        self.conversation_state.entries_dropped(dropped);
     }
 
     pub fn refresh_conversation(&mut self) {
         // Rate limit: Don't refresh more than once per second
         // This prevents flooding from actively-written files
         if let Some(last_refresh) = self.last_conversation_refresh
-            && last_refresh.elapsed() < Duration::from_secs(1)
+            && last_refresh.elapsed() < self.config.conversation_refresh_interval()
         {
             return;
         }
 
-        // Prevent duplicate refreshes
-        if self.is_refreshing {
+        // Prevent duplicate refreshes, and refreshes while a branch is laid out
+        if self.is_refreshing || self.is_rebuilding {
             return;
         }
 
@@ -532,6 +1136,289 @@ impl App {
         }
     }
 
+    /// Open the search prompt in the given direction
+    pub fn start_search(&mut self, direction: SearchDirection) {
+        let top_entry = self.conversation_state.top_entry();
+        self.search.open(
+            direction,
+            self.conversation_state.scroll_offset,
+            self.conversation_state.follow_mode,
+            top_entry,
+        );
+    }
+
+    /// Append a character to the search prompt and jump to the first match as you type
+    pub fn search_push_char(&mut self, c: char) {
+        if let Some(prompt) = self.search.prompt.as_mut() {
+            prompt.push(c);
+            self.update_incremental_search();
+        }
+    }
+
+    /// Delete the last character of the search prompt; an empty prompt is cancelled
+    pub fn search_backspace(&mut self) {
+        match self.search.prompt.as_mut() {
+            Some(prompt) if prompt.is_empty() => self.cancel_search(),
+            Some(prompt) => {
+                prompt.pop();
+                self.update_incremental_search();
+            }
+            None => {}
+        }
+    }
+
+    fn update_incremental_search(&mut self) {
+        self.search.update_matches(&self.conversation, 0);
+        if let Some(entry_idx) = self.search.select_incremental() {
+            self.conversation_state.jump_to_entry(entry_idx);
+        } else if let Some((scroll_offset, follow_mode)) = self.search.origin() {
+            // No match (yet): stay where the search started
+            self.conversation_state.pending_jump = None;
+            self.conversation_state.scroll_offset = scroll_offset;
+            self.conversation_state.follow_mode = follow_mode;
+        }
+    }
+
+    /// Close the prompt and keep the current match
+    pub fn commit_search(&mut self) {
+        let reuse_last = self
+            .search
+            .prompt
+            .as_ref()
+            .is_some_and(|prompt| prompt.is_empty());
+        self.search.commit();
+        if reuse_last {
+            // `/` followed by Enter repeats the last search
+            self.search.update_matches(&self.conversation, 0);
+            self.search_next(false);
+        }
+    }
+
+    /// Close the prompt and return to where the search started
+    pub fn cancel_search(&mut self) {
+        if let Some((scroll_offset, follow_mode)) = self.search.cancel() {
+            self.conversation_state.pending_jump = None;
+            self.conversation_state.scroll_offset = scroll_offset;
+            self.conversation_state.follow_mode = follow_mode;
+        }
+        // Restore highlights for the previous query, if any
+        self.search.update_matches(&self.conversation, 0);
+    }
+
+    /// Jump to the next match in the search direction (`n`), or the opposite one (`N`)
+    pub fn search_next(&mut self, reverse: bool) {
+        let direction = if reverse {
+            self.search.direction.reversed()
+        } else {
+            self.search.direction
+        };
+        let from_entry = self.conversation_state.top_entry();
+        if let Some(entry_idx) = self.search.step(direction, from_entry) {
+            self.conversation_state.jump_to_entry(entry_idx);
+        }
+    }
+
+    /// Clear the active search and its highlights
+    pub fn clear_search(&mut self) {
+        self.search.clear();
+    }
+
+    /// Run a search over every session on disk in the background
+    pub fn run_global_search(&mut self) {
+        let query = self.global_search.input.clone();
+        if query.is_empty() {
+            return;
+        }
+        self.global_search.query = query.clone();
+        self.global_search.is_searching = true;
+        self.global_search.editing = false;
+        self.global_search.set_results(Vec::new());
+
+        let tx = self.discovery_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let projects = discover_projects().unwrap_or_default();
+            let hits = search_projects(&projects, &query, GLOBAL_SEARCH_LIMIT);
+            let _ = tx.send(DiscoveryMessage::GlobalSearchComplete { query, hits });
+        });
+    }
+
+    /// Handle completion of a background global search
+    pub fn handle_global_search_complete(&mut self, query: String, hits: Vec<SearchHit>) {
+        // Ignore results for a query that has since been replaced
+        if query != self.global_search.query {
+            return;
+        }
+        self.global_search.is_searching = false;
+        self.global_search.set_results(hits);
+    }
+
+    /// Open the selected global search result in the four-pane layout, scrolled to the match
+    pub fn open_selected_search_hit(&mut self) {
+        let Some(hit) = self.global_search.selected_hit().cloned() else {
+            return;
+        };
+
+        let Some(project_idx) = self
+            .projects
+            .iter()
+            .position(|p| p.path == hit.project_path)
+        else {
+            self.error_message = Some(format!("Project not found: {}", hit.project_name));
+            return;
+        };
+        self.project_state.select(Some(project_idx));
+
+        match discover_sessions(&self.projects[project_idx]) {
+            Ok(sessions) => {
+                self.sessions = sessions;
+                self.cached_session_width = None; // Invalidate cache
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to load sessions: {}", e));
+                return;
+            }
+        }
+        let Some(session_idx) = self
+            .sessions
+            .iter()
+            .position(|s| s.log_path == hit.session_log_path)
+        else {
+            self.error_message = Some(format!("Session not found: {}", hit.session_id));
+            return;
+        };
+        self.session_state.select(Some(session_idx));
+        self.load_agents_for_selected_session();
+        self.refresh_usage();
+
+        let agent_idx = self
+            .agents
+            .iter()
+            .position(|a| a.log_path == hit.log_path)
+            .unwrap_or(0);
+        self.agent_state.select(Some(agent_idx));
+
+        // Supersede any parse in flight; its result is ignored once parsing_path changes
+        self.is_parsing = false;
+        self.search.query = self.global_search.query.clone();
+        self.load_conversation_for_selected_agent();
+        if self.is_parsing {
+            self.pending_entry_jump = Some(hit.entry_index);
+        }
+
+        self.global_search.close();
+        self.focus = FocusPane::Conversation;
+    }
+
+    /// Index the files touched by the selected session and its sub-agents in the background
+    pub fn open_file_index(&mut self) {
+        let Some(session) = self
+            .session_state
+            .selected()
+            .and_then(|idx| self.sessions.get(idx))
+        else {
+            return;
+        };
+        self.file_index.show_loading(session.log_path.clone());
+
+        let session_log_path = session.log_path.clone();
+        let agents = self.agents.clone();
+        let tx = self.discovery_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let index = FileIndex::build(&agents);
+            let _ = tx.send(DiscoveryMessage::FilesIndexed {
+                session_log_path,
+                index,
+            });
+        });
+    }
+
+    /// Handle completion of a background file index
+    pub fn handle_files_indexed(&mut self, session_log_path: PathBuf, index: FileIndex) {
+        // Ignore an index for a session that is no longer selected
+        if self.file_index.session_log_path.as_ref() != Some(&session_log_path) {
+            return;
+        }
+        self.file_index.set_index(index);
+    }
+
+    /// Show the calls that touched the selected file: search the conversation for its path and
+    /// jump to the first match, switching to the agent that touched it if the loaded one didn't
+    pub fn open_selected_file(&mut self) {
+        let Some(file) = self.file_index.selected_file().cloned() else {
+            return;
+        };
+        self.file_index.close();
+        self.focus = FocusPane::Conversation;
+        self.search.query = file.path.clone();
+        self.search.current = None;
+
+        let loaded = self.watcher.current_path().cloned();
+        let touch = file
+            .touches
+            .iter()
+            .find(|touch| Some(&touch.log_path) == loaded.as_ref())
+            .or_else(|| file.touches.first());
+        let Some(touch) = touch else {
+            return;
+        };
+
+        if Some(&touch.log_path) == loaded.as_ref() {
+            // Already loaded: the conversation's own indices are authoritative
+            self.search.update_matches(&self.conversation, 0);
+            if let Some(entry_idx) = self
+                .conversation
+                .iter()
+                .position(|entry| touches_file(entry, &file.path))
+            {
+                self.conversation_state.jump_to_entry(entry_idx);
+                self.search.current = self.search.matches.iter().position(|&m| m == entry_idx);
+            }
+            return;
+        }
+
+        let Some(agent_idx) = self
+            .agents
+            .iter()
+            .position(|a| a.log_path == touch.log_path)
+        else {
+            return;
+        };
+        self.agent_state.select(Some(agent_idx));
+        // Supersede any parse in flight; its result is ignored once parsing_path changes
+        self.is_parsing = false;
+        self.load_conversation_for_selected_agent();
+        if self.is_parsing {
+            self.pending_entry_jump = Some(touch.entry_index);
+        }
+    }
+
+    /// Export the selected agent's conversation to Markdown in the current directory
+    pub fn export_selected_agent(&mut self) {
+        let Some(session) = self
+            .session_state
+            .selected()
+            .and_then(|idx| self.sessions.get(idx))
+        else {
+            return;
+        };
+        let Some(agent) = self
+            .agent_state
+            .selected()
+            .and_then(|idx| self.agents.get(idx))
+        else {
+            return;
+        };
+
+        let format = ExportFormat::Markdown;
+        let path = export::default_file_name(session, agent, format);
+        match export::export_agent(session, agent, format, &self.theme)
+            .and_then(|content| Ok(std::fs::write(&path, content)?))
+        {
+            Ok(()) => self.status_message = Some(format!("Exported to {}", path.display())),
+            Err(e) => self.error_message = Some(format!("Export failed: {}", e)),
+        }
+    }
+
     pub fn selected_project_name(&self) -> Option<&str> {
         self.project_state
             .selected()
@@ -579,7 +1466,8 @@ impl App {
             width
         } else {
             use crate::ui::SessionList;
-            let width = SessionList::max_content_width(&self.sessions);
+            let costs = self.session_costs();
+            let width = SessionList::max_content_width(&self.sessions, &costs);
             self.cached_session_width = Some(width);
             width
         }
@@ -631,41 +1519,80 @@ impl App {
 impl Default for App {
     fn default() -> Self {
         // Infallible - returns empty state on error
-        Self::new(Theme::default(), false).unwrap_or_else(|_| {
-            let (parse_tx, parse_rx) = mpsc::unbounded_channel();
-            let (discovery_tx, discovery_rx) = mpsc::unbounded_channel();
-            Self {
-                focus: FocusPane::Projects,
-                projects: Vec::new(),
-                sessions: Vec::new(),
-                agents: Vec::new(),
-                conversation: VecDeque::new(),
-                project_state: ProjectListState::new(),
-                session_state: SessionListState::new(),
-                agent_state: AgentListState::new(),
-                conversation_state: ConversationState::new(),
-                theme: Theme::default(),
-                watcher: SessionWatcher::new(),
-                show_thinking: false,
-                expand_tools: true,
-                show_help: false,
-                viewport_height: None,
-                error_message: Some("Failed to initialize application".to_string()),
-                entries_truncated: 0,
-                parse_errors: Vec::new(),
-                parse_rx,
-                parse_tx,
-                is_parsing: false,
-                parsing_path: None,
-                is_refreshing: false,
-                discovery_rx,
-                discovery_tx,
-                super_follow_enabled: false,
-                cached_project_width: None,
-                cached_session_width: None,
-                cached_agent_width: None,
-                last_conversation_refresh: None,
-            }
-        })
+        Self::new(Theme::default(), PriceTable::default(), &Config::default()).unwrap_or_else(
+            |_| {
+                let (parse_tx, parse_rx) = mpsc::unbounded_channel();
+                let (discovery_tx, discovery_rx) = mpsc::unbounded_channel();
+                Self {
+                    focus: FocusPane::Projects,
+                    projects: Vec::new(),
+                    sessions: Vec::new(),
+                    agents: Vec::new(),
+                    conversation: VecDeque::new(),
+                    project_state: ProjectListState::new(),
+                    session_state: SessionListState::new(),
+                    agent_state: AgentListState::new(),
+                    conversation_state: ConversationState::new(),
+                    theme: Theme::default(),
+                    watcher: SessionWatcher::new(),
+                    show_thinking: false,
+                    expand_tools: true,
+                    full_diffs: false,
+                    show_metadata: false,
+                    show_stats: false,
+                    stats: SessionStats::default(),
+                    show_help: false,
+                    viewport_height: None,
+                    layout: None,
+                    error_message: Some("Failed to initialize application".to_string()),
+                    status_message: None,
+                    entries_truncated: 0,
+                    parse_errors: Vec::new(),
+                    parse_rx,
+                    parse_tx,
+                    is_parsing: false,
+                    parsing_path: None,
+                    is_refreshing: false,
+                    is_rebuilding: false,
+                    discovery_rx,
+                    discovery_tx,
+                    super_follow_enabled: false,
+                    cached_project_width: None,
+                    cached_session_width: None,
+                    cached_agent_width: None,
+                    last_conversation_refresh: None,
+                    prices: PriceTable::default(),
+                    agent_usage: UsageTotals::new(),
+                    usage_cache: HashMap::new(),
+                    session_agent_logs: HashMap::new(),
+                    search: SearchState::new(),
+                    global_search: GlobalSearchState::new(),
+                    pending_entry_jump: None,
+                    tree: ConversationTree::new(),
+                    selected_branch: None,
+                    branch_picker: BranchPickerState::new(),
+                    slowest_tools: SlowestToolsState::new(),
+                    file_index: FileIndexState::new(),
+                    config: Config::default(),
+                    pending_keys: Vec::new(),
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_command() {
+        let command = resume_command("claude --resume {id}", "abc").unwrap();
+        assert_eq!(command.get_program(), "claude");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["--resume", "abc"]);
+
+        let command = resume_command("happy -r", "abc").unwrap();
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["-r", "abc"]);
+        assert!(resume_command("  ", "abc").is_none());
     }
 }
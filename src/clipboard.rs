@@ -0,0 +1,82 @@
+use std::io::{self, Write};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::export::entry_to_markdown;
+use crate::logs::{DisplayEntry, file_operation};
+
+/// Copies `text` to the system clipboard by asking the terminal to set it with the OSC 52
+/// escape sequence, which also works over SSH
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52(text, std::env::var_os("TMUX").is_some()).as_bytes())?;
+    stdout.flush()
+}
+
+fn osc52(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    if !tmux {
+        return sequence;
+    }
+    // tmux takes the plain sequence with `set-clipboard on`, and hands the wrapped one to the
+    // outer terminal with `allow-passthrough on`; send both so either setting works
+    format!(
+        "{}\x1bPtmux;{}\x1b\\",
+        sequence,
+        sequence.replace('\x1b', "\x1b\x1b")
+    )
+}
+
+/// What the copy entry key copies: a message or thinking block as written, anything else as it appears in a
+/// Markdown export. None for entries with no text, like token usage.
+pub fn entry_text(entry: &DisplayEntry) -> Option<String> {
+    let text = match entry {
+        DisplayEntry::Thinking { text, .. } => text.trim_end().to_string(),
+        _ => entry_to_markdown(entry).trim_end().to_string(),
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// What the copy target key copies: a Bash call's command, or the path of the file a tool call read or changed
+pub fn entry_target(entry: &DisplayEntry) -> Option<String> {
+    let DisplayEntry::ToolCall { name, input, .. } = entry else {
+        return None;
+    };
+    let input: serde_json::Value = serde_json::from_str(input).ok()?;
+    let target = match name.as_str() {
+        "Bash" => input.get("command")?.as_str()?,
+        _ => file_operation(name, &input)?.1,
+    };
+    Some(target.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(osc52("hi", false), "\x1b]52;c;aGk=\x07");
+        assert_eq!(
+            osc52("hi", true),
+            "\x1b]52;c;aGk=\x07\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_entry_target() {
+        let call = DisplayEntry::tool_call;
+        assert_eq!(
+            entry_target(&call("Bash", serde_json::json!({"command": "cargo test"}))).as_deref(),
+            Some("cargo test")
+        );
+        assert_eq!(
+            entry_target(&call("Edit", serde_json::json!({"file_path": "/src/a.rs"}))).as_deref(),
+            Some("/src/a.rs")
+        );
+        assert_eq!(
+            entry_target(&call("Grep", serde_json::json!({"pattern": "x"}))),
+            None
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::app::{DEFAULT_RESUME_COMMAND, MAX_CONVERSATION_ENTRIES};
+use crate::input::Keymap;
+use crate::ui::layout::COLLAPSED_WIDTH;
+
+/// Settings from `~/.config/claude-tail/config.toml`. Every key is optional; command-line
+/// flags override the file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Color theme, bundled or from the themes directory
+    pub theme: String,
+    /// Switch to the project, session and agent with the most recent activity
+    pub super_follow: bool,
+    /// Log to ~/.claude/logs/claude-tail.log at this level
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Capture the mouse for clicks and scrolling, instead of leaving it to the terminal
+    pub mouse: bool,
+    /// Command `R` runs to resume a session; `{id}` is replaced by its ID
+    pub resume_command: String,
+    /// Entries kept in memory per conversation; older ones are dropped
+    pub max_conversation_entries: usize,
+    pub display: DisplayConfig,
+    pub layout: LayoutSettings,
+    pub refresh: RefreshConfig,
+    /// Key bindings per scope, by action; these replace the default keys of the actions
+    /// they name
+    pub keys: Keymap,
+}
+
+/// What the conversation pane shows at startup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplayConfig {
+    pub show_thinking: bool,
+    pub expand_tools: bool,
+    pub full_diffs: bool,
+    pub show_metadata: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutSettings {
+    /// Width of the project, session and agent columns when they aren't focused
+    pub collapsed_width: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RefreshConfig {
+    /// How often the project and session lists are rescanned, in seconds
+    pub list_interval_secs: u64,
+    /// Shortest time between reloads of a conversation that is being written, in milliseconds
+    pub conversation_interval_ms: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: "tokyonight-storm".to_string(),
+            super_follow: false,
+            log_level: None,
+            mouse: true,
+            resume_command: DEFAULT_RESUME_COMMAND.to_string(),
+            max_conversation_entries: MAX_CONVERSATION_ENTRIES,
+            display: DisplayConfig::default(),
+            layout: LayoutSettings::default(),
+            refresh: RefreshConfig::default(),
+            keys: Keymap::default(),
+        }
+    }
+}
+
+impl Default for DisplayConfig {
+    fn default() -> Self {
+        Self {
+            show_thinking: false,
+            expand_tools: true,
+            full_diffs: false,
+            show_metadata: false,
+        }
+    }
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            collapsed_width: COLLAPSED_WIDTH,
+        }
+    }
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        Self {
+            list_interval_secs: 5,
+            conversation_interval_ms: 1000,
+        }
+    }
+}
+
+impl Config {
+    /// Load `~/.config/claude-tail/config.toml`, or the defaults if there is none
+    pub fn load() -> Result<Self> {
+        match config_file_path() {
+            Some(path) if path.exists() => {
+                let content = std::fs::read_to_string(&path)?;
+                Self::parse(&content)
+                    .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// The theme from the config file, for subcommands that use no other setting. Other keys
+    /// aren't checked, and a file that can't be read or parsed leaves the default theme with
+    /// a warning.
+    pub fn load_theme() -> String {
+        let default = Self::default().theme;
+        let Some(path) = config_file_path().filter(|path| path.exists()) else {
+            return default;
+        };
+        let theme = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| Self::parse_theme(&content).map_err(|e| e.to_string()));
+        match theme {
+            Ok(theme) => theme.unwrap_or(default),
+            Err(e) => {
+                eprintln!("Warning: ignoring invalid {}: {}", path.display(), e);
+                default
+            }
+        }
+    }
+
+    fn parse_theme(content: &str) -> Result<Option<String>, toml::de::Error> {
+        #[derive(Deserialize)]
+        struct ThemeOnly {
+            theme: Option<String>,
+        }
+        toml::from_str::<ThemeOnly>(content).map(|file| file.theme)
+    }
+
+    /// The settings as a config file
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    pub fn list_refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh.list_interval_secs.max(1))
+    }
+
+    pub fn conversation_refresh_interval(&self) -> Duration {
+        Duration::from_millis(self.refresh.conversation_interval_ms)
+    }
+}
+
+pub fn config_file_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("claude-tail").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_config_keeps_defaults() {
+        let config = Config::parse(
+            "theme = \"nord\"\n\n[display]\nshow_thinking = true\n\n[refresh]\nlist_interval_secs = 30\n",
+        )
+        .unwrap();
+        assert_eq!(config.theme, "nord");
+        assert!(config.display.show_thinking);
+        assert!(config.display.expand_tools);
+        assert_eq!(config.list_refresh_interval(), Duration::from_secs(30));
+        assert_eq!(config.max_conversation_entries, MAX_CONVERSATION_ENTRIES);
+
+        assert!(Config::parse("thme = \"nord\"").is_err());
+        assert_eq!(
+            Config::parse(&Config::default().to_toml().unwrap()).unwrap(),
+            Config::default()
+        );
+    }
+
+    #[test]
+    fn test_theme_ignores_other_keys() {
+        assert_eq!(
+            Config::parse_theme("theme = \"nord\"\nthme = 1\n[display]\nfoo = true\n").unwrap(),
+            Some("nord".to_string())
+        );
+        assert_eq!(Config::parse_theme("mouse = false").unwrap(), None);
+        assert!(Config::parse_theme("theme = ").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::logs::diff::tool_edits;
+use crate::logs::{DisplayEntry, file_operation};
+
+/// Editors that take `file:line` rather than `+line file`
+const COLON_LINE_EDITORS: &[&str] = &["hx", "helix", "subl", "zed"];
+
+/// Editors in the VS Code family, which need `-g` before `file:line`
+const GOTO_FLAG_EDITORS: &[&str] = &["code", "code-insiders", "codium", "cursor"];
+
+/// The file a tool call read or changed, and the line to open it at where one is known: where
+/// a `Read` started, or where an edit's new text is in the file now
+pub fn entry_location(entry: &DisplayEntry) -> Option<(String, Option<usize>)> {
+    let DisplayEntry::ToolCall { name, input, .. } = entry else {
+        return None;
+    };
+    let input: serde_json::Value = serde_json::from_str(input).ok()?;
+    let (_, path) = file_operation(name, &input)?;
+    let line = match name.as_str() {
+        "Read" => input
+            .get("offset")
+            .and_then(|v| v.as_u64())
+            .map(|offset| offset.max(1) as usize),
+        "Edit" | "MultiEdit" => tool_edits(name, &input).and_then(|(_, edits)| {
+            let content = std::fs::read_to_string(path).ok()?;
+            edits.iter().find_map(|edit| line_of(&content, &edit.new))
+        }),
+        _ => None,
+    };
+    Some((path.to_string(), line))
+}
+
+/// 1-based line where `text` first appears in `content`
+fn line_of(content: &str, text: &str) -> Option<usize> {
+    if text.is_empty() {
+        return None;
+    }
+    let start = content.find(text)?;
+    Some(content[..start].matches('\n').count() + 1)
+}
+
+/// Command that opens `path` at `line` in the user's editor: `$VISUAL`, then `$EDITOR`, then
+/// `vi`. The variable may include arguments, e.g. `code --wait`.
+pub fn editor_command(path: &str, line: Option<usize>) -> Command {
+    let editor = std::env::var("VISUAL")
+        .ok()
+        .or_else(|| std::env::var("EDITOR").ok())
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let mut command = Command::new(program);
+    command.args(words);
+    command.args(editor_args(program, path, line));
+    command
+}
+
+fn editor_args(program: &str, path: &str, line: Option<usize>) -> Vec<String> {
+    let Some(line) = line else {
+        return vec![path.to_string()];
+    };
+    let name = Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program);
+    if GOTO_FLAG_EDITORS.contains(&name) {
+        vec!["-g".to_string(), format!("{}:{}", path, line)]
+    } else if COLON_LINE_EDITORS.contains(&name) {
+        vec![format!("{}:{}", path, line)]
+    } else {
+        // vi, vim, nvim, nano, emacs, kak, micro and most others
+        vec![format!("+{}", line), path.to_string()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_args() {
+        assert_eq!(editor_args("nvim", "/a.rs", Some(12)), ["+12", "/a.rs"]);
+        assert_eq!(editor_args("/usr/bin/hx", "/a.rs", Some(12)), ["/a.rs:12"]);
+        assert_eq!(editor_args("code", "/a.rs", Some(12)), ["-g", "/a.rs:12"]);
+        assert_eq!(editor_args("code", "/a.rs", None), ["/a.rs"]);
+    }
+
+    #[test]
+    fn test_read_location_uses_offset() {
+        let entry = DisplayEntry::tool_call(
+            "Read",
+            serde_json::json!({"file_path": "/src/a.rs", "offset": 40, "limit": 20}),
+        );
+        assert_eq!(
+            entry_location(&entry),
+            Some(("/src/a.rs".to_string(), Some(40)))
+        );
+        assert_eq!(line_of("a\nb\nc\n", "c"), Some(3));
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, Local, Utc};
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::logs::diff::{tool_edits, unified_diff};
+use crate::logs::{DisplayEntry, ToolCallResult, format_token_count};
+use crate::ui::Theme;
+
+/// A sub-agent transcript to inline under the tool call that spawned it
+pub struct Subagent {
+    pub name: String,
+    pub entries: Vec<DisplayEntry>,
+}
+
+/// Renders a conversation as a single self-contained HTML page.
+///
+/// `subagents` maps the ID of a `Task`/`Agent` tool call to the transcript it spawned;
+/// each is rendered in a collapsible block below its tool call. Colors come from `theme`
+/// so the page looks like the TUI. No external stylesheets, scripts or fonts are used.
+pub fn to_html(
+    title: &str,
+    subtitle: &str,
+    entries: &[DisplayEntry],
+    subagents: &HashMap<String, Subagent>,
+    theme: &Theme,
+) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<main>\n",
+        escape(title),
+        stylesheet(theme)
+    );
+    let _ = writeln!(out, "<h1>{}</h1>", escape(title));
+    if !subtitle.is_empty() {
+        let _ = writeln!(out, "<p class=\"meta\">{}</p>", escape(subtitle));
+    }
+
+    write_entries(&mut out, entries, subagents);
+
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+fn write_entries(
+    out: &mut String,
+    entries: &[DisplayEntry],
+    subagents: &HashMap<String, Subagent>,
+) {
+    for entry in entries {
+        match entry {
+            DisplayEntry::UserMessage { text, timestamp } => {
+                write_message(out, "user", "User", text, *timestamp);
+            }
+            DisplayEntry::AssistantText { text, timestamp } => {
+                write_message(out, "assistant", "Assistant", text, *timestamp);
+            }
+            DisplayEntry::ToolCall {
+                name,
+                input,
+                id,
+                result,
+                ..
+            } => {
+                out.push_str("<div class=\"tool\">\n");
+                write_tool_call(out, name, input, result.as_ref());
+                if let Some(subagent) = subagents.get(id) {
+                    let _ = writeln!(
+                        out,
+                        "<details class=\"subagent\">\n<summary>Sub-agent: {} \
+                         <span class=\"timestamp\">({} entries)</span></summary>",
+                        escape(&subagent.name),
+                        subagent.entries.len()
+                    );
+                    // Sub-agents can't spawn further agents, so one level is enough
+                    write_entries(out, &subagent.entries, &HashMap::new());
+                    out.push_str("</details>\n");
+                }
+                out.push_str("</div>\n");
+            }
+            DisplayEntry::ToolResult {
+                content, is_error, ..
+            } => {
+                out.push_str("<div class=\"tool\">\n");
+                write_result(out, content, *is_error);
+                out.push_str("</div>\n");
+            }
+            DisplayEntry::Thinking { text, .. } => {
+                let _ = writeln!(
+                    out,
+                    "<details class=\"thinking\">\n<summary>Thinking</summary>\n\
+                     <pre>{}</pre>\n</details>",
+                    escape(text.trim_end())
+                );
+            }
+            DisplayEntry::HookEvent {
+                event,
+                hook_name,
+                command,
+                ..
+            } => {
+                let _ = write!(out, "<p class=\"hook\">Hook: {}", escape(event));
+                if let Some(name) = hook_name {
+                    let _ = write!(out, " ({})", escape(name));
+                }
+                if let Some(command) = command {
+                    let _ = write!(out, " <code>{}</code>", escape(command));
+                }
+                out.push_str("</p>\n");
+            }
+            DisplayEntry::AgentSpawn {
+                agent_type,
+                description,
+                ..
+            } => {
+                let _ = writeln!(
+                    out,
+                    "<p class=\"agent-spawn\">Agent ({}): {}</p>",
+                    escape(agent_type),
+                    escape(description)
+                );
+            }
+            DisplayEntry::Usage { .. } => {}
+            DisplayEntry::BranchPoint {
+                branch, branches, ..
+            } => {
+                let _ = writeln!(
+                    out,
+                    "<p class=\"branch\">Branch {} of {}</p>",
+                    branch, branches
+                );
+            }
+            DisplayEntry::CompactBoundary { pre_tokens, .. } => {
+                out.push_str("<p class=\"compact\">Conversation compacted");
+                if let Some(tokens) = pre_tokens {
+                    let _ = write!(out, " ({} tokens)", format_token_count(*tokens));
+                }
+                out.push_str("</p>\n");
+            }
+            DisplayEntry::ApiError {
+                message,
+                retry_attempt,
+                ..
+            } => {
+                let _ = write!(out, "<p class=\"api-error\">API error: {}", escape(message));
+                if let Some(attempt) = retry_attempt {
+                    let _ = write!(out, " (retry {})", attempt);
+                }
+                out.push_str("</p>\n");
+            }
+            DisplayEntry::LocalCommand { text, .. } => {
+                let _ = writeln!(out, "<pre class=\"local-command\">{}</pre>", escape(text));
+            }
+            DisplayEntry::Metadata { metadata, .. } => {
+                let _ = writeln!(
+                    out,
+                    "<p class=\"metadata\">{}</p>",
+                    escape(&metadata.to_string())
+                );
+            }
+            DisplayEntry::SystemNotice { text, .. } => {
+                let _ = writeln!(out, "<p class=\"system\">System: {}</p>", escape(text));
+            }
+        }
+    }
+}
+
+fn write_message(
+    out: &mut String,
+    class: &str,
+    label: &str,
+    text: &str,
+    timestamp: Option<DateTime<Utc>>,
+) {
+    let _ = writeln!(
+        out,
+        "<section class=\"message {class}\">\n<h2 class=\"{class}-label\">{label}{}</h2>\n\
+         <div class=\"text\">{}</div>\n</section>",
+        time_tag(timestamp),
+        escape(text.trim_end())
+    );
+}
+
+fn write_tool_call(out: &mut String, name: &str, input: &str, result: Option<&ToolCallResult>) {
+    let parsed: Option<serde_json::Value> = serde_json::from_str(input).ok();
+    let field = |key: &str| {
+        parsed
+            .as_ref()
+            .and_then(|v| v.get(key))
+            .and_then(|v| v.as_str())
+    };
+    let header = |detail: &str| {
+        format!(
+            "<div class=\"tool-header\"><span class=\"tool-name\">{}</span> \
+             <span class=\"tool-input\">{}</span></div>\n",
+            escape(name),
+            escape(detail)
+        )
+    };
+
+    if let Some(parsed) = &parsed
+        && let Some((path, edits)) = tool_edits(name, parsed)
+    {
+        out.push_str(&header(&path));
+        for edit in edits {
+            out.push_str(&diff_block(&unified_diff(&path, &edit.old, &edit.new)));
+        }
+    } else {
+        match name {
+            "Bash" => {
+                out.push_str(&header(field("description").unwrap_or("")));
+                let _ = writeln!(
+                    out,
+                    "<pre class=\"input\">$ {}</pre>",
+                    escape(field("command").unwrap_or(""))
+                );
+            }
+            "Read" => out.push_str(&header(field("file_path").unwrap_or("<unknown>"))),
+            "Write" => {
+                out.push_str(&header(field("file_path").unwrap_or("<unknown>")));
+                let _ = writeln!(
+                    out,
+                    "<pre class=\"input\">{}</pre>",
+                    escape(field("content").unwrap_or(""))
+                );
+            }
+            "Grep" | "Glob" => out.push_str(&header(field("pattern").unwrap_or(""))),
+            "Task" | "Agent" => {
+                out.push_str(&header(&format!(
+                    "({}) {}",
+                    field("subagent_type").unwrap_or("unknown"),
+                    field("description").unwrap_or("")
+                )));
+                if let Some(prompt) = field("prompt") {
+                    let _ = writeln!(out, "<pre class=\"prompt\">{}</pre>", escape(prompt));
+                }
+            }
+            _ => {
+                out.push_str(&header(""));
+                if !input.is_empty() {
+                    let _ = writeln!(out, "<pre class=\"input\">{}</pre>", escape(input));
+                }
+            }
+        }
+    }
+
+    if let Some(result) = result {
+        write_result(out, &result.content, result.is_error);
+    }
+}
+
+fn write_result(out: &mut String, content: &str, is_error: bool) {
+    let class = if is_error {
+        "tool-error"
+    } else {
+        "tool-result"
+    };
+    let content = if content.is_empty() {
+        if is_error {
+            "[Error: no output]"
+        } else {
+            "[OK]"
+        }
+    } else {
+        content
+    };
+    let _ = writeln!(
+        out,
+        "<pre class=\"{}\">{}</pre>",
+        class,
+        escape(content.trim_end())
+    );
+}
+
+/// Unified diff with added, removed and hunk-header lines colored separately
+fn diff_block(diff: &str) -> String {
+    let mut out = String::from("<pre class=\"diff\">");
+    for line in diff.lines() {
+        let class = if line.starts_with("+++") || line.starts_with("---") {
+            "diff-file"
+        } else if line.starts_with('+') {
+            "diff-add"
+        } else if line.starts_with('-') {
+            "diff-del"
+        } else if line.starts_with("@@") {
+            "diff-hunk"
+        } else {
+            "diff-context"
+        };
+        let _ = writeln!(out, "<span class=\"{}\">{}</span>", class, escape(line));
+    }
+    out.push_str("</pre>\n");
+    out
+}
+
+fn time_tag(timestamp: Option<DateTime<Utc>>) -> String {
+    timestamp
+        .map(|t| {
+            let local: DateTime<Local> = t.into();
+            format!(
+                " <time class=\"timestamp\" datetime=\"{}\">{}</time>",
+                t.to_rfc3339(),
+                local.format("%Y-%m-%d %H:%M:%S")
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Page stylesheet, with colors taken from the theme
+fn stylesheet(theme: &Theme) -> String {
+    let mut css = String::new();
+    let _ = writeln!(
+        css,
+        "body {{ margin: 0; font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; \
+         font-size: 14px; line-height: 1.45; {} }}",
+        style_css(theme.background)
+    );
+    css.push_str(
+        "main { max-width: 100ch; margin: 0 auto; padding: 1.5em; }\n\
+         h1 { font-size: 1.4em; margin: 0 0 0.2em; }\n\
+         h2 { font-size: 1em; margin: 0 0 0.3em; }\n\
+         pre, .text { white-space: pre-wrap; word-wrap: break-word; margin: 0.3em 0; }\n\
+         pre { font-family: inherit; }\n\
+         .message { margin: 1.2em 0 0.6em; }\n\
+         .tool { margin: 0.6em 0; padding: 0.4em 0.8em; border-left: 2px solid; }\n\
+         details { margin: 0.4em 0; }\n\
+         summary { cursor: pointer; }\n\
+         .subagent { margin-left: 0.5em; padding-left: 1em; border-left: 2px dashed; }\n\
+         .diff span { display: block; }\n\
+         .timestamp { font-weight: normal; }\n",
+    );
+
+    let rules: [(&str, Style); 22] = [
+        ("h1", theme.title_focused),
+        (".meta", theme.timestamp),
+        (".user-label", theme.user_label),
+        (".assistant-label", theme.assistant_label),
+        (".user .text", theme.user_message),
+        (".assistant .text", theme.assistant_text),
+        (".tool-name", theme.tool_name),
+        (".tool-input, .input", theme.tool_input),
+        (".tool-result, .diff-add", theme.tool_result),
+        (".tool-error, .diff-del", theme.tool_error),
+        (".thinking, .prompt", theme.thinking),
+        (".thinking summary", theme.thinking_collapsed),
+        (".hook", theme.hook_event),
+        (".agent-spawn, .subagent > summary", theme.agent_spawn),
+        (".compact", theme.compact_boundary),
+        (".api-error", theme.api_error),
+        (".local-command", theme.local_command),
+        (".system", theme.system_notice),
+        (".metadata", theme.metadata),
+        (".timestamp, .diff-hunk, .diff-file", theme.timestamp),
+        (".diff-context", theme.tool_input),
+        ("summary:hover, .branch", theme.key_hint),
+    ];
+    for (selector, style) in rules {
+        let declarations = style_css(style);
+        if !declarations.is_empty() {
+            let _ = writeln!(css, "{} {{ {} }}", selector, declarations);
+        }
+    }
+    // Borders take the border style's foreground
+    if let Some(color) = theme.border.fg.and_then(css_color) {
+        let _ = writeln!(css, ".tool, .subagent {{ border-color: {}; }}", color);
+    }
+    css
+}
+
+/// CSS declarations for a ratatui style
+fn style_css(style: Style) -> String {
+    let mut declarations = Vec::new();
+    if let Some(color) = style.fg.and_then(css_color) {
+        declarations.push(format!("color: {};", color));
+    }
+    if let Some(color) = style.bg.and_then(css_color) {
+        declarations.push(format!("background-color: {};", color));
+    }
+    if style.add_modifier.contains(Modifier::BOLD) {
+        declarations.push("font-weight: bold;".to_string());
+    }
+    if style.add_modifier.contains(Modifier::ITALIC) {
+        declarations.push("font-style: italic;".to_string());
+    }
+    declarations.join(" ")
+}
+
+/// CSS color for a terminal color. Named colors use the xterm palette; colors that
+/// depend on the terminal (Reset, indexed) have no CSS equivalent.
+fn css_color(color: Color) -> Option<String> {
+    let hex = match color {
+        Color::Rgb(r, g, b) => return Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        Color::Black => "#000000",
+        Color::Red => "#cd0000",
+        Color::Green => "#00cd00",
+        Color::Yellow => "#cdcd00",
+        Color::Blue => "#0000ee",
+        Color::Magenta => "#cd00cd",
+        Color::Cyan => "#00cdcd",
+        Color::Gray => "#e5e5e5",
+        Color::DarkGray => "#7f7f7f",
+        Color::LightRed => "#ff0000",
+        Color::LightGreen => "#00ff00",
+        Color::LightYellow => "#ffff00",
+        Color::LightBlue => "#5c5cff",
+        Color::LightMagenta => "#ff00ff",
+        Color::LightCyan => "#00ffff",
+        Color::White => "#ffffff",
+        Color::Reset | Color::Indexed(_) => return None,
+    };
+    Some(hex.to_string())
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task_call(id: &str) -> DisplayEntry {
+        DisplayEntry::tool_call(
+            "Task",
+            serde_json::json!({"description": "Explore", "subagent_type": "Explore", "prompt": "Look around"}),
+        )
+        .with_id(id)
+    }
+
+    #[test]
+    fn test_escapes_text() {
+        let entries = vec![DisplayEntry::UserMessage {
+            text: "<script>alert('x')</script>".to_string(),
+            timestamp: None,
+        }];
+        let html = to_html("T", "", &entries, &HashMap::new(), &Theme::default());
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn test_subagent_inlined_under_task_call() {
+        let entries = vec![task_call("toolu_1"), task_call("toolu_2")];
+        let mut subagents = HashMap::new();
+        subagents.insert(
+            "toolu_1".to_string(),
+            Subagent {
+                name: "Explore".to_string(),
+                entries: vec![DisplayEntry::AssistantText {
+                    text: "Found it".to_string(),
+                    timestamp: None,
+                }],
+            },
+        );
+        let html = to_html("T", "", &entries, &subagents, &Theme::default());
+        assert_eq!(html.matches("<details class=\"subagent\">").count(), 1);
+        let task = html.find("Look around").unwrap();
+        let subagent = html.find("Found it").unwrap();
+        assert!(task < subagent);
+    }
+
+    #[test]
+    fn test_theme_colors_in_stylesheet() {
+        let theme = Theme {
+            tool_name: Style::default().fg(Color::Rgb(0x12, 0x34, 0x56)),
+            ..Default::default()
+        };
+        let html = to_html("T", "", &[], &HashMap::new(), &theme);
+        assert!(html.contains(".tool-name { color: #123456; }"));
+    }
+
+    #[test]
+    fn test_edit_diff_lines_classed() {
+        let block = diff_block(&unified_diff("a.rs", "x\n", "y\n"));
+        assert!(block.contains("<span class=\"diff-del\">-x</span>"));
+        assert!(block.contains("<span class=\"diff-add\">+y</span>"));
+    }
+}
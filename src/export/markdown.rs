@@ -0,0 +1,368 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Local, Utc};
+
+use crate::logs::diff::{tool_edits, unified_diff};
+use crate::logs::{DisplayEntry, ToolCallResult, format_token_count};
+
+/// Who a run of entries belongs to; a heading is written whenever it changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Turn {
+    User,
+    Assistant,
+}
+
+/// Renders a conversation as Markdown.
+///
+/// Each turn gets a `##` heading and each tool call a `###` heading. Bash commands,
+/// file contents and tool output go in fenced code blocks, `Edit`/`MultiEdit` calls become
+/// unified diffs, and thinking blocks are wrapped in collapsible `<details>` elements.
+pub fn to_markdown(title: &str, subtitle: &str, entries: &[DisplayEntry]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", title);
+    if !subtitle.is_empty() {
+        let _ = writeln!(out, "_{}_\n", subtitle);
+    }
+
+    let mut turn = None;
+    for entry in entries {
+        let entry_turn = match entry {
+            DisplayEntry::UserMessage { .. } => Some(Turn::User),
+            // Hooks, usage, branch markers and system notices don't start a turn of their own
+            DisplayEntry::HookEvent { .. }
+            | DisplayEntry::Usage { .. }
+            | DisplayEntry::BranchPoint { .. }
+            | DisplayEntry::CompactBoundary { .. }
+            | DisplayEntry::ApiError { .. }
+            | DisplayEntry::LocalCommand { .. }
+            | DisplayEntry::Metadata { .. }
+            | DisplayEntry::SystemNotice { .. } => None,
+            _ => Some(Turn::Assistant),
+        };
+        if let Some(entry_turn) = entry_turn
+            && turn != Some(entry_turn)
+        {
+            turn = Some(entry_turn);
+            let label = match entry_turn {
+                Turn::User => "User",
+                Turn::Assistant => "Assistant",
+            };
+            let _ = writeln!(out, "## {}{}\n", label, heading_time(entry.timestamp()));
+        }
+
+        write_entry(&mut out, entry);
+    }
+
+    out
+}
+
+/// A single entry as it appears in an exported conversation, without the turn heading
+pub fn entry_to_markdown(entry: &DisplayEntry) -> String {
+    let mut out = String::new();
+    write_entry(&mut out, entry);
+    out
+}
+
+fn write_entry(out: &mut String, entry: &DisplayEntry) {
+    match entry {
+        DisplayEntry::UserMessage { text, .. } | DisplayEntry::AssistantText { text, .. } => {
+            let _ = writeln!(out, "{}\n", text.trim_end());
+        }
+        DisplayEntry::ToolCall {
+            name,
+            input,
+            result,
+            ..
+        } => write_tool_call(out, name, input, result.as_ref()),
+        DisplayEntry::ToolResult {
+            content, is_error, ..
+        } => write_result(out, content, *is_error),
+        DisplayEntry::Thinking { text, .. } => {
+            let _ = writeln!(
+                out,
+                "<details>\n<summary>Thinking</summary>\n\n{}\n\n</details>\n",
+                text.trim_end()
+            );
+        }
+        DisplayEntry::HookEvent {
+            event,
+            hook_name,
+            command,
+            ..
+        } => {
+            let _ = write!(out, "> Hook: {}", event);
+            if let Some(name) = hook_name {
+                let _ = write!(out, " ({})", name);
+            }
+            if let Some(command) = command {
+                let _ = write!(out, " {}", inline_code(command));
+            }
+            out.push_str("\n\n");
+        }
+        DisplayEntry::AgentSpawn {
+            agent_type,
+            description,
+            ..
+        } => {
+            let _ = writeln!(out, "> Agent ({}): {}\n", agent_type, description);
+        }
+        DisplayEntry::Usage { .. } => {}
+        DisplayEntry::BranchPoint {
+            branch, branches, ..
+        } => {
+            let _ = writeln!(out, "> Branch {} of {}\n", branch, branches);
+        }
+        DisplayEntry::CompactBoundary { pre_tokens, .. } => {
+            out.push_str("---\n\n> Conversation compacted");
+            if let Some(tokens) = pre_tokens {
+                let _ = write!(out, " ({} tokens)", format_token_count(*tokens));
+            }
+            out.push_str("\n\n");
+        }
+        DisplayEntry::ApiError {
+            message,
+            retry_attempt,
+            ..
+        } => {
+            let _ = write!(out, "> API error: {}", message);
+            if let Some(attempt) = retry_attempt {
+                let _ = write!(out, " (retry {})", attempt);
+            }
+            out.push_str("\n\n");
+        }
+        DisplayEntry::LocalCommand { text, .. } => out.push_str(&fence("", text)),
+        DisplayEntry::Metadata { metadata, .. } => {
+            let _ = writeln!(out, "> {}\n", metadata);
+        }
+        DisplayEntry::SystemNotice { text, .. } => {
+            let _ = writeln!(out, "> System: {}\n", text);
+        }
+    }
+}
+
+fn write_tool_call(out: &mut String, name: &str, input: &str, result: Option<&ToolCallResult>) {
+    let parsed: Option<serde_json::Value> = serde_json::from_str(input).ok();
+    let field = |key: &str| {
+        parsed
+            .as_ref()
+            .and_then(|v| v.get(key))
+            .and_then(|v| v.as_str())
+    };
+
+    if let Some(parsed) = &parsed
+        && let Some((path, edits)) = tool_edits(name, parsed)
+    {
+        let _ = writeln!(out, "### {}: {}\n", name, inline_code(&path));
+        for edit in edits {
+            out.push_str(&fence("diff", &unified_diff(&path, &edit.old, &edit.new)));
+        }
+    } else {
+        match name {
+            "Bash" => {
+                let _ = writeln!(out, "### Bash: {}\n", field("description").unwrap_or(""));
+                out.push_str(&fence("bash", field("command").unwrap_or("")));
+            }
+            "Read" => {
+                let _ = writeln!(
+                    out,
+                    "### Read: {}\n",
+                    inline_code(field("file_path").unwrap_or("<unknown>"))
+                );
+            }
+            "Write" => {
+                let path = field("file_path").unwrap_or("<unknown>");
+                let _ = writeln!(out, "### Write: {}\n", inline_code(path));
+                out.push_str(&fence(fence_language(path), field("content").unwrap_or("")));
+            }
+            "Grep" | "Glob" => {
+                let _ = writeln!(
+                    out,
+                    "### {}: {}\n",
+                    name,
+                    inline_code(field("pattern").unwrap_or(""))
+                );
+            }
+            "Task" | "Agent" => {
+                let _ = writeln!(
+                    out,
+                    "### {} ({}): {}\n",
+                    name,
+                    field("subagent_type").unwrap_or("unknown"),
+                    field("description").unwrap_or("")
+                );
+                if let Some(prompt) = field("prompt") {
+                    let _ = writeln!(out, "{}\n", blockquote(prompt));
+                }
+            }
+            _ => {
+                let _ = writeln!(out, "### Tool: {}\n", name);
+                if !input.is_empty() {
+                    out.push_str(&fence("json", input));
+                }
+            }
+        }
+    }
+
+    if let Some(result) = result {
+        write_result(out, &result.content, result.is_error);
+    }
+}
+
+fn write_result(out: &mut String, content: &str, is_error: bool) {
+    let label = if is_error { "**Error:**" } else { "Output:" };
+    if content.is_empty() {
+        let _ = writeln!(out, "{} _(no output)_\n", label);
+    } else {
+        let _ = writeln!(out, "{}\n", label);
+        out.push_str(&fence("text", content));
+    }
+}
+
+/// Wraps `body` in a code fence longer than any backtick run inside it
+fn fence(language: &str, body: &str) -> String {
+    let longest_run = body.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let ticks = "`".repeat(longest_run.max(2) + 1);
+    format!(
+        "{ticks}{language}\n{}\n{ticks}\n\n",
+        body.trim_end_matches('\n')
+    )
+}
+
+/// Inline code span delimited by more backticks than any run inside `text`
+fn inline_code(text: &str) -> String {
+    let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let ticks = "`".repeat(longest_run + 1);
+    if longest_run > 0 {
+        format!("{ticks} {text} {ticks}")
+    } else {
+        format!("{ticks}{text}{ticks}")
+    }
+}
+
+fn blockquote(text: &str) -> String {
+    text.trim_end()
+        .lines()
+        .map(|line| format!("> {}", line).trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn heading_time(timestamp: Option<DateTime<Utc>>) -> String {
+    timestamp
+        .map(|t| {
+            let local: DateTime<Local> = t.into();
+            format!(" ({})", local.format("%Y-%m-%d %H:%M:%S"))
+        })
+        .unwrap_or_default()
+}
+
+/// Code fence info string for a file, from its extension
+pub fn fence_language(path: &str) -> &'static str {
+    let extension = path.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("");
+    match extension {
+        "rs" => "rust",
+        "py" => "python",
+        "js" | "mjs" | "cjs" => "javascript",
+        "ts" => "typescript",
+        "tsx" => "tsx",
+        "jsx" => "jsx",
+        "go" => "go",
+        "rb" => "ruby",
+        "java" => "java",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "hpp" => "cpp",
+        "sh" | "bash" | "zsh" => "bash",
+        "json" => "json",
+        "toml" => "toml",
+        "yaml" | "yml" => "yaml",
+        "md" => "markdown",
+        "html" => "html",
+        "css" => "css",
+        "sql" => "sql",
+        "nix" => "nix",
+        "ex" | "exs" => "elixir",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_call(name: &str, input: serde_json::Value, result: Option<&str>) -> DisplayEntry {
+        let call = DisplayEntry::tool_call(name, input);
+        match result {
+            Some(content) => call.with_result(content, false, None),
+            None => call,
+        }
+    }
+
+    #[test]
+    fn test_turn_headings() {
+        let entries = vec![
+            DisplayEntry::UserMessage {
+                text: "Hi".to_string(),
+                timestamp: None,
+            },
+            DisplayEntry::AssistantText {
+                text: "Hello".to_string(),
+                timestamp: None,
+            },
+            DisplayEntry::AssistantText {
+                text: "More".to_string(),
+                timestamp: None,
+            },
+        ];
+        let md = to_markdown("Session", "", &entries);
+        assert_eq!(
+            md,
+            "# Session\n\n## User\n\nHi\n\n## Assistant\n\nHello\n\nMore\n\n"
+        );
+    }
+
+    #[test]
+    fn test_bash_command_and_output_fenced() {
+        let entries = vec![tool_call(
+            "Bash",
+            serde_json::json!({"command": "ls", "description": "List files"}),
+            Some("a.txt"),
+        )];
+        let md = to_markdown("S", "", &entries);
+        assert!(md.contains("### Bash: List files\n\n```bash\nls\n```\n"));
+        assert!(md.contains("Output:\n\n```text\na.txt\n```\n"));
+    }
+
+    #[test]
+    fn test_edit_becomes_diff() {
+        let entries = vec![tool_call(
+            "Edit",
+            serde_json::json!({"file_path": "/src/a.rs", "old_string": "x\n", "new_string": "y\n"}),
+            None,
+        )];
+        let md = to_markdown("S", "", &entries);
+        assert!(md.contains("### Edit: `/src/a.rs`"));
+        assert!(md.contains("```diff\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1 +1 @@\n-x\n+y\n```"));
+    }
+
+    #[test]
+    fn test_thinking_is_collapsible() {
+        let entries = vec![DisplayEntry::Thinking {
+            text: "hmm".to_string(),
+            timestamp: None,
+        }];
+        let md = to_markdown("S", "", &entries);
+        assert!(md.contains("<details>\n<summary>Thinking</summary>\n\nhmm\n\n</details>"));
+    }
+
+    #[test]
+    fn test_fence_outgrows_backticks() {
+        assert_eq!(fence("", "a ``` b"), "````\na ``` b\n````\n\n");
+    }
+
+    #[test]
+    fn test_inline_code_outgrows_backticks() {
+        assert_eq!(inline_code("ls"), "`ls`");
+        assert_eq!(inline_code("echo `date`"), "`` echo `date` ``");
+        assert_eq!(inline_code("a `` b"), "``` a `` b ```");
+    }
+}
@@ -0,0 +1,214 @@
+pub mod html;
+pub mod markdown;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use clap::ValueEnum;
+
+use crate::logs::{
+    Agent, DisplayEntry, Session, discover_agents, find_agent, find_session, latest_session,
+    merge_tool_results, parse_jsonl_file,
+};
+use crate::ui::Theme;
+
+pub use html::{Subagent, to_html};
+pub use markdown::{entry_to_markdown, to_markdown};
+
+/// Output format for `claude-tail export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Markdown,
+    /// Single offline HTML page with sub-agents inlined, styled with the active theme
+    Html,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// Arguments for `claude-tail export`
+#[derive(Debug, clap::Args)]
+pub struct ExportArgs {
+    /// Session ID (or unique prefix), or path to a session .jsonl file
+    #[arg(required_unless_present = "latest")]
+    session: Option<String>,
+
+    /// Export the session with the most recent activity
+    #[arg(long, conflicts_with = "session")]
+    latest: bool,
+
+    /// Agent to export: "main" (default), or a sub-agent ID or name
+    #[arg(short, long, default_value = "main")]
+    agent: String,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = ExportFormat::Markdown)]
+    format: ExportFormat,
+
+    /// File to write; prints to stdout if omitted
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+/// Runs `claude-tail export`
+pub fn run(args: &ExportArgs, theme: &Theme) -> Result<()> {
+    let session = match &args.session {
+        Some(query) if !args.latest => find_session(query)?,
+        _ => latest_session()?,
+    };
+    let agent = find_agent(&session, &args.agent)?;
+    let content = export_agent(&session, &agent, args.format, theme)?;
+
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, content)?;
+            eprintln!("Exported {} to {}", agent.display_name, path.display());
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+/// Parses an agent log into display entries, with tool results merged into their calls
+pub fn load_entries(log_path: &Path) -> Result<Vec<DisplayEntry>> {
+    let result = parse_jsonl_file(log_path)?;
+    Ok(merge_tool_results(result.latest_branch()))
+}
+
+/// Exports one agent's conversation of a session in the given format
+pub fn export_agent(
+    session: &Session,
+    agent: &Agent,
+    format: ExportFormat,
+    theme: &Theme,
+) -> Result<String> {
+    let entries = load_entries(&agent.log_path)?;
+    let title = session
+        .title()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Session {}", session.id));
+
+    Ok(match format {
+        ExportFormat::Markdown => {
+            let subtitle = format!("Session `{}` · Agent: {}", session.id, agent.display_name);
+            to_markdown(&title, &subtitle, &entries)
+        }
+        ExportFormat::Html => {
+            let subtitle = format!("Session {} · Agent: {}", session.id, agent.display_name);
+            // Only the main agent spawns sub-agents
+            let subagents = if agent.is_main {
+                link_subagents(&entries, &discover_agents(session)?)
+            } else {
+                HashMap::new()
+            };
+            to_html(&title, &subtitle, &entries, &subagents, theme)
+        }
+    })
+}
+
+/// Matches sub-agent logs to the `Task`/`Agent` tool calls that spawned them.
+///
+/// Recent Claude Code versions end the tool result with `agentId: <id>`, which names the
+/// `agent-<id>.jsonl` log directly. Otherwise a sub-agent is matched by its first user
+/// message, which is the prompt it was given.
+fn link_subagents(entries: &[DisplayEntry], agents: &[Agent]) -> HashMap<String, Subagent> {
+    let mut remaining: Vec<(&Agent, Option<Vec<DisplayEntry>>)> = agents
+        .iter()
+        .filter(|agent| !agent.is_main)
+        .map(|agent| (agent, None))
+        .collect();
+    let mut linked = HashMap::new();
+
+    for entry in entries {
+        let DisplayEntry::ToolCall {
+            name,
+            input,
+            id,
+            result,
+            ..
+        } = entry
+        else {
+            continue;
+        };
+        if name != "Task" && name != "Agent" {
+            continue;
+        }
+
+        let by_id = result
+            .as_ref()
+            .and_then(|result| result_agent_id(&result.content))
+            .and_then(|agent_id| remaining.iter().position(|(a, _)| a.id == agent_id));
+
+        let position = by_id.or_else(|| {
+            let prompt = serde_json::from_str::<serde_json::Value>(input)
+                .ok()?
+                .get("prompt")?
+                .as_str()?
+                .trim()
+                .to_string();
+            remaining.iter_mut().position(|(agent, loaded)| {
+                let entries =
+                    loaded.get_or_insert_with(|| load_entries(&agent.log_path).unwrap_or_default());
+                entries.iter().find_map(|e| match e {
+                    DisplayEntry::UserMessage { text, .. } => Some(text.trim() == prompt),
+                    _ => None,
+                }) == Some(true)
+            })
+        });
+
+        if let Some(position) = position {
+            let (agent, loaded) = remaining.remove(position);
+            let entries =
+                loaded.unwrap_or_else(|| load_entries(&agent.log_path).unwrap_or_default());
+            linked.insert(
+                id.clone(),
+                Subagent {
+                    name: agent.display_name.clone(),
+                    entries,
+                },
+            );
+        }
+    }
+
+    linked
+}
+
+/// Extracts the sub-agent ID from a Task result ending in "agentId: <id> ..."
+fn result_agent_id(content: &str) -> Option<&str> {
+    let start = content.rfind("agentId: ")? + "agentId: ".len();
+    let id = content[start..]
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .next()?;
+    (!id.is_empty()).then_some(id)
+}
+
+/// Default file name for an export, e.g. `claude-1a2b3c4d-main.md`
+pub fn default_file_name(session: &Session, agent: &Agent, format: ExportFormat) -> PathBuf {
+    let short_id: String = session.id.chars().take(8).collect();
+    PathBuf::from(format!(
+        "claude-{}-{}.{}",
+        short_id,
+        agent.id,
+        format.extension()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_agent_id() {
+        let content = "Summary of findings.\nagentId: a356e17 (for resuming to continue this agent's work if needed)";
+        assert_eq!(result_agent_id(content), Some("a356e17"));
+        assert_eq!(result_agent_id("no id here"), None);
+        assert_eq!(result_agent_id("agentId: "), None);
+    }
+}
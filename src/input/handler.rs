@@ -1,191 +1,418 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::process::Command;
 
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use ratatui::widgets::ListState;
+
+use super::keymap::{Key, KeyAction, Resolution, Scope};
 use crate::app::{App, FocusPane};
+use crate::ui::{AppLayout, FocusedPane, SearchDirection, content_row};
+
+/// Lines the conversation scrolls per notch of the mouse wheel
+const WHEEL_SCROLL_LINES: usize = 3;
 
 pub enum Action {
     Quit,
     None,
     Redraw,
+    /// Hand the terminal to a program, such as an editor, until it exits
+    Suspend(Command),
 }
 
 pub fn handle_key_event(app: &mut App, key: KeyEvent) -> Action {
-    // Global keybindings
-    match key.code {
-        KeyCode::Char('q') => return Action::Quit,
-        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Action::Quit,
-        KeyCode::Tab => {
-            app.cycle_focus();
-            return Action::Redraw;
-        }
-        KeyCode::BackTab => {
-            app.cycle_focus_reverse();
-            return Action::Redraw;
-        }
-        KeyCode::Char('t') => {
-            app.toggle_thinking();
-            return Action::Redraw;
-        }
-        KeyCode::Char('e') => {
-            app.toggle_tool_expansion();
-            return Action::Redraw;
+    // Notices last until the next key press
+    app.status_message = None;
+
+    // While the search prompt is open, all keys edit the query
+    if app.search.is_prompting() {
+        return handle_search_input(app, key);
+    }
+
+    if app.global_search.open {
+        return handle_global_search_input(app, key);
+    }
+
+    if app.branch_picker.open {
+        return handle_branch_picker_input(app, key);
+    }
+
+    if app.slowest_tools.open {
+        return handle_slowest_tools_input(app, key);
+    }
+
+    if app.file_index.open {
+        return handle_file_index_input(app, key);
+    }
+
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Action::Quit;
+    }
+
+    app.pending_keys.push(Key::from(key));
+    match app
+        .config
+        .keys
+        .resolve(key_scopes(app.focus), &app.pending_keys)
+    {
+        Resolution::Action(action) => {
+            app.pending_keys.clear();
+            run_key_action(app, action)
         }
-        KeyCode::Char('f') => {
-            app.conversation_state.toggle_follow();
-            return Action::Redraw;
+        Resolution::Pending => {
+            let pressed: Vec<String> = app.pending_keys.iter().map(Key::to_string).collect();
+            app.status_message = Some(format!("{} …", pressed.join(" ")));
+            Action::Redraw
         }
-        KeyCode::Char('?') => {
-            app.show_help = !app.show_help;
-            return Action::Redraw;
+        Resolution::Unbound if app.pending_keys.len() > 1 => {
+            // The chord went nowhere; the last key may still mean something on its own
+            app.pending_keys.clear();
+            match handle_key_event(app, key) {
+                Action::None => Action::Redraw,
+                action => action,
+            }
         }
-        KeyCode::Char('r') => {
-            app.refresh_projects();
-            app.refresh_sessions();
-            return Action::Redraw;
+        Resolution::Unbound => {
+            app.pending_keys.clear();
+            Action::None
         }
-        _ => {}
     }
+}
 
-    // Pane-specific keybindings
-    match app.focus {
-        FocusPane::Projects => handle_projects_input(app, key),
-        FocusPane::Sessions => handle_sessions_input(app, key),
-        FocusPane::Agents => handle_agents_input(app, key),
-        FocusPane::Conversation => handle_conversation_input(app, key),
+/// Scopes whose bindings apply in a pane, most specific first
+fn key_scopes(focus: FocusPane) -> &'static [Scope] {
+    match focus {
+        FocusPane::Projects => &[Scope::Projects, Scope::Lists, Scope::Global],
+        FocusPane::Sessions => &[Scope::Sessions, Scope::Lists, Scope::Global],
+        FocusPane::Agents => &[Scope::Agents, Scope::Lists, Scope::Global],
+        FocusPane::Conversation => &[Scope::Conversation, Scope::Global],
     }
 }
 
-fn handle_projects_input(app: &mut App, key: KeyEvent) -> Action {
-    match key.code {
-        KeyCode::Char('j') | KeyCode::Down => {
-            app.project_state.next(app.projects.len());
-            app.load_sessions_for_selected_project();
-            Action::Redraw
+/// Perform a bound action. Movement acts on the focused pane; the rest do the same wherever
+/// they are bound.
+fn run_key_action(app: &mut App, action: KeyAction) -> Action {
+    let viewport_height = app.viewport_height.unwrap_or(20);
+
+    match action {
+        KeyAction::Quit => return Action::Quit,
+        KeyAction::NextPane => app.cycle_focus(),
+        KeyAction::PreviousPane => app.cycle_focus_reverse(),
+        KeyAction::ToggleHelp => app.show_help = !app.show_help,
+        KeyAction::ToggleThinking => app.toggle_thinking(),
+        KeyAction::ToggleToolExpansion => app.toggle_tool_expansion(),
+        KeyAction::ToggleFullDiffs => app.toggle_full_diffs(),
+        KeyAction::ToggleMetadata => app.toggle_metadata(),
+        KeyAction::ToggleFollow => app.conversation_state.toggle_follow(),
+        KeyAction::ToggleStats => app.toggle_stats(),
+        KeyAction::SearchAllSessions => app.global_search.show(),
+        KeyAction::SwitchBranch => app.open_branch_picker(),
+        KeyAction::SlowestTools => app.open_slowest_tools(),
+        KeyAction::FilesTouched => app.open_file_index(),
+        KeyAction::Export => app.export_selected_agent(),
+        KeyAction::Refresh => {
+            app.refresh_projects();
+            app.refresh_sessions();
         }
-        KeyCode::Char('k') | KeyCode::Up => {
-            app.project_state.previous(app.projects.len());
-            app.load_sessions_for_selected_project();
-            Action::Redraw
+        KeyAction::Down | KeyAction::Up | KeyAction::Top | KeyAction::Bottom
+            if app.focus != FocusPane::Conversation =>
+        {
+            move_list_selection(app, action)
         }
-        KeyCode::Char('g') => {
-            app.project_state.first();
-            app.load_sessions_for_selected_project();
-            Action::Redraw
+        KeyAction::Down => app.conversation_state.scroll_down(1, viewport_height),
+        KeyAction::Up => app.conversation_state.scroll_up(1),
+        KeyAction::HalfPageDown => app
+            .conversation_state
+            .scroll_down(viewport_height / 2, viewport_height),
+        KeyAction::HalfPageUp => app.conversation_state.scroll_up(viewport_height / 2),
+        KeyAction::PageDown => app
+            .conversation_state
+            .scroll_down(viewport_height, viewport_height),
+        KeyAction::PageUp => app.conversation_state.scroll_up(viewport_height),
+        KeyAction::Top => app.conversation_state.scroll_to_top(),
+        KeyAction::Bottom => app.conversation_state.scroll_to_bottom(viewport_height),
+        KeyAction::Select => match app.focus {
+            FocusPane::Projects => app.focus = FocusPane::Sessions,
+            FocusPane::Sessions => app.focus = FocusPane::Agents,
+            FocusPane::Agents => app.focus = FocusPane::Conversation,
+            FocusPane::Conversation => app.toggle_selected_entry(),
+        },
+        KeyAction::ResumeSession => {
+            return match app.resume_command_for_selected_session() {
+                Some(command) => Action::Suspend(command),
+                None => Action::Redraw,
+            };
         }
-        KeyCode::Char('G') => {
-            app.project_state.last(app.projects.len());
-            app.load_sessions_for_selected_project();
-            Action::Redraw
+        KeyAction::SearchForward => app.start_search(SearchDirection::Forward),
+        KeyAction::SearchBackward => app.start_search(SearchDirection::Backward),
+        KeyAction::NextMatch => app.search_next(false),
+        KeyAction::PreviousMatch => app.search_next(true),
+        KeyAction::NextEntry => app.conversation_state.select_next_entry(viewport_height),
+        KeyAction::PreviousEntry => app
+            .conversation_state
+            .select_previous_entry(viewport_height),
+        KeyAction::ToggleEntry => app.toggle_selected_entry(),
+        KeyAction::YankEntry => app.yank_selected_entry(false),
+        KeyAction::YankTarget => app.yank_selected_entry(true),
+        KeyAction::OpenInEditor => {
+            return match app.editor_for_selected_entry() {
+                Some(command) => Action::Suspend(command),
+                None => Action::Redraw,
+            };
         }
-        KeyCode::Enter => {
-            app.focus = FocusPane::Sessions;
-            Action::Redraw
+        KeyAction::Clear => {
+            app.clear_search();
+            app.conversation_state.selected_entry = None;
         }
-        _ => Action::None,
     }
+    Action::Redraw
 }
 
-fn handle_sessions_input(app: &mut App, key: KeyEvent) -> Action {
-    match key.code {
-        KeyCode::Char('j') | KeyCode::Down => {
-            app.session_state.next(app.sessions.len());
-            app.load_agents_for_selected_session();
-            app.load_conversation_for_selected_agent();
-            Action::Redraw
-        }
-        KeyCode::Char('k') | KeyCode::Up => {
-            app.session_state.previous(app.sessions.len());
-            app.load_agents_for_selected_session();
-            app.load_conversation_for_selected_agent();
-            Action::Redraw
+/// Move the selection in the focused list and load what it leads to
+fn move_list_selection(app: &mut App, action: KeyAction) {
+    match app.focus {
+        FocusPane::Projects => {
+            let state = &mut app.project_state;
+            match action {
+                KeyAction::Down => state.next(app.projects.len()),
+                KeyAction::Up => state.previous(app.projects.len()),
+                KeyAction::Top => state.first(),
+                _ => state.last(app.projects.len()),
+            }
+            app.load_sessions_for_selected_project();
         }
-        KeyCode::Char('g') => {
-            app.session_state.first();
+        FocusPane::Sessions => {
+            let state = &mut app.session_state;
+            match action {
+                KeyAction::Down => state.next(app.sessions.len()),
+                KeyAction::Up => state.previous(app.sessions.len()),
+                KeyAction::Top => state.first(),
+                _ => state.last(app.sessions.len()),
+            }
             app.load_agents_for_selected_session();
             app.load_conversation_for_selected_agent();
-            Action::Redraw
         }
-        KeyCode::Char('G') => {
-            app.session_state.last(app.sessions.len());
-            app.load_agents_for_selected_session();
+        FocusPane::Agents => {
+            let state = &mut app.agent_state;
+            match action {
+                KeyAction::Down => state.next(app.agents.len()),
+                KeyAction::Up => state.previous(app.agents.len()),
+                KeyAction::Top => state.first(),
+                _ => state.last(app.agents.len()),
+            }
             app.load_conversation_for_selected_agent();
-            Action::Redraw
-        }
-        KeyCode::Enter => {
-            app.focus = FocusPane::Agents;
-            Action::Redraw
         }
-        _ => Action::None,
+        FocusPane::Conversation => {}
     }
 }
 
-fn handle_agents_input(app: &mut App, key: KeyEvent) -> Action {
-    match key.code {
-        KeyCode::Char('j') | KeyCode::Down => {
-            app.agent_state.next(app.agents.len());
-            app.load_conversation_for_selected_agent();
-            Action::Redraw
-        }
-        KeyCode::Char('k') | KeyCode::Up => {
-            app.agent_state.previous(app.agents.len());
-            app.load_conversation_for_selected_agent();
-            Action::Redraw
-        }
-        KeyCode::Char('g') => {
-            app.agent_state.first();
-            app.load_conversation_for_selected_agent();
+pub fn handle_mouse_event(app: &mut App, mouse: MouseEvent) -> Action {
+    // Overlays and the search prompt own the input until they are closed
+    if app.search.is_prompting()
+        || app.global_search.open
+        || app.branch_picker.open
+        || app.slowest_tools.open
+        || app.file_index.open
+    {
+        return Action::None;
+    }
+    let Some(layout) = app.layout else {
+        return Action::None;
+    };
+
+    match mouse.kind {
+        MouseEventKind::Down(_) if app.show_help => {
+            app.show_help = false;
             Action::Redraw
         }
-        KeyCode::Char('G') => {
-            app.agent_state.last(app.agents.len());
-            app.load_conversation_for_selected_agent();
+        MouseEventKind::ScrollDown
+            if layout.pane_at(mouse.column, mouse.row) == Some(FocusedPane::Conversation) =>
+        {
+            let viewport_height = app.viewport_height.unwrap_or(20);
+            app.conversation_state
+                .scroll_down(WHEEL_SCROLL_LINES, viewport_height);
             Action::Redraw
         }
-        KeyCode::Enter => {
-            app.focus = FocusPane::Conversation;
+        MouseEventKind::ScrollUp
+            if layout.pane_at(mouse.column, mouse.row) == Some(FocusedPane::Conversation) =>
+        {
+            app.conversation_state.scroll_up(WHEEL_SCROLL_LINES);
             Action::Redraw
         }
+        MouseEventKind::Down(MouseButton::Left) => match layout.pane_at(mouse.column, mouse.row) {
+            Some(pane) => handle_click(app, &layout, pane, mouse.row),
+            None => Action::None,
+        },
         _ => Action::None,
     }
 }
 
-fn handle_conversation_input(app: &mut App, key: KeyEvent) -> Action {
-    let viewport_height = app.viewport_height.unwrap_or(20);
+/// Focus the clicked pane, then act on the row under the pointer: select a list item, or
+/// expand or collapse a tool call in the conversation
+fn handle_click(app: &mut App, layout: &AppLayout, pane: FocusedPane, row: u16) -> Action {
+    let focus = match pane {
+        FocusedPane::Projects => FocusPane::Projects,
+        FocusedPane::Sessions => FocusPane::Sessions,
+        FocusedPane::Agents => FocusPane::Agents,
+        FocusedPane::Conversation => FocusPane::Conversation,
+    };
+    // Unfocused lists are collapsed, so the first click only expands them
+    if focus != app.focus && focus != FocusPane::Conversation {
+        app.focus = focus;
+        return Action::Redraw;
+    }
+    app.focus = focus;
 
-    match key.code {
-        KeyCode::Char('j') | KeyCode::Down => {
-            app.conversation_state.scroll_down(1, viewport_height);
-            Action::Redraw
+    match focus {
+        FocusPane::Projects => {
+            if let Some(i) = clicked_item(
+                layout.projects,
+                row,
+                &app.project_state.list_state,
+                app.projects.len(),
+            ) && app.project_state.selected() != Some(i)
+            {
+                app.project_state.select(Some(i));
+                app.load_sessions_for_selected_project();
+            }
         }
-        KeyCode::Char('k') | KeyCode::Up => {
-            app.conversation_state.scroll_up(1);
-            Action::Redraw
+        FocusPane::Sessions => {
+            if let Some(i) = clicked_item(
+                layout.sessions,
+                row,
+                &app.session_state.list_state,
+                app.sessions.len(),
+            ) && app.session_state.selected() != Some(i)
+            {
+                app.session_state.select(Some(i));
+                app.load_agents_for_selected_session();
+                app.load_conversation_for_selected_agent();
+            }
         }
-        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.conversation_state
-                .scroll_down(viewport_height / 2, viewport_height);
-            Action::Redraw
+        FocusPane::Agents => {
+            if let Some(i) = clicked_item(
+                layout.agents,
+                row,
+                &app.agent_state.list_state,
+                app.agents.len(),
+            ) && app.agent_state.selected() != Some(i)
+            {
+                app.agent_state.select(Some(i));
+                app.load_conversation_for_selected_agent();
+            }
         }
-        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.conversation_state.scroll_up(viewport_height / 2);
-            Action::Redraw
+        FocusPane::Conversation => {
+            let state = &app.conversation_state;
+            if !app.show_stats
+                && let Some(line) = content_row(layout.conversation, row)
+                    .map(|line| state.scroll_offset + line)
+                    .filter(|&line| line < state.total_lines)
+                && let Some(entry_idx) = state.entry_at_line(line)
+            {
+                // Clicking moves the cursor and toggles the entry, like Enter
+                app.conversation_state.selected_entry = Some(entry_idx);
+                app.toggle_selected_entry();
+            }
         }
-        KeyCode::PageDown => {
-            app.conversation_state
-                .scroll_down(viewport_height, viewport_height);
-            Action::Redraw
+    }
+    Action::Redraw
+}
+
+/// Index of the list item on a screen row, accounting for the list's scroll offset
+fn clicked_item(area: Rect, row: u16, list_state: &ListState, len: usize) -> Option<usize> {
+    let i = list_state.offset() + content_row(area, row)?;
+    (i < len).then_some(i)
+}
+
+fn handle_search_input(app: &mut App, key: KeyEvent) -> Action {
+    match key.code {
+        KeyCode::Enter => app.commit_search(),
+        KeyCode::Esc => app.cancel_search(),
+        KeyCode::Backspace => app.search_backspace(),
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.cancel_search(),
+        KeyCode::Char(c) => app.search_push_char(c),
+        _ => return Action::None,
+    }
+    Action::Redraw
+}
+
+fn handle_global_search_input(app: &mut App, key: KeyEvent) -> Action {
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Action::Quit;
+    }
+
+    if app.global_search.editing {
+        match key.code {
+            KeyCode::Enter => app.run_global_search(),
+            KeyCode::Esc => {
+                // Back to the results if there are any, otherwise close
+                if app.global_search.results.is_empty() {
+                    app.global_search.close();
+                } else {
+                    app.global_search.editing = false;
+                }
+            }
+            KeyCode::Backspace => {
+                app.global_search.input.pop();
+            }
+            KeyCode::Char(c) => app.global_search.input.push(c),
+            _ => return Action::None,
         }
-        KeyCode::PageUp => {
-            app.conversation_state.scroll_up(viewport_height);
-            Action::Redraw
+        return Action::Redraw;
+    }
+
+    match key.code {
+        KeyCode::Char('j') | KeyCode::Down => app.global_search.next(),
+        KeyCode::Char('k') | KeyCode::Up => app.global_search.previous(),
+        KeyCode::Char('g') => app.global_search.first(),
+        KeyCode::Char('G') => app.global_search.last(),
+        KeyCode::Char('/') | KeyCode::Char('i') => app.global_search.editing = true,
+        KeyCode::Enter => app.open_selected_search_hit(),
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S') => app.global_search.close(),
+        _ => return Action::None,
+    }
+    Action::Redraw
+}
+
+fn handle_branch_picker_input(app: &mut App, key: KeyEvent) -> Action {
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            return Action::Quit;
         }
-        KeyCode::Char('g') => {
-            app.conversation_state.scroll_to_top();
-            Action::Redraw
+        KeyCode::Char('j') | KeyCode::Down => app.branch_picker.next(),
+        KeyCode::Char('k') | KeyCode::Up => app.branch_picker.previous(),
+        KeyCode::Enter => app.switch_to_selected_branch(),
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('b') => app.branch_picker.close(),
+        _ => return Action::None,
+    }
+    Action::Redraw
+}
+
+fn handle_slowest_tools_input(app: &mut App, key: KeyEvent) -> Action {
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            return Action::Quit;
         }
-        KeyCode::Char('G') => {
-            app.conversation_state.scroll_to_bottom(viewport_height);
-            Action::Redraw
+        KeyCode::Char('j') | KeyCode::Down => app.slowest_tools.next(),
+        KeyCode::Char('k') | KeyCode::Up => app.slowest_tools.previous(),
+        KeyCode::Enter => app.jump_to_selected_slow_tool(),
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('w') => app.slowest_tools.close(),
+        _ => return Action::None,
+    }
+    Action::Redraw
+}
+
+fn handle_file_index_input(app: &mut App, key: KeyEvent) -> Action {
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            return Action::Quit;
         }
-        _ => Action::None,
+        KeyCode::Char('j') | KeyCode::Down => app.file_index.next(),
+        KeyCode::Char('k') | KeyCode::Up => app.file_index.previous(),
+        KeyCode::Char('g') => app.file_index.first(),
+        KeyCode::Char('G') => app.file_index.last(),
+        KeyCode::Enter => app.open_selected_file(),
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('i') => app.file_index.close(),
+        _ => return Action::None,
     }
+    Action::Redraw
 }
@@ -0,0 +1,627 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::{Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+/// Where a binding applies. A pane's own scope is checked first, then the scopes it shares,
+/// so a pane can rebind a key that is also global.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    Global,
+    /// The projects, sessions and agents panes
+    Lists,
+    Projects,
+    Sessions,
+    Agents,
+    Conversation,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 6] = [
+        Scope::Global,
+        Scope::Lists,
+        Scope::Projects,
+        Scope::Sessions,
+        Scope::Agents,
+        Scope::Conversation,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Scope::Global => "Global",
+            Scope::Lists => "Lists",
+            Scope::Projects => "Projects",
+            Scope::Sessions => "Sessions",
+            Scope::Agents => "Agents",
+            Scope::Conversation => "Conversation",
+        }
+    }
+}
+
+/// A command a key can be bound to, named in the `[keys]` tables of the config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAction {
+    Quit,
+    NextPane,
+    PreviousPane,
+    ToggleHelp,
+    ToggleThinking,
+    ToggleToolExpansion,
+    ToggleFullDiffs,
+    ToggleMetadata,
+    ToggleFollow,
+    ToggleStats,
+    SearchAllSessions,
+    SwitchBranch,
+    SlowestTools,
+    FilesTouched,
+    Export,
+    Refresh,
+    Down,
+    Up,
+    HalfPageDown,
+    HalfPageUp,
+    PageDown,
+    PageUp,
+    Top,
+    Bottom,
+    Select,
+    ResumeSession,
+    SearchForward,
+    SearchBackward,
+    NextMatch,
+    PreviousMatch,
+    NextEntry,
+    PreviousEntry,
+    ToggleEntry,
+    YankEntry,
+    YankTarget,
+    OpenInEditor,
+    Clear,
+}
+
+impl KeyAction {
+    /// What the action does, for the help overlay
+    pub fn description(self) -> &'static str {
+        match self {
+            KeyAction::Quit => "Quit",
+            KeyAction::NextPane => "Next pane",
+            KeyAction::PreviousPane => "Previous pane",
+            KeyAction::ToggleHelp => "Toggle this help",
+            KeyAction::ToggleThinking => "Toggle thinking blocks",
+            KeyAction::ToggleToolExpansion => "Toggle tool expansion",
+            KeyAction::ToggleFullDiffs => "Toggle full Edit diffs",
+            KeyAction::ToggleMetadata => "Toggle cwd/branch/version",
+            KeyAction::ToggleFollow => "Toggle follow mode",
+            KeyAction::ToggleStats => "Toggle session statistics",
+            KeyAction::SearchAllSessions => "Search all sessions",
+            KeyAction::SwitchBranch => "Switch conversation branch",
+            KeyAction::SlowestTools => "Slowest tool calls",
+            KeyAction::FilesTouched => "Files touched by the session",
+            KeyAction::Export => "Export agent to Markdown",
+            KeyAction::Refresh => "Rescan projects and sessions",
+            KeyAction::Down => "Move down / scroll down",
+            KeyAction::Up => "Move up / scroll up",
+            KeyAction::HalfPageDown => "Half page down",
+            KeyAction::HalfPageUp => "Half page up",
+            KeyAction::PageDown => "Page down",
+            KeyAction::PageUp => "Page up",
+            KeyAction::Top => "Go to top",
+            KeyAction::Bottom => "Go to bottom",
+            KeyAction::Select => "Focus next pane",
+            KeyAction::ResumeSession => "Resume session in Claude",
+            KeyAction::SearchForward => "Search forward",
+            KeyAction::SearchBackward => "Search backward",
+            KeyAction::NextMatch => "Next match",
+            KeyAction::PreviousMatch => "Previous match",
+            KeyAction::NextEntry => "Move entry cursor down",
+            KeyAction::PreviousEntry => "Move entry cursor up",
+            KeyAction::ToggleEntry => "Expand / collapse entry",
+            KeyAction::YankEntry => "Copy entry",
+            KeyAction::YankTarget => "Copy command or file path",
+            KeyAction::OpenInEditor => "Open file in $EDITOR",
+            KeyAction::Clear => "Clear highlights / cursor",
+        }
+    }
+}
+
+/// The bindings claude-tail starts with, in help order
+const DEFAULT_BINDINGS: &[(Scope, KeyAction, &[&str])] = &[
+    (Scope::Global, KeyAction::Quit, &["q"]),
+    (Scope::Global, KeyAction::NextPane, &["tab"]),
+    (Scope::Global, KeyAction::PreviousPane, &["shift-tab"]),
+    (Scope::Global, KeyAction::ToggleHelp, &["?", "f1"]),
+    (Scope::Global, KeyAction::ToggleThinking, &["t"]),
+    (Scope::Global, KeyAction::ToggleToolExpansion, &["e"]),
+    (Scope::Global, KeyAction::ToggleFullDiffs, &["d"]),
+    (Scope::Global, KeyAction::ToggleMetadata, &["m"]),
+    (Scope::Global, KeyAction::ToggleFollow, &["f"]),
+    (Scope::Global, KeyAction::ToggleStats, &["s"]),
+    (Scope::Global, KeyAction::SearchAllSessions, &["S"]),
+    (Scope::Global, KeyAction::SwitchBranch, &["b"]),
+    (Scope::Global, KeyAction::SlowestTools, &["w"]),
+    (Scope::Global, KeyAction::FilesTouched, &["i"]),
+    (Scope::Global, KeyAction::Export, &["x"]),
+    (Scope::Global, KeyAction::Refresh, &["r"]),
+    (Scope::Lists, KeyAction::Down, &["j", "down"]),
+    (Scope::Lists, KeyAction::Up, &["k", "up"]),
+    (Scope::Lists, KeyAction::Top, &["g"]),
+    (Scope::Lists, KeyAction::Bottom, &["G"]),
+    (Scope::Lists, KeyAction::Select, &["enter"]),
+    (Scope::Sessions, KeyAction::ResumeSession, &["R"]),
+    (Scope::Conversation, KeyAction::Down, &["j", "down"]),
+    (Scope::Conversation, KeyAction::Up, &["k", "up"]),
+    (Scope::Conversation, KeyAction::HalfPageDown, &["ctrl-d"]),
+    (Scope::Conversation, KeyAction::HalfPageUp, &["ctrl-u"]),
+    (Scope::Conversation, KeyAction::PageDown, &["pgdown"]),
+    (Scope::Conversation, KeyAction::PageUp, &["pgup"]),
+    (Scope::Conversation, KeyAction::Top, &["g"]),
+    (Scope::Conversation, KeyAction::Bottom, &["G"]),
+    (Scope::Conversation, KeyAction::SearchForward, &["/"]),
+    (Scope::Conversation, KeyAction::SearchBackward, &["?"]),
+    (Scope::Conversation, KeyAction::NextMatch, &["n"]),
+    (Scope::Conversation, KeyAction::PreviousMatch, &["N"]),
+    (Scope::Conversation, KeyAction::NextEntry, &["J"]),
+    (Scope::Conversation, KeyAction::PreviousEntry, &["K"]),
+    (
+        Scope::Conversation,
+        KeyAction::ToggleEntry,
+        &["enter", "space"],
+    ),
+    (Scope::Conversation, KeyAction::YankEntry, &["y"]),
+    (Scope::Conversation, KeyAction::YankTarget, &["Y"]),
+    (Scope::Conversation, KeyAction::OpenInEditor, &["o"]),
+    (Scope::Conversation, KeyAction::Clear, &["esc"]),
+];
+
+/// Names for keys that aren't a single printable character
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("shift-tab", KeyCode::BackTab),
+    ("space", KeyCode::Char(' ')),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pgup", KeyCode::PageUp),
+    ("pgdown", KeyCode::PageDown),
+];
+
+/// A key with its modifiers. Shift is part of the character for printable keys, so `G` and
+/// `shift-g` are the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut code = code;
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            match code {
+                KeyCode::Char(c) => code = KeyCode::Char(c.to_ascii_uppercase()),
+                KeyCode::BackTab => {}
+                _ => return Self { code, modifiers },
+            }
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    /// Parse one key, e.g. `j`, `G`, `ctrl-d`, `enter` or `f1`
+    fn parse(token: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = token;
+        // A trailing `-` is the key itself, as in `ctrl--`
+        while let Some((modifier, key)) = rest.split_once('-')
+            && !key.is_empty()
+        {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                "shift" => modifiers |= KeyModifiers::SHIFT,
+                _ => break,
+            }
+            rest = key;
+        }
+        if modifiers.contains(KeyModifiers::SHIFT) && rest.eq_ignore_ascii_case("tab") {
+            return Some(Self::new(KeyCode::BackTab, modifiers));
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                let name = rest.to_ascii_lowercase();
+                let name = match name.as_str() {
+                    "escape" => "esc",
+                    "return" => "enter",
+                    "pageup" => "pgup",
+                    "pagedown" => "pgdown",
+                    name => name,
+                };
+                match KEY_NAMES.iter().find(|(n, _)| *n == name) {
+                    Some((_, code)) => *code,
+                    None => KeyCode::F(name.strip_prefix('f')?.parse().ok()?),
+                }
+            }
+        };
+        Some(Self::new(code, modifiers))
+    }
+
+    fn is_plain_char(&self) -> bool {
+        matches!(self.code, KeyCode::Char(c) if c != ' ') && self.modifiers.is_empty()
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift-")?;
+        }
+        match KEY_NAMES.iter().find(|(_, code)| *code == self.code) {
+            Some((name, _)) => write!(f, "{}", name),
+            None => match self.code {
+                KeyCode::Char(c) => write!(f, "{}", c),
+                KeyCode::F(n) => write!(f, "f{}", n),
+                code => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+/// Keys pressed one after another, like `gg`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySequence(Vec<Key>);
+
+impl KeySequence {
+    /// Parse keys separated by spaces (`ctrl-w j`). A word that isn't a key name is a chord
+    /// of its characters, so `gg` is `g` twice.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut keys = Vec::new();
+        for token in text.split_whitespace() {
+            match Key::parse(token) {
+                Some(key) => keys.push(key),
+                None if token.contains('-') => bail!("unknown key '{}'", token),
+                None => keys.extend(
+                    token
+                        .chars()
+                        .map(|c| Key::new(KeyCode::Char(c), KeyModifiers::NONE)),
+                ),
+            }
+        }
+        if keys.is_empty() {
+            bail!("empty key binding");
+        }
+        Ok(Self(keys))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `gg` rather than `g g`, which would read back the same
+        let separator = if self.0.len() > 1 && self.0.iter().all(Key::is_plain_char) {
+            ""
+        } else {
+            " "
+        };
+        let keys: Vec<String> = self.0.iter().map(Key::to_string).collect();
+        write!(f, "{}", keys.join(separator))
+    }
+}
+
+/// The keys of one action in the config file: a single binding or a list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The `[keys]` tables of the config file: bindings per scope, by action
+pub type KeyBindings = BTreeMap<Scope, BTreeMap<KeyAction, KeyList>>;
+
+/// The outcome of the keys pressed so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Action(KeyAction),
+    /// The keys start a longer binding
+    Pending,
+    Unbound,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Binding {
+    scope: Scope,
+    action: KeyAction,
+    keys: Vec<KeySequence>,
+}
+
+/// The active key bindings: the defaults with the config file's `[keys]` tables applied.
+/// Binding an action in a scope replaces its default keys there; `[]` unbinds it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "KeyBindings", into = "KeyBindings")]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    pub fn new(overrides: &KeyBindings) -> Result<Self> {
+        let mut bindings: Vec<Binding> = DEFAULT_BINDINGS
+            .iter()
+            .map(|(scope, action, keys)| Binding {
+                scope: *scope,
+                action: *action,
+                keys: keys
+                    .iter()
+                    .map(|keys| KeySequence::parse(keys).expect("default binding"))
+                    .collect(),
+            })
+            .collect();
+
+        for (scope, actions) in overrides {
+            for (action, list) in actions {
+                let texts = match list {
+                    KeyList::One(text) => std::slice::from_ref(text),
+                    KeyList::Many(texts) => texts.as_slice(),
+                };
+                let keys = texts
+                    .iter()
+                    .map(|text| KeySequence::parse(text))
+                    .collect::<Result<Vec<_>>>()?;
+                match bindings
+                    .iter_mut()
+                    .find(|b| b.scope == *scope && b.action == *action)
+                {
+                    Some(binding) => binding.keys = keys,
+                    None => bindings.push(Binding {
+                        scope: *scope,
+                        action: *action,
+                        keys,
+                    }),
+                }
+            }
+        }
+
+        let keymap = Self { bindings };
+        keymap.check_conflicts()?;
+        Ok(keymap)
+    }
+
+    /// Within a scope a key sequence can name one action, and can't also begin a longer
+    /// binding, since there would be no telling when it was complete
+    fn check_conflicts(&self) -> Result<()> {
+        for scope in Scope::ALL {
+            let keys: Vec<(&KeySequence, KeyAction)> = self
+                .bindings_in(scope)
+                .flat_map(|b| b.keys.iter().map(move |keys| (keys, b.action)))
+                .collect();
+            for (i, (a, a_action)) in keys.iter().enumerate() {
+                for (b, b_action) in &keys[i + 1..] {
+                    let (shorter, longer) = if a.0.len() <= b.0.len() {
+                        ((a, a_action), (b, b_action))
+                    } else {
+                        ((b, b_action), (a, a_action))
+                    };
+                    if longer.0.0.starts_with(&shorter.0.0) {
+                        bail!(
+                            "'{}' ({}) conflicts with '{}' ({}) in [keys.{}]",
+                            shorter.0,
+                            action_name(*shorter.1),
+                            longer.0,
+                            action_name(*longer.1),
+                            scope_name(scope)
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn bindings_in(&self, scope: Scope) -> impl Iterator<Item = &Binding> {
+        self.bindings.iter().filter(move |b| b.scope == scope)
+    }
+
+    /// Look up the keys pressed so far. The first scope with a binding that starts with them
+    /// decides.
+    pub fn resolve(&self, scopes: &[Scope], pressed: &[Key]) -> Resolution {
+        for &scope in scopes {
+            let mut pending = false;
+            for binding in self.bindings_in(scope) {
+                for keys in &binding.keys {
+                    if keys.0 == pressed {
+                        return Resolution::Action(binding.action);
+                    }
+                    pending |= keys.0.starts_with(pressed);
+                }
+            }
+            if pending {
+                return Resolution::Pending;
+            }
+        }
+        Resolution::Unbound
+    }
+
+    /// The first key bound to `action` in `scope`, for hints
+    pub fn key_for(&self, scope: Scope, action: KeyAction) -> Option<String> {
+        self.bindings_in(scope)
+            .find(|b| b.action == action)
+            .and_then(|b| b.keys.first())
+            .map(KeySequence::to_string)
+    }
+
+    /// Bound actions per scope with their keys, in help order
+    pub fn sections(&self) -> Vec<(Scope, Vec<(String, KeyAction)>)> {
+        Scope::ALL
+            .into_iter()
+            .map(|scope| {
+                let actions = self
+                    .bindings_in(scope)
+                    .filter(|b| !b.keys.is_empty())
+                    .map(|b| {
+                        let keys: Vec<String> = b.keys.iter().map(KeySequence::to_string).collect();
+                        (keys.join(", "), b.action)
+                    })
+                    .collect();
+                (scope, actions)
+            })
+            .filter(|(_, actions): &(Scope, Vec<_>)| !actions.is_empty())
+            .collect()
+    }
+
+    /// Every binding, as the config file would spell it
+    pub fn to_bindings(&self) -> KeyBindings {
+        let mut bindings = KeyBindings::new();
+        for binding in &self.bindings {
+            let mut keys: Vec<String> = binding.keys.iter().map(KeySequence::to_string).collect();
+            let list = if keys.len() == 1 {
+                KeyList::One(keys.remove(0))
+            } else {
+                KeyList::Many(keys)
+            };
+            bindings
+                .entry(binding.scope)
+                .or_default()
+                .insert(binding.action, list);
+        }
+        bindings
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&KeyBindings::new()).expect("default bindings don't conflict")
+    }
+}
+
+impl TryFrom<KeyBindings> for Keymap {
+    type Error = anyhow::Error;
+
+    fn try_from(overrides: KeyBindings) -> Result<Self> {
+        Self::new(&overrides)
+    }
+}
+
+impl From<Keymap> for KeyBindings {
+    fn from(keymap: Keymap) -> Self {
+        keymap.to_bindings()
+    }
+}
+
+/// The config file's name for an action
+fn action_name(action: KeyAction) -> String {
+    serde_json::to_value(action)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn scope_name(scope: Scope) -> String {
+    serde_json::to_value(scope)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(text: &str) -> Vec<Key> {
+        KeySequence::parse(text).unwrap().0
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        let g = Key::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        assert_eq!(keys("gg"), [g, g]);
+        assert_eq!(
+            keys("ctrl-d"),
+            [Key::new(KeyCode::Char('d'), KeyModifiers::CONTROL)]
+        );
+        assert_eq!(
+            keys("G"),
+            [Key::new(KeyCode::Char('G'), KeyModifiers::SHIFT)]
+        );
+        assert_eq!(
+            keys("Shift-Tab"),
+            [Key::new(KeyCode::BackTab, KeyModifiers::NONE)]
+        );
+        assert_eq!(keys("f1"), [Key::new(KeyCode::F(1), KeyModifiers::NONE)]);
+        assert_eq!(
+            keys("-"),
+            [Key::new(KeyCode::Char('-'), KeyModifiers::NONE)]
+        );
+        assert_eq!(keys("shift-g"), keys("G"));
+        assert!(KeySequence::parse("hyper-x").is_err());
+
+        for text in ["gg", "ctrl-w j", "space", "shift-tab", "alt-pgdown"] {
+            assert_eq!(KeySequence::parse(text).unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_resolve_chords_and_scopes() {
+        let overrides: KeyBindings = toml::from_str(
+            "[conversation]\ntop = \"gg\"\nyank_entry = [\"y y\", \"ctrl-y\"]\n\n[global]\nquit = []\n",
+        )
+        .unwrap();
+        let keymap = Keymap::new(&overrides).unwrap();
+        let conversation = [Scope::Conversation, Scope::Global];
+
+        assert_eq!(
+            keymap.resolve(&conversation, &keys("g")),
+            Resolution::Pending
+        );
+        assert_eq!(
+            keymap.resolve(&conversation, &keys("gg")),
+            Resolution::Action(KeyAction::Top)
+        );
+        assert_eq!(
+            keymap.resolve(&conversation, &keys("q")),
+            Resolution::Unbound
+        );
+        // The pane's own binding wins over the global one
+        assert_eq!(
+            keymap.resolve(&conversation, &keys("?")),
+            Resolution::Action(KeyAction::SearchBackward)
+        );
+        assert_eq!(
+            keymap.resolve(&[Scope::Projects, Scope::Lists, Scope::Global], &keys("g")),
+            Resolution::Action(KeyAction::Top)
+        );
+
+        let conflicting: KeyBindings =
+            toml::from_str("[conversation]\nnext_entry = \"g j\"\n").unwrap();
+        assert!(Keymap::new(&conflicting).is_err());
+
+        assert_eq!(Keymap::new(&keymap.to_bindings()).unwrap(), keymap);
+    }
+}
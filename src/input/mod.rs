@@ -1,3 +1,5 @@
 pub mod handler;
+pub mod keymap;
 
-pub use handler::{Action, handle_key_event};
+pub use handler::{Action, handle_key_event, handle_mouse_event};
+pub use keymap::{Key, KeyAction, Keymap, Scope};
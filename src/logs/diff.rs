@@ -0,0 +1,185 @@
+use similar::udiff::UnifiedHunkHeader;
+use similar::{ChangeTag, TextDiff};
+
+/// Lines of unchanged context around each change in a unified diff
+const CONTEXT_LINES: usize = 3;
+
+/// One string replacement made by an `Edit` or `MultiEdit` tool call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEdit {
+    pub old: String,
+    pub new: String,
+    /// Replace every occurrence of `old` rather than the single one it must match
+    pub replace_all: bool,
+}
+
+/// Whether a diff line is unchanged context, added or removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// One line of a diff, split into segments; emphasized segments are the words that
+/// changed within a replaced line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub segments: Vec<(bool, String)>,
+}
+
+/// A group of changes with its surrounding context, headed by `@@ -a,b +c,d @@`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffHunk {
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// Extracts the target file and replacements from an `Edit` or `MultiEdit` tool input.
+/// Returns None for other tools or inputs missing a file path.
+pub fn tool_edits(name: &str, input: &serde_json::Value) -> Option<(String, Vec<FileEdit>)> {
+    let file_path = input.get("file_path")?.as_str()?.to_string();
+    let edit = |value: &serde_json::Value| FileEdit {
+        old: value
+            .get("old_string")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        new: value
+            .get("new_string")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        replace_all: value
+            .get("replace_all")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    };
+
+    let edits = match name {
+        "Edit" => vec![edit(input)],
+        "MultiEdit" => input
+            .get("edits")
+            .and_then(|v| v.as_array())
+            .map(|edits| edits.iter().map(edit).collect())
+            .unwrap_or_default(),
+        _ => return None,
+    };
+    Some((file_path, edits))
+}
+
+/// Unified diff of `old` against `new`, with `a/` and `b/` headers for `path`.
+///
+/// Line numbers in hunk headers are relative to the replaced text, since tool inputs
+/// don't record where in the file the replacement happened.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let path = path.trim_start_matches('/');
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .missing_newline_hint(false)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string()
+}
+
+/// Line-level diff of `old` against `new`, grouped into hunks with context lines.
+/// Lines have their trailing newline removed.
+pub fn diff_hunks(old: &str, new: &str) -> Vec<DiffHunk> {
+    let diff = TextDiff::from_lines(old, new);
+    diff.grouped_ops(CONTEXT_LINES)
+        .iter()
+        .map(|ops| DiffHunk {
+            header: UnifiedHunkHeader::new(ops).to_string(),
+            lines: ops
+                .iter()
+                .flat_map(|op| diff.iter_inline_changes(op))
+                .map(|change| DiffLine {
+                    kind: match change.tag() {
+                        ChangeTag::Equal => DiffLineKind::Context,
+                        ChangeTag::Insert => DiffLineKind::Added,
+                        ChangeTag::Delete => DiffLineKind::Removed,
+                    },
+                    segments: change
+                        .iter_strings_lossy()
+                        .map(|(emphasized, text)| {
+                            (emphasized, text.trim_end_matches(['\n', '\r']).to_string())
+                        })
+                        .filter(|(_, text)| !text.is_empty())
+                        .collect(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_edits_edit() {
+        let input = json!({"file_path": "/src/lib.rs", "old_string": "a", "new_string": "b"});
+        let (path, edits) = tool_edits("Edit", &input).unwrap();
+        assert_eq!(path, "/src/lib.rs");
+        assert_eq!(
+            edits,
+            vec![FileEdit {
+                old: "a".to_string(),
+                new: "b".to_string(),
+                replace_all: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_tool_edits_multiedit() {
+        let input = json!({"file_path": "/src/lib.rs", "edits": [
+            {"old_string": "a", "new_string": "b"},
+            {"old_string": "c", "new_string": "d"}
+        ]});
+        let (_, edits) = tool_edits("MultiEdit", &input).unwrap();
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[1].new, "d");
+        assert!(tool_edits("Write", &input).is_none());
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let diff = unified_diff("/src/lib.rs", "one\ntwo\n", "one\nthree\n");
+        assert_eq!(
+            diff,
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,2 @@\n one\n-two\n+three\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_hunks_emphasize_changed_words() {
+        let hunks = diff_hunks("a\nlet x = 1;\nb\n", "a\nlet x = 2;\nb\n");
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].header, "@@ -1,3 +1,3 @@");
+        let kinds: Vec<_> = hunks[0].lines.iter().map(|l| l.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiffLineKind::Context,
+                DiffLineKind::Removed,
+                DiffLineKind::Added,
+                DiffLineKind::Context
+            ]
+        );
+        let added = &hunks[0].lines[2].segments;
+        assert_eq!(
+            added
+                .iter()
+                .map(|(_, text)| text.as_str())
+                .collect::<String>(),
+            "let x = 2;"
+        );
+        assert_eq!(
+            added,
+            &vec![(false, "let x = ".to_string()), (true, "2;".to_string())]
+        );
+    }
+}
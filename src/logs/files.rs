@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::parser::{merge_tool_results, parse_jsonl_file};
+use super::types::{Agent, DisplayEntry};
+
+/// What a tool call did to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileOperation {
+    Read,
+    Write,
+    Edit,
+}
+
+/// The file a tool call read, wrote or edited, from its parsed input
+pub fn file_operation<'a>(
+    name: &str,
+    input: &'a serde_json::Value,
+) -> Option<(FileOperation, &'a str)> {
+    let (operation, key) = match name {
+        "Read" => (FileOperation::Read, "file_path"),
+        "Write" => (FileOperation::Write, "file_path"),
+        "Edit" | "MultiEdit" => (FileOperation::Edit, "file_path"),
+        "NotebookEdit" => (FileOperation::Edit, "notebook_path"),
+        _ => return None,
+    };
+    input
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(|path| (operation, path))
+}
+
+/// One tool call that touched a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileTouch {
+    /// Log of the agent that made the call
+    pub log_path: PathBuf,
+    /// Index of the call in the agent's conversation (before truncation)
+    pub entry_index: usize,
+    pub operation: FileOperation,
+}
+
+/// A file and every call that touched it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TouchedFile {
+    pub path: String,
+    /// Calls in agent order, then log order
+    pub touches: Vec<FileTouch>,
+}
+
+impl TouchedFile {
+    /// Number of calls that performed `operation` on this file
+    pub fn count(&self, operation: FileOperation) -> usize {
+        self.touches
+            .iter()
+            .filter(|touch| touch.operation == operation)
+            .count()
+    }
+
+    /// Number of distinct agents that touched this file
+    pub fn agents(&self) -> usize {
+        // Touches are grouped by agent, so repeats are adjacent
+        let mut logs: Vec<&PathBuf> = self.touches.iter().map(|touch| &touch.log_path).collect();
+        logs.dedup();
+        logs.len()
+    }
+}
+
+/// Every file read, written or edited in a session, sorted by path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileIndex {
+    pub files: Vec<TouchedFile>,
+}
+
+impl FileIndex {
+    /// Index the conversations of a session's agents, reading each log from disk
+    pub fn build(agents: &[Agent]) -> Self {
+        let mut files = BTreeMap::new();
+        for agent in agents {
+            let Ok(result) = parse_jsonl_file(&agent.log_path) else {
+                continue;
+            };
+            let entries = merge_tool_results(result.latest_branch());
+            add_touches(&mut files, &agent.log_path, &entries);
+        }
+        Self::from_map(files)
+    }
+
+    /// Index a single conversation
+    pub fn from_entries(log_path: &Path, entries: &[DisplayEntry]) -> Self {
+        let mut files = BTreeMap::new();
+        add_touches(&mut files, log_path, entries);
+        Self::from_map(files)
+    }
+
+    fn from_map(files: BTreeMap<String, Vec<FileTouch>>) -> Self {
+        Self {
+            files: files
+                .into_iter()
+                .map(|(path, touches)| TouchedFile { path, touches })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+fn add_touches(
+    files: &mut BTreeMap<String, Vec<FileTouch>>,
+    log_path: &Path,
+    entries: &[DisplayEntry],
+) {
+    for (entry_index, entry) in entries.iter().enumerate() {
+        let DisplayEntry::ToolCall { name, input, .. } = entry else {
+            continue;
+        };
+        let Ok(parsed) = serde_json::from_str::<serde_json::Value>(input) else {
+            continue;
+        };
+        if let Some((operation, path)) = file_operation(name, &parsed) {
+            files.entry(path.to_string()).or_default().push(FileTouch {
+                log_path: log_path.to_path_buf(),
+                entry_index,
+                operation,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, path: &str) -> DisplayEntry {
+        DisplayEntry::tool_call(name, serde_json::json!({ "file_path": path }))
+    }
+
+    #[test]
+    fn test_file_index_counts_operations() {
+        let log = PathBuf::from("/logs/main.jsonl");
+        let entries = vec![
+            call("Read", "/src/b.rs"),
+            DisplayEntry::AssistantText {
+                text: "looks fine".to_string(),
+                timestamp: None,
+            },
+            call("Edit", "/src/a.rs"),
+            call("Read", "/src/a.rs"),
+            call("Bash", "/src/c.rs"),
+        ];
+        let index = FileIndex::from_entries(&log, &entries);
+
+        let paths: Vec<&str> = index.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["/src/a.rs", "/src/b.rs"]);
+        let a = &index.files[0];
+        assert_eq!(a.count(FileOperation::Edit), 1);
+        assert_eq!(a.count(FileOperation::Read), 1);
+        assert_eq!(a.touches[0].entry_index, 2);
+        assert_eq!(a.agents(), 1);
+    }
+}
@@ -1,11 +1,28 @@
+pub mod diff;
+pub mod files;
 pub mod parser;
 pub mod project;
+pub mod search;
+pub mod stats;
+pub mod timing;
+pub mod tree;
 pub mod types;
+pub mod usage;
 pub mod watcher;
 
+pub use files::{FileIndex, FileOperation, TouchedFile, file_operation};
 pub use parser::{
-    ParseResult, merge_tool_results, parse_jsonl_file_async, parse_jsonl_from_position_async,
+    ParseResult, merge_tool_results, parse_jsonl_file, parse_jsonl_file_async,
+    parse_jsonl_from_position_async, parse_jsonl_to_position_async,
 };
-pub use project::{Project, Session, discover_agents, discover_projects, discover_sessions};
-pub use types::{Agent, DisplayEntry, ToolCallResult};
+pub use project::{
+    Project, Session, discover_agents, discover_projects, discover_sessions, find_agent,
+    find_session, latest_session, session_cwd,
+};
+pub use search::{SearchHit, search_projects};
+pub use stats::SessionStats;
+pub use timing::{ResponseTimer, ToolTiming, format_duration, slowest_tools, tool_duration};
+pub use tree::{Branch, ConversationTree};
+pub use types::{Agent, DisplayEntry, SessionMetadata, TokenUsage, ToolCallResult};
+pub use usage::{UsageTotals, format_token_count, scan_usage};
 pub use watcher::{SessionWatcher, WatcherEvent};
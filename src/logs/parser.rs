@@ -3,19 +3,33 @@ use serde_json::error::Category;
 use std::io::{Read as _, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 
+use super::tree::{ConversationTree, EntryNode};
 use super::types::{
-    ContentBlock, ContentValue, DisplayEntry, LogEntry, ToolCallResult, ToolResultContent,
+    ContentBlock, ContentValue, DisplayEntry, LogEntry, SessionMetadata, ToolCallResult,
+    ToolResultContent,
 };
 
 /// Result of parsing a JSONL file, including any errors encountered
 pub struct ParseResult {
+    /// Entries in file order, across every branch of the conversation
     pub entries: Vec<DisplayEntry>,
+    /// One node per log line, linking its entries into the conversation tree
+    pub nodes: Vec<EntryNode>,
     /// Parse errors (line descriptions, not fatal)
     pub errors: Vec<String>,
     /// Number of bytes read from the file
     pub bytes_read: u64,
 }
 
+impl ParseResult {
+    /// Entries on the most recent branch of the conversation, in order
+    pub fn latest_branch(self) -> Vec<DisplayEntry> {
+        let mut tree = ConversationTree::new();
+        tree.extend(&self.entries, &self.nodes);
+        tree.branch_entries(None, self.entries, &self.nodes)
+    }
+}
+
 pub fn parse_jsonl_file(path: &Path) -> Result<ParseResult> {
     let mut file = std::fs::File::open(path)?;
     let mut content = String::new();
@@ -31,11 +45,25 @@ pub fn parse_jsonl_from_position(path: &Path, position: u64) -> Result<ParseResu
     parse_stream_content(&content, position)
 }
 
+/// Parses the first `end` bytes of the file, e.g. the lines already in a conversation tree
+pub fn parse_jsonl_to_position(path: &Path, end: u64) -> Result<ParseResult> {
+    let file = std::fs::File::open(path)?;
+    let mut content = String::new();
+    file.take(end).read_to_string(&mut content)?;
+    parse_stream_content(&content, 0)
+}
+
 fn parse_stream_content(content: &str, base_position: u64) -> Result<ParseResult> {
     let mut entries = Vec::new();
+    let mut nodes = Vec::new();
     let mut errors = Vec::new();
     let mut last_valid_position = 0;
     let mut current_pos = 0;
+    // Index of the most recent Usage entry, so streamed lines of the same message
+    // update it in place instead of being counted once per content block
+    let mut last_usage_idx: Option<usize> = None;
+    // Metadata of the previous line, so a Metadata entry is only emitted when it changes
+    let mut last_metadata: Option<SessionMetadata> = None;
 
     while current_pos < content.len() {
         let slice = &content[current_pos..];
@@ -44,7 +72,56 @@ fn parse_stream_content(content: &str, base_position: u64) -> Result<ParseResult
 
         match stream.next() {
             Some(Ok(entry)) => {
-                entries.extend(convert_log_entry(&entry));
+                let mut converted = convert_log_entry(&entry).into_iter().peekable();
+                let same_message = match converted.peek() {
+                    Some(DisplayEntry::Usage { message_id, .. }) => Some(
+                        message_id.is_some()
+                            && matches!(
+                                last_usage_idx.and_then(|i| entries.get(i)),
+                                Some(DisplayEntry::Usage { message_id: prev, .. }) if prev == message_id
+                            ),
+                    ),
+                    _ => None,
+                };
+                let node_start = entries.len();
+                let metadata = entry_metadata(&entry)
+                    .filter(|metadata| last_metadata.as_ref() != Some(metadata))
+                    .map(|metadata| {
+                        last_metadata = Some(metadata.clone());
+                        DisplayEntry::Metadata {
+                            metadata,
+                            timestamp: entry_timestamp(&entry),
+                        }
+                    });
+                // A change goes ahead of the line's entries, except after a tool result,
+                // which has to stay next to its call to be merged into it
+                let metadata_after =
+                    matches!(converted.peek(), Some(DisplayEntry::ToolResult { .. }));
+                if !metadata_after {
+                    entries.extend(metadata.clone());
+                }
+                if let Some(same_message) = same_message
+                    && let Some(usage) = converted.next()
+                {
+                    match last_usage_idx {
+                        Some(idx) if same_message => entries[idx] = usage,
+                        _ => {
+                            last_usage_idx = Some(entries.len());
+                            entries.push(usage);
+                        }
+                    }
+                }
+                entries.extend(converted);
+                if metadata_after {
+                    entries.extend(metadata);
+                }
+                if let Some((uuid, parent_uuid)) = entry_links(&entry) {
+                    nodes.push(EntryNode {
+                        uuid: uuid.cloned(),
+                        parent_uuid: parent_uuid.cloned(),
+                        entries: node_start..entries.len(),
+                    });
+                }
                 let offset = stream.byte_offset();
                 current_pos += offset;
 
@@ -91,6 +168,7 @@ fn parse_stream_content(content: &str, base_position: u64) -> Result<ParseResult
 
     Ok(ParseResult {
         entries,
+        nodes,
         errors,
         bytes_read: base_position + last_valid_position as u64,
     })
@@ -103,6 +181,13 @@ pub async fn parse_jsonl_file_async(path: PathBuf) -> Result<ParseResult> {
         .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?
 }
 
+/// Async version of parse_jsonl_to_position that runs parsing on a background thread
+pub async fn parse_jsonl_to_position_async(path: PathBuf, end: u64) -> Result<ParseResult> {
+    tokio::task::spawn_blocking(move || parse_jsonl_to_position(&path, end))
+        .await
+        .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?
+}
+
 /// Async version of parse_jsonl_from_position that runs parsing on a background thread
 pub async fn parse_jsonl_from_position_async(path: PathBuf, position: u64) -> Result<ParseResult> {
     tokio::task::spawn_blocking(move || parse_jsonl_from_position(&path, position))
@@ -110,6 +195,78 @@ pub async fn parse_jsonl_from_position_async(path: PathBuf, position: u64) -> Re
         .map_err(|e| anyhow::anyhow!("Task join error: {}", e))?
 }
 
+/// The `cwd`, `gitBranch` and `version` of a log line, if it has any of them
+fn entry_metadata(entry: &LogEntry) -> Option<SessionMetadata> {
+    let metadata = match entry {
+        LogEntry::User {
+            cwd,
+            git_branch,
+            version,
+            ..
+        }
+        | LogEntry::Assistant {
+            cwd,
+            git_branch,
+            version,
+            ..
+        }
+        | LogEntry::Progress {
+            cwd,
+            git_branch,
+            version,
+            ..
+        }
+        | LogEntry::System {
+            cwd,
+            git_branch,
+            version,
+            ..
+        } => SessionMetadata {
+            cwd: cwd.clone(),
+            // Outside a git repository the branch is logged as an empty string
+            git_branch: git_branch.clone().filter(|branch| !branch.is_empty()),
+            version: version.clone(),
+        },
+        LogEntry::Unknown => return None,
+    };
+    (!metadata.is_empty()).then_some(metadata)
+}
+
+fn entry_timestamp(entry: &LogEntry) -> Option<chrono::DateTime<chrono::Utc>> {
+    match entry {
+        LogEntry::User { timestamp, .. }
+        | LogEntry::Assistant { timestamp, .. }
+        | LogEntry::Progress { timestamp, .. }
+        | LogEntry::System { timestamp, .. } => *timestamp,
+        LogEntry::Unknown => None,
+    }
+}
+
+/// The `uuid` and `parentUuid` of a log line, or None for lines that aren't part of the conversation
+fn entry_links(entry: &LogEntry) -> Option<(Option<&String>, Option<&String>)> {
+    match entry {
+        LogEntry::User {
+            uuid, parent_uuid, ..
+        }
+        | LogEntry::Assistant {
+            uuid, parent_uuid, ..
+        }
+        | LogEntry::Progress {
+            uuid, parent_uuid, ..
+        } => Some((uuid.as_ref(), parent_uuid.as_ref())),
+        LogEntry::System {
+            uuid,
+            parent_uuid,
+            logical_parent_uuid,
+            ..
+        } => Some((
+            uuid.as_ref(),
+            parent_uuid.as_ref().or(logical_parent_uuid.as_ref()),
+        )),
+        LogEntry::Unknown => None,
+    }
+}
+
 fn convert_log_entry(entry: &LogEntry) -> Vec<DisplayEntry> {
     match entry {
         LogEntry::User {
@@ -121,10 +278,110 @@ fn convert_log_entry(entry: &LogEntry) -> Vec<DisplayEntry> {
         LogEntry::Progress {
             data, timestamp, ..
         } => parse_progress_data(data, *timestamp),
+        LogEntry::System {
+            subtype,
+            content,
+            level,
+            compact_metadata,
+            error,
+            retry_attempt,
+            max_retries,
+            retry_in_ms,
+            timestamp,
+            ..
+        } => {
+            let content = content.as_ref().and_then(|c| c.as_str()).unwrap_or("");
+            match subtype.as_deref() {
+                Some("compact_boundary") => vec![DisplayEntry::CompactBoundary {
+                    trigger: compact_metadata
+                        .as_ref()
+                        .and_then(|m| m.get("trigger"))
+                        .and_then(|t| t.as_str())
+                        .map(|t| t.to_string()),
+                    pre_tokens: compact_metadata
+                        .as_ref()
+                        .and_then(|m| m.get("preTokens"))
+                        .and_then(|t| t.as_u64()),
+                    timestamp: *timestamp,
+                }],
+                Some("api_error") => vec![DisplayEntry::ApiError {
+                    message: api_error_message(error.as_ref(), content),
+                    retry_attempt: *retry_attempt,
+                    max_retries: *max_retries,
+                    retry_in_ms: retry_in_ms.map(|ms| ms.round() as u64),
+                    timestamp: *timestamp,
+                }],
+                Some("local_command") => {
+                    let text = local_command_text(content);
+                    if text.is_empty() {
+                        Vec::new()
+                    } else {
+                        vec![DisplayEntry::LocalCommand {
+                            text,
+                            timestamp: *timestamp,
+                        }]
+                    }
+                }
+                _ if !content.trim().is_empty() => vec![DisplayEntry::SystemNotice {
+                    subtype: subtype.clone(),
+                    level: level.clone(),
+                    text: content.trim().to_string(),
+                    timestamp: *timestamp,
+                }],
+                _ => Vec::new(),
+            }
+        }
         LogEntry::Unknown => Vec::new(),
     }
 }
 
+/// Best description of a failed API request, e.g. `529 Overloaded`.
+/// The error object nests the API's own error body a couple of levels down.
+fn api_error_message(error: Option<&serde_json::Value>, content: &str) -> String {
+    let message = error.and_then(|error| {
+        ["/error/error/message", "/error/message", "/message"]
+            .iter()
+            .find_map(|pointer| error.pointer(pointer).and_then(|m| m.as_str()))
+    });
+    let status = error
+        .and_then(|error| error.get("status"))
+        .and_then(|s| s.as_u64());
+    match (status, message) {
+        (Some(status), Some(message)) => format!("{} {}", status, message),
+        (None, Some(message)) => message.to_string(),
+        (Some(status), None) => format!("HTTP {}", status),
+        (None, None) if !content.trim().is_empty() => content.trim().to_string(),
+        (None, None) => "API request failed".to_string(),
+    }
+}
+
+/// Turns the XML-ish tags of a local command line into plain text: the command with its
+/// arguments (`/model opus`), or the output it printed
+fn local_command_text(content: &str) -> String {
+    let tag = |name: &str| {
+        let open = format!("<{}>", name);
+        let close = format!("</{}>", name);
+        let start = content.find(&open)? + open.len();
+        let end = content[start..].find(&close)? + start;
+        Some(content[start..end].trim())
+    };
+
+    if let Some(command) = tag("command-name") {
+        let args = tag("command-args").unwrap_or("");
+        if args.is_empty() {
+            command.to_string()
+        } else {
+            format!("{} {}", command, args)
+        }
+    } else if let Some(stdout) = tag("local-command-stdout") {
+        stdout.to_string()
+    } else if let Some(stderr) = tag("local-command-stderr") {
+        stderr.to_string()
+    } else {
+        content.trim().to_string()
+    }
+}
+
 fn parse_user_message(
     message: &super::types::MessageContent,
     timestamp: Option<chrono::DateTime<chrono::Utc>>,
@@ -132,14 +389,13 @@ fn parse_user_message(
     let mut entries = Vec::new();
 
     match &message.content {
-        Some(ContentValue::Text(text)) => {
-            if !text.is_empty() {
-                entries.push(DisplayEntry::UserMessage {
-                    text: text.clone(),
-                    timestamp,
-                });
-            }
+        Some(ContentValue::Text(text)) if !text.is_empty() => {
+            entries.push(DisplayEntry::UserMessage {
+                text: text.clone(),
+                timestamp,
+            });
         }
+        Some(ContentValue::Text(_)) => {}
         Some(ContentValue::Blocks(blocks)) => {
             let mut text_parts = Vec::new();
             for block in blocks {
@@ -257,16 +513,34 @@ fn parse_assistant_message(
     message: &super::types::MessageContent,
     timestamp: Option<chrono::DateTime<chrono::Utc>>,
 ) -> Vec<DisplayEntry> {
+    // Usage goes first so it never separates a ToolCall from its ToolResult.
+    // Progress entries carry no usage: sub-agent tokens are counted from their own logs.
+    let mut entries: Vec<DisplayEntry> = message
+        .usage
+        .filter(|usage| !usage.is_empty())
+        .map(|usage| DisplayEntry::Usage {
+            message_id: message.id.clone(),
+            model: message.model.clone(),
+            usage,
+            timestamp,
+        })
+        .into_iter()
+        .collect();
+
     match &message.content {
         Some(ContentValue::Text(text)) => {
-            vec![DisplayEntry::AssistantText {
+            entries.push(DisplayEntry::AssistantText {
                 text: text.clone(),
                 timestamp,
-            }]
+            });
+        }
+        Some(ContentValue::Blocks(blocks)) => {
+            entries.extend(parse_content_blocks_vec(blocks, timestamp));
         }
-        Some(ContentValue::Blocks(blocks)) => parse_content_blocks_vec(blocks, timestamp),
-        None => Vec::new(),
+        None => {}
     }
+
+    entries
 }
 
 fn parse_content_blocks(
@@ -332,7 +606,6 @@ fn parse_content_blocks(
                         if let Some(thinking) = block.get("thinking").and_then(|t| t.as_str()) {
                             entries.push(DisplayEntry::Thinking {
                                 text: thinking.to_string(),
-                                collapsed: true,
                                 timestamp,
                             });
                         }
@@ -394,7 +667,6 @@ fn parse_content_blocks_vec(
             ContentBlock::Thinking { thinking, .. } => {
                 entries.push(DisplayEntry::Thinking {
                     text: thinking.clone(),
-                    collapsed: true,
                     timestamp,
                 });
             }
@@ -446,7 +718,7 @@ pub fn merge_tool_results(mut entries: Vec<DisplayEntry>) -> Vec<DisplayEntry> {
                     tool_use_id,
                     content,
                     is_error,
-                    ..
+                    timestamp: result_timestamp,
                 }) = &next_entry
                 {
                     if tool_use_id == &id {
@@ -455,6 +727,7 @@ pub fn merge_tool_results(mut entries: Vec<DisplayEntry>) -> Vec<DisplayEntry> {
                         let consumed_result = Some(ToolCallResult {
                             content: content.clone(),
                             is_error: *is_error,
+                            timestamp: *result_timestamp,
                         });
                         next_entry = iter.next(); // Skip the consumed result
                         consumed_result
@@ -1032,6 +1305,130 @@ mod tests {
         ));
     }
 
+    // ── Token usage ───────────────────────────────────────────────────────────
+
+    fn assistant_block_with_usage(id: &str, block: serde_json::Value, output: u64) -> String {
+        serde_json::json!({
+            "type": "assistant",
+            "message": {
+                "id": id,
+                "model": "claude-opus-4-5",
+                "role": "assistant",
+                "content": [block],
+                "usage": {"input_tokens": 10, "output_tokens": output}
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_usage_entry_precedes_content() {
+        let mut file = NamedTempFile::new().unwrap();
+        let line = assistant_block_with_usage(
+            "msg-1",
+            serde_json::json!({"type": "text", "text": "hi"}),
+            5,
+        );
+        writeln!(file, "{}", line).unwrap();
+        file.flush().unwrap();
+
+        let result = parse_jsonl_file(file.path()).unwrap();
+        assert_eq!(result.entries.len(), 2);
+        match &result.entries[0] {
+            DisplayEntry::Usage {
+                message_id,
+                model,
+                usage,
+                ..
+            } => {
+                assert_eq!(message_id.as_deref(), Some("msg-1"));
+                assert_eq!(model.as_deref(), Some("claude-opus-4-5"));
+                assert_eq!(usage.input_tokens, 10);
+                assert_eq!(usage.output_tokens, 5);
+            }
+            _ => panic!("expected Usage"),
+        }
+        assert!(matches!(
+            result.entries[1],
+            DisplayEntry::AssistantText { .. }
+        ));
+    }
+
+    #[test]
+    fn test_streamed_usage_collapsed_to_last() {
+        // One response split across lines: thinking, text, then tool_use
+        let mut file = NamedTempFile::new().unwrap();
+        let blocks = [
+            serde_json::json!({"type": "thinking", "thinking": "hmm"}),
+            serde_json::json!({"type": "text", "text": "running it"}),
+            serde_json::json!({"type": "tool_use", "id": "tool-1", "name": "Bash", "input": {}}),
+        ];
+        for (i, block) in blocks.into_iter().enumerate() {
+            let line = assistant_block_with_usage("msg-1", block, (i as u64 + 1) * 10);
+            writeln!(file, "{}", line).unwrap();
+        }
+        file.flush().unwrap();
+
+        let result = parse_jsonl_file(file.path()).unwrap();
+        let usages: Vec<_> = result
+            .entries
+            .iter()
+            .filter_map(|e| match e {
+                DisplayEntry::Usage { usage, .. } => Some(usage.output_tokens),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(usages, vec![30]);
+        assert!(matches!(result.entries[0], DisplayEntry::Usage { .. }));
+        assert_eq!(result.entries.len(), 4);
+    }
+
+    #[test]
+    fn test_usage_does_not_block_tool_merge() {
+        let mut file = NamedTempFile::new().unwrap();
+        let call = assistant_block_with_usage(
+            "msg-1",
+            serde_json::json!({"type": "tool_use", "id": "tool-1", "name": "Bash", "input": {}}),
+            5,
+        );
+        let result_line = serde_json::json!({
+            "type": "user",
+            "message": {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "tool-1", "content": "ok"}]}
+        })
+        .to_string();
+        writeln!(file, "{}", call).unwrap();
+        writeln!(file, "{}", result_line).unwrap();
+        file.flush().unwrap();
+
+        let result = parse_jsonl_file(file.path()).unwrap();
+        let merged = merge_tool_results(result.entries);
+        assert_eq!(merged.len(), 2);
+        match &merged[1] {
+            DisplayEntry::ToolCall { result, .. } => assert!(result.is_some()),
+            _ => panic!("expected ToolCall"),
+        }
+    }
+
+    #[test]
+    fn test_zero_usage_not_emitted() {
+        let mut file = NamedTempFile::new().unwrap();
+        let line = assistant_block_with_usage(
+            "msg-1",
+            serde_json::json!({"type": "text", "text": "hi"}),
+            0,
+        )
+        .replace("\"input_tokens\":10", "\"input_tokens\":0");
+        writeln!(file, "{}", line).unwrap();
+        file.flush().unwrap();
+
+        let result = parse_jsonl_file(file.path()).unwrap();
+        assert_eq!(result.entries.len(), 1);
+        assert!(matches!(
+            result.entries[0],
+            DisplayEntry::AssistantText { .. }
+        ));
+    }
+
     #[test]
     fn test_merge_tool_results_intervening_entry() {
         // A ToolResult that doesn't immediately follow its ToolCall is NOT merged
@@ -1062,4 +1459,147 @@ mod tests {
             _ => panic!("expected ToolCall"),
         }
     }
+
+    #[test]
+    fn test_nodes_link_entries_to_parents() {
+        let mut file = NamedTempFile::new().unwrap();
+        let line = |kind: &str, uuid: &str, parent: Option<&str>, text: &str| {
+            serde_json::json!({
+                "type": kind,
+                "uuid": uuid,
+                "parentUuid": parent,
+                "message": {"role": kind, "content": text}
+            })
+            .to_string()
+        };
+        writeln!(file, "{}", line("user", "u1", None, "first")).unwrap();
+        writeln!(file, "{}", line("assistant", "a1", Some("u1"), "reply")).unwrap();
+        writeln!(file, "{}", line("user", "u2", None, "edited")).unwrap();
+        file.flush().unwrap();
+
+        let result = parse_jsonl_file(file.path()).unwrap();
+        assert_eq!(result.nodes.len(), 3);
+        assert_eq!(result.nodes[2].uuid.as_deref(), Some("u2"));
+        assert_eq!(result.nodes[1].parent_uuid.as_deref(), Some("u1"));
+        assert_eq!(result.nodes[2].entries, 2..3);
+        // The edited prompt replaces the first exchange on the latest branch
+        let entries = result.latest_branch();
+        assert!(matches!(
+            entries[0],
+            DisplayEntry::BranchPoint { branch: 2, .. }
+        ));
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn test_system_entries() {
+        let mut file = NamedTempFile::new().unwrap();
+        let lines = [
+            serde_json::json!({
+                "type": "system",
+                "subtype": "compact_boundary",
+                "content": "Conversation compacted",
+                "compactMetadata": {"trigger": "manual", "preTokens": 90000}
+            }),
+            serde_json::json!({
+                "type": "system",
+                "subtype": "api_error",
+                "level": "error",
+                "error": {"status": 529, "error": {"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}},
+                "retryInMs": 1137.4,
+                "retryAttempt": 2,
+                "maxRetries": 10
+            }),
+            serde_json::json!({
+                "type": "system",
+                "subtype": "local_command",
+                "content": "<command-name>/model</command-name>\n<command-message>model</command-message>\n<command-args>opus</command-args>"
+            }),
+            serde_json::json!({
+                "type": "system",
+                "subtype": "local_command",
+                "content": "<local-command-stdout>Set model to opus</local-command-stdout>"
+            }),
+        ];
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        file.flush().unwrap();
+
+        let result = parse_jsonl_file(file.path()).unwrap();
+        assert_eq!(result.entries.len(), 4);
+        assert!(matches!(
+            &result.entries[0],
+            DisplayEntry::CompactBoundary { trigger: Some(t), pre_tokens: Some(90000), .. } if t == "manual"
+        ));
+        match &result.entries[1] {
+            DisplayEntry::ApiError {
+                message,
+                retry_attempt,
+                retry_in_ms,
+                ..
+            } => {
+                assert_eq!(message, "529 Overloaded");
+                assert_eq!(*retry_attempt, Some(2));
+                assert_eq!(*retry_in_ms, Some(1137));
+            }
+            other => panic!("expected ApiError, got {:?}", other),
+        }
+        assert!(
+            matches!(&result.entries[2], DisplayEntry::LocalCommand { text, .. } if text == "/model opus")
+        );
+        assert!(
+            matches!(&result.entries[3], DisplayEntry::LocalCommand { text, .. } if text == "Set model to opus")
+        );
+    }
+
+    #[test]
+    fn test_metadata_emitted_on_change() {
+        let mut file = NamedTempFile::new().unwrap();
+        let line = |kind: &str, branch: &str, content: serde_json::Value| {
+            serde_json::json!({
+                "type": kind,
+                "cwd": "/src/app",
+                "gitBranch": branch,
+                "version": "1.0.80",
+                "message": {"role": kind, "content": content}
+            })
+        };
+        let lines = [
+            line("user", "main", serde_json::json!("hi")),
+            line(
+                "assistant",
+                "main",
+                serde_json::json!([{"type": "tool_use", "id": "t1", "name": "Bash", "input": {}}]),
+            ),
+            line(
+                "user",
+                "feature",
+                serde_json::json!([{"type": "tool_result", "tool_use_id": "t1", "content": "ok"}]),
+            ),
+        ];
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        file.flush().unwrap();
+
+        let entries = merge_tool_results(parse_jsonl_file(file.path()).unwrap().entries);
+        let branches: Vec<Option<&str>> = entries
+            .iter()
+            .filter_map(|entry| match entry {
+                DisplayEntry::Metadata { metadata, .. } => Some(metadata.git_branch.as_deref()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(branches, [Some("main"), Some("feature")]);
+        // The change after a tool result doesn't keep the result from merging into its call
+        assert!(matches!(
+            &entries[2],
+            DisplayEntry::ToolCall {
+                result: Some(_),
+                ..
+            }
+        ));
+        assert!(matches!(&entries[3], DisplayEntry::Metadata { .. }));
+    }
 }
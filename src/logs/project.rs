@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
 use std::time::SystemTime;
 
 use anyhow::Result;
@@ -102,7 +103,10 @@ pub struct Session {
     pub id: String,
     pub project_path: PathBuf,
     pub log_path: PathBuf,
+    /// Summary from `sessions-index.json`, or from a `summary` line in the log
     pub summary: Option<String>,
+    /// First line of the first prompt, shown when there is no summary
+    pub first_prompt: Option<String>,
     pub last_modified: std::time::SystemTime,
 }
 
@@ -127,6 +131,181 @@ pub fn get_claude_projects_dir() -> Option<PathBuf> {
     dirs::home_dir().map(|home| home.join(".claude").join("projects"))
 }
 
+/// How much of a log's head to scan for a title. Summary lines come first and the first
+/// prompt is usually within a few lines, so this only matters for unusual logs.
+const TITLE_SCAN_BYTES: u64 = 64 * 1024;
+
+/// Fields of a log line needed to find a session's title
+#[derive(Deserialize)]
+struct TitleLine {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    summary: Option<String>,
+    /// Last message of the conversation a summary describes
+    #[serde(default, rename = "leafUuid")]
+    leaf_uuid: Option<String>,
+    #[serde(default)]
+    uuid: Option<String>,
+    #[serde(default)]
+    message: Option<TitleMessage>,
+    #[serde(default, rename = "isMeta")]
+    is_meta: bool,
+    #[serde(default, rename = "isCompactSummary")]
+    is_compact_summary: bool,
+}
+
+#[derive(Deserialize)]
+struct TitleMessage {
+    #[serde(default)]
+    content: Option<serde_json::Value>,
+}
+
+/// Summary and first prompt of a session log
+type SessionTitle = (Option<String>, Option<String>);
+
+/// A title with the modification time and length of the log it was read from
+type CachedTitle = ((SystemTime, u64), SessionTitle);
+
+/// Titles already read, by log path. Sessions are rediscovered on every list refresh, and a log
+/// that starts with summaries may have to be read in full to check them.
+static TITLE_CACHE: LazyLock<Mutex<HashMap<PathBuf, CachedTitle>>> =
+    LazyLock::new(Default::default);
+
+/// The title of a session log, read again only when the file has changed since the last time
+fn session_title(path: &Path) -> SessionTitle {
+    let stamp = std::fs::metadata(path)
+        .ok()
+        .and_then(|meta| Some((meta.modified().ok()?, meta.len())));
+    if let Some(stamp) = stamp
+        && let Ok(cache) = TITLE_CACHE.lock()
+        && let Some((cached_stamp, title)) = cache.get(path)
+        && *cached_stamp == stamp
+    {
+        return title.clone();
+    }
+    let title = read_session_title(path);
+    if let Some(stamp) = stamp
+        && let Ok(mut cache) = TITLE_CACHE.lock()
+    {
+        cache.insert(path.to_path_buf(), (stamp, title.clone()));
+    }
+    title
+}
+
+/// Reads the start of a log for its title: the last `summary` line before the conversation
+/// starts, and the first line of the first prompt typed by the user.
+///
+/// A summary describes the conversation ending at its `leafUuid`, and a resumed session starts
+/// with summaries of the conversations it continues, whose leaves are in other logs. So a
+/// summary only counts if its leaf is a line of this log. Finding the leaf can mean reading the
+/// whole file, but only for logs that start with summaries; the rest stop at the first prompt.
+fn read_session_title(path: &Path) -> SessionTitle {
+    use std::collections::HashSet;
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+
+    let Ok(file) = File::open(path) else {
+        return (None, None);
+    };
+    // (leaf, text) of each summary, in file order
+    let mut summaries: Vec<(String, String)> = Vec::new();
+    let mut uuids = HashSet::new();
+    let mut first_prompt = None;
+    let mut scanned = 0;
+    let mut lines = BufReader::new(file).lines().map_while(Result::ok);
+    for line in lines.by_ref() {
+        scanned += line.len() as u64 + 1;
+        if scanned > TITLE_SCAN_BYTES {
+            break;
+        }
+        let Ok(entry) = serde_json::from_str::<TitleLine>(&line) else {
+            continue;
+        };
+        uuids.extend(entry.uuid);
+        match entry.kind.as_str() {
+            "summary" => {
+                if let Some(text) = entry.summary.filter(|s| !s.trim().is_empty())
+                    && let Some(leaf) = entry.leaf_uuid
+                {
+                    summaries.push((leaf, text.trim().to_string()));
+                }
+            }
+            "user" if !entry.is_meta && !entry.is_compact_summary => {
+                let content = entry.message.and_then(|m| m.content);
+                let text = match &content {
+                    Some(serde_json::Value::String(text)) => Some(text.as_str()),
+                    // Tool results have no text block, so they're skipped here
+                    Some(serde_json::Value::Array(blocks)) => blocks.iter().find_map(|block| {
+                        (block.get("type").and_then(|t| t.as_str()) == Some("text"))
+                            .then(|| block.get("text").and_then(|t| t.as_str()))
+                            .flatten()
+                    }),
+                    _ => None,
+                };
+                // Slash commands and their output are wrapped in tags like <command-name>
+                if let Some(first_line) = text
+                    .map(str::trim)
+                    .filter(|text| !text.is_empty() && !text.starts_with('<'))
+                    .and_then(|text| text.lines().next())
+                {
+                    first_prompt = Some(first_line.trim().to_string());
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut summary = summaries
+        .iter()
+        .rev()
+        .find(|(leaf, _)| uuids.contains(leaf))
+        .map(|(_, text)| text.clone());
+    if summary.is_none() && !summaries.is_empty() {
+        #[derive(Deserialize)]
+        struct UuidOnly {
+            uuid: Option<String>,
+        }
+        // Only parse the lines that mention one of the leaves
+        summary = lines
+            .filter(|line| {
+                summaries
+                    .iter()
+                    .any(|(leaf, _)| line.contains(leaf.as_str()))
+            })
+            .filter_map(|line| serde_json::from_str::<UuidOnly>(&line).ok()?.uuid)
+            .find_map(|uuid| {
+                summaries
+                    .iter()
+                    .rev()
+                    .find(|(leaf, _)| *leaf == uuid)
+                    .map(|(_, text)| text.clone())
+            });
+    }
+    (summary, first_prompt)
+}
+
+/// Working directory a session ran in: the `cwd` of the first log line that records one.
+/// Unlike `Project::original_path`, which is decoded from the directory name, this is exact
+/// for paths containing dashes.
+pub fn session_cwd(log_path: &Path) -> Option<PathBuf> {
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+
+    #[derive(Deserialize)]
+    struct CwdOnly {
+        cwd: Option<PathBuf>,
+    }
+
+    let file = File::open(log_path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| line.contains("\"cwd\""))
+        .find_map(|line| serde_json::from_str::<CwdOnly>(&line).ok()?.cwd)
+}
+
 /// Extracts the timestamp of the last entry in a JSONL file.
 ///
 /// Reads the tail of the file and parses the `timestamp` field from the last non-empty line.
@@ -266,7 +445,7 @@ pub fn discover_projects() -> Result<Vec<Project>> {
     }
 
     // Sort by last modified, newest first
-    projects.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    projects.sort_by_key(|p| std::cmp::Reverse(p.last_modified));
     Ok(projects)
 }
 
@@ -315,23 +494,96 @@ pub fn discover_sessions(project: &Project) -> Result<Vec<Session>> {
 
             let last_modified = get_last_jsonl_timestamp(&path);
 
-            let summary = summaries.get(&session_id).cloned().flatten();
+            let (logged_summary, first_prompt) = session_title(&path);
+            let summary = summaries
+                .get(&session_id)
+                .cloned()
+                .flatten()
+                .or(logged_summary);
 
             sessions.push(Session {
                 id: session_id,
                 project_path: project.path.clone(),
                 log_path: path,
                 summary,
+                first_prompt,
                 last_modified,
             });
         }
     }
 
     // Sort by last modified, newest first
-    sessions.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.last_modified));
     Ok(sessions)
 }
 
+/// Finds a session by ID (or unique ID prefix) across all projects.
+///
+/// A path to a session `.jsonl` file is also accepted.
+pub fn find_session(query: &str) -> Result<Session> {
+    let path = Path::new(query);
+    if path.extension().and_then(|e| e.to_str()) == Some("jsonl") && path.is_file() {
+        return session_from_log_path(path);
+    }
+
+    let mut matches = Vec::new();
+    for project in discover_projects()? {
+        for session in discover_sessions(&project)? {
+            if session.id == query {
+                return Ok(session);
+            }
+            if session.id.starts_with(query) {
+                matches.push(session);
+            }
+        }
+    }
+
+    match matches.len() {
+        0 => anyhow::bail!("No session matching '{}'", query),
+        1 => Ok(matches.remove(0)),
+        n => anyhow::bail!("'{}' matches {} sessions; use a longer ID", query, n),
+    }
+}
+
+/// Returns the session with the most recent activity across all projects
+pub fn latest_session() -> Result<Session> {
+    // Projects are sorted newest first, and so are their sessions
+    for project in discover_projects()? {
+        if let Some(session) = discover_sessions(&project)?.into_iter().next() {
+            return Ok(session);
+        }
+    }
+    anyhow::bail!("No sessions found in ~/.claude/projects")
+}
+
+/// Builds a session for a log file given directly by path
+fn session_from_log_path(path: &Path) -> Result<Session> {
+    let id = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_string();
+    let project_path = path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let (logged_summary, first_prompt) = session_title(path);
+    let summary = load_session_summaries(&project_path.join("sessions-index.json"))
+        .get(&id)
+        .cloned()
+        .flatten()
+        .or(logged_summary);
+
+    Ok(Session {
+        id,
+        project_path,
+        log_path: path.to_path_buf(),
+        summary,
+        first_prompt,
+        last_modified: get_last_jsonl_timestamp(path),
+    })
+}
+
 fn load_session_summaries(path: &Path) -> HashMap<String, Option<String>> {
     let mut summaries = HashMap::new();
 
@@ -362,45 +614,38 @@ impl Session {
         }
     }
 
-    /// Returns display name without timestamp: "summary" or "id..."
+    /// The session's summary, or its first prompt if it has none
+    pub fn title(&self) -> Option<&str> {
+        self.summary.as_deref().or(self.first_prompt.as_deref())
+    }
+
+    /// Returns display name without timestamp: "title" or "id..."
     pub fn display_name_without_timestamp(&self) -> String {
-        if let Some(ref summary) = self.summary {
-            // Truncate long summaries (respecting char boundaries)
-            if summary.len() > 40 {
-                let truncate_at = summary
+        if let Some(title) = self.title() {
+            // Truncate long titles (respecting char boundaries)
+            if title.len() > 40 {
+                let truncate_at = title
                     .char_indices()
                     .take_while(|(i, _)| *i < 37)
                     .last()
                     .map(|(i, c)| i + c.len_utf8())
-                    .unwrap_or(summary.len());
-                format!("{}...", &summary[..truncate_at])
+                    .unwrap_or(title.len());
+                format!("{}...", &title[..truncate_at])
             } else {
-                summary.clone()
+                title.to_string()
             }
         } else {
             self.short_id()
         }
     }
 
-    /// Returns display name with timestamp: "summary (HH:MM:SS)" or "id... (HH:MM:SS)"
+    /// Returns display name with timestamp: "title (HH:MM:SS)" or "id... (HH:MM:SS)"
     pub fn display_name(&self) -> String {
-        let timestamp = self.timestamp_str();
-        if let Some(ref summary) = self.summary {
-            // Truncate long summaries (respecting char boundaries)
-            if summary.len() > 40 {
-                let truncate_at = summary
-                    .char_indices()
-                    .take_while(|(i, _)| *i < 37)
-                    .last()
-                    .map(|(i, c)| i + c.len_utf8())
-                    .unwrap_or(summary.len());
-                format!("{}... ({})", &summary[..truncate_at], timestamp)
-            } else {
-                format!("{} ({})", summary, timestamp)
-            }
-        } else {
-            format!("{} ({})", self.short_id(), timestamp)
-        }
+        format!(
+            "{} ({})",
+            self.display_name_without_timestamp(),
+            self.timestamp_str()
+        )
     }
 }
 
@@ -448,12 +693,22 @@ pub fn discover_agents(session: &Session) -> Result<Vec<Agent>> {
 
     // Sort: Main first (already at index 0), then sub-agents by last_modified (newest first)
     if agents.len() > 1 {
-        agents[1..].sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+        agents[1..].sort_by_key(|a| std::cmp::Reverse(a.last_modified));
     }
 
     Ok(agents)
 }
 
+/// Finds an agent of a session by ID or display name (case-insensitive), e.g. "main"
+pub fn find_agent(session: &Session, query: &str) -> Result<Agent> {
+    discover_agents(session)?
+        .into_iter()
+        .find(|agent| {
+            agent.id.eq_ignore_ascii_case(query) || agent.display_name.eq_ignore_ascii_case(query)
+        })
+        .ok_or_else(|| anyhow::anyhow!("No agent '{}' in session {}", query, session.id))
+}
+
 struct AgentInfo {
     id: String,
     display_name: String,
@@ -493,3 +748,82 @@ fn parse_agent_filename(filename: &str) -> Option<AgentInfo> {
         display_name: rest.to_string(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn log(lines: &[serde_json::Value]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        file.flush().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_title_from_summary_line() {
+        let file = log(&[
+            serde_json::json!({"type": "summary", "summary": "Fix payment rounding", "leafUuid": "a1"}),
+            serde_json::json!({"type": "user", "uuid": "u1", "message": {"role": "user", "content": "hi"}}),
+            serde_json::json!({"type": "assistant", "uuid": "a1", "parentUuid": "u1", "message": {"role": "assistant", "content": "hello"}}),
+        ]);
+        let (summary, first_prompt) = read_session_title(file.path());
+        assert_eq!(summary.as_deref(), Some("Fix payment rounding"));
+        assert_eq!(first_prompt.as_deref(), Some("hi"));
+    }
+
+    #[test]
+    fn test_summary_of_another_log_is_ignored() {
+        // A resumed session starts with the summary of the conversation it continues
+        let file = log(&[
+            serde_json::json!({"type": "summary", "summary": "Earlier work", "leafUuid": "elsewhere"}),
+            serde_json::json!({"type": "user", "uuid": "u1", "message": {"role": "user", "content": "carry on"}}),
+            serde_json::json!({"type": "assistant", "uuid": "a1", "parentUuid": "u1", "message": {"role": "assistant", "content": "ok"}}),
+        ]);
+        let (summary, first_prompt) = read_session_title(file.path());
+        assert!(summary.is_none());
+        assert_eq!(first_prompt.as_deref(), Some("carry on"));
+    }
+
+    #[test]
+    fn test_session_cwd_from_first_line_with_one() {
+        let file = log(&[
+            serde_json::json!({"type": "summary", "summary": "Earlier work", "leafUuid": "a0"}),
+            serde_json::json!({"type": "user", "cwd": "/home/me/src/claude-tail", "message": {"content": "hi"}}),
+            serde_json::json!({"type": "user", "cwd": "/tmp", "message": {"content": "cd /tmp"}}),
+        ]);
+        assert_eq!(
+            session_cwd(file.path()),
+            Some(PathBuf::from("/home/me/src/claude-tail"))
+        );
+        assert_eq!(session_cwd(log(&[]).path()), None);
+    }
+
+    #[test]
+    fn test_session_title_rereads_changed_log() {
+        let prompt = |text: &str| serde_json::json!({"type": "user", "message": {"content": text}});
+        let file = log(&[prompt("first")]);
+        assert_eq!(session_title(file.path()).1.as_deref(), Some("first"));
+        assert_eq!(session_title(file.path()).1.as_deref(), Some("first"));
+
+        std::fs::write(file.path(), format!("{}\n", prompt("second try"))).unwrap();
+        assert_eq!(session_title(file.path()).1.as_deref(), Some("second try"));
+    }
+
+    #[test]
+    fn test_first_prompt_skips_commands_and_tool_results() {
+        let file = log(&[
+            serde_json::json!({"type": "user", "isMeta": true, "message": {"content": "Caveat: ..."}}),
+            serde_json::json!({"type": "user", "message": {"content": "<command-name>/clear</command-name>"}}),
+            serde_json::json!({"type": "user", "message": {"content": [{"type": "tool_result", "tool_use_id": "t1", "content": "ok"}]}}),
+            serde_json::json!({"type": "user", "message": {"content": [{"type": "text", "text": "\nadd a login page\nwith OAuth"}]}}),
+        ]);
+        let (summary, first_prompt) = read_session_title(file.path());
+        assert!(summary.is_none());
+        assert_eq!(first_prompt.as_deref(), Some("add a login page"));
+    }
+}
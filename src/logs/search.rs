@@ -0,0 +1,267 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+
+use super::parser::{merge_tool_results, parse_jsonl_file};
+use super::project::{Project, Session, discover_agents, discover_sessions};
+use super::types::{Agent, DisplayEntry};
+
+/// Characters of context kept before a match in a snippet
+const SNIPPET_LEAD: usize = 30;
+
+/// Maximum characters in a snippet
+const SNIPPET_WIDTH: usize = 120;
+
+/// A conversation entry matching a cross-project search
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    /// Abbreviated project path, e.g. `~/s/c/my-project`
+    pub project_name: String,
+    /// Project directory under `~/.claude/projects`
+    pub project_path: PathBuf,
+    pub session_id: String,
+    /// Session log path, used to select the session
+    pub session_log_path: PathBuf,
+    pub agent_name: String,
+    /// Log file the match was found in (the session log or a sub-agent log)
+    pub log_path: PathBuf,
+    /// Index of the matching entry in the merged conversation, as the conversation pane loads it
+    pub entry_index: usize,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Single-line excerpt around the first match
+    pub snippet: String,
+}
+
+/// Smartcase: the search is case-sensitive only if the pattern contains an uppercase letter
+pub fn is_case_sensitive(pattern: &str) -> bool {
+    pattern.chars().any(|c| c.is_uppercase())
+}
+
+/// Whether any searchable text of `entry` contains `pattern`
+pub fn entry_matches(entry: &DisplayEntry, pattern: &str) -> bool {
+    find_match(entry, pattern).is_some()
+}
+
+/// First searchable text of `entry` containing `pattern`, with the match's byte offset.
+/// ASCII case folding keeps byte offsets identical between folded and original text.
+fn find_match<'a>(entry: &'a DisplayEntry, pattern: &str) -> Option<(&'a str, usize)> {
+    if pattern.is_empty() {
+        return None;
+    }
+    let case_sensitive = is_case_sensitive(pattern);
+    let needle = if case_sensitive {
+        pattern.to_string()
+    } else {
+        pattern.to_ascii_lowercase()
+    };
+    entry.search_fragments().into_iter().find_map(|text| {
+        let pos = if case_sensitive {
+            text.find(&needle)
+        } else {
+            text.to_ascii_lowercase().find(&needle)
+        };
+        pos.map(|pos| (text, pos))
+    })
+}
+
+/// Single-line excerpt of `entry` around the first match of `pattern`
+pub fn match_snippet(entry: &DisplayEntry, pattern: &str) -> Option<String> {
+    let (text, pos) = find_match(entry, pattern)?;
+
+    // Start a little before the match, on a char boundary
+    let start = text[..pos]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_LEAD.saturating_sub(1))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+
+    let excerpt: String = text[start..]
+        .chars()
+        .take(SNIPPET_WIDTH)
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+
+    let mut snippet = excerpt.split_whitespace().collect::<Vec<_>>().join(" ");
+    if start > 0 {
+        snippet.insert_str(0, "...");
+    }
+    Some(snippet)
+}
+
+/// Searches every session (and sub-agent) log of the given projects for `pattern`.
+///
+/// Logs are fully parsed, so matches are found in the same text the conversation pane
+/// shows. Files that fail to parse are skipped. Logs are searched most recently modified
+/// first, and the search stops once `limit` hits are found. Hits are returned newest first.
+pub fn search_projects(projects: &[Project], pattern: &str, limit: usize) -> Vec<SearchHit> {
+    let mut hits = Vec::new();
+    if pattern.is_empty() || limit == 0 {
+        return hits;
+    }
+
+    let sessions: Vec<(&Project, Session, Vec<Agent>)> = projects
+        .iter()
+        .flat_map(|project| {
+            discover_sessions(project)
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |session| (project, session))
+        })
+        .filter_map(|(project, session)| {
+            let agents = discover_agents(&session).ok()?;
+            Some((project, session, agents))
+        })
+        .collect();
+    let mut logs: Vec<(&Project, &Session, &Agent)> = sessions
+        .iter()
+        .flat_map(|(project, session, agents)| {
+            agents.iter().map(move |agent| (*project, session, agent))
+        })
+        .collect();
+    logs.sort_by_key(|(_, _, agent)| std::cmp::Reverse(agent.last_modified));
+
+    'logs: for (project, session, agent) in logs {
+        let Ok(result) = parse_jsonl_file(&agent.log_path) else {
+            continue;
+        };
+        let entries = merge_tool_results(result.latest_branch());
+        for (entry_index, entry) in entries.iter().enumerate() {
+            if let Some(snippet) = match_snippet(entry, pattern) {
+                hits.push(SearchHit {
+                    project_name: project.abbreviated_path(),
+                    project_path: project.path.clone(),
+                    session_id: session.id.clone(),
+                    session_log_path: session.log_path.clone(),
+                    agent_name: agent.display_name.clone(),
+                    log_path: agent.log_path.clone(),
+                    entry_index,
+                    timestamp: entry.timestamp(),
+                    snippet,
+                });
+                if hits.len() == limit {
+                    break 'logs;
+                }
+            }
+        }
+    }
+
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.timestamp));
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn user(text: &str) -> DisplayEntry {
+        DisplayEntry::UserMessage {
+            text: text.to_string(),
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_smartcase() {
+        assert!(!entry_matches(&user("Fix payments.rs"), "PAYMENTS"));
+        assert!(entry_matches(&user("Fix payments.rs"), "fix"));
+        assert!(entry_matches(&user("fix payments.rs"), "payments.rs"));
+        assert!(!entry_matches(&user("fix payments.rs"), ""));
+    }
+
+    #[test]
+    fn test_snippet_trims_leading_context() {
+        let text = format!("{}needle and more", "x".repeat(100));
+        let snippet = match_snippet(&user(&text), "needle").unwrap();
+        assert!(snippet.starts_with("..."));
+        assert!(snippet.contains("needle and more"));
+        assert!(snippet.len() < text.len());
+    }
+
+    #[test]
+    fn test_snippet_is_single_line() {
+        let snippet = match_snippet(&user("first line\n  second  needle\nthird"), "needle");
+        assert_eq!(snippet.as_deref(), Some("first line second needle third"));
+    }
+
+    /// A project directory holding one session log per (session id, prompts) pair
+    fn project_with_sessions(dir: &std::path::Path, sessions: &[(&str, &[&str])]) -> Project {
+        let project_path = dir.join("-home-user-shop");
+        std::fs::create_dir_all(&project_path).unwrap();
+        for (id, prompts) in sessions {
+            let body: Vec<String> = prompts
+                .iter()
+                .map(|prompt| {
+                    serde_json::json!({"type": "user", "timestamp": "2025-01-01T00:00:00Z",
+                        "message": {"role": "user", "content": prompt}})
+                    .to_string()
+                })
+                .collect();
+            std::fs::write(project_path.join(format!("{}.jsonl", id)), body.join("\n")).unwrap();
+        }
+        Project {
+            name: "shop".to_string(),
+            path: project_path,
+            encoded_path: "-home-user-shop".to_string(),
+            original_path: PathBuf::from("/home/user/shop"),
+            last_modified: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_search_projects_finds_entries_in_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_path = dir.path().join("-home-user-shop");
+        std::fs::create_dir_all(&project_path).unwrap();
+        let lines = [
+            serde_json::json!({"type": "user", "timestamp": "2025-01-01T00:00:00Z",
+                "message": {"role": "user", "content": "refactor payments.rs please"}}),
+            serde_json::json!({"type": "assistant", "timestamp": "2025-01-01T00:00:05Z",
+                "message": {"role": "assistant", "content": [{"type": "text", "text": "Done"}]}}),
+        ];
+        let body: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        std::fs::write(project_path.join("abc123.jsonl"), body.join("\n")).unwrap();
+
+        let project = Project {
+            name: "shop".to_string(),
+            path: project_path.clone(),
+            encoded_path: "-home-user-shop".to_string(),
+            original_path: PathBuf::from("/home/user/shop"),
+            last_modified: SystemTime::now(),
+        };
+
+        let hits = search_projects(&[project], "payments", 100);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "abc123");
+        assert_eq!(hits[0].agent_name, "Main");
+        assert_eq!(hits[0].entry_index, 0);
+        assert_eq!(hits[0].snippet, "refactor payments.rs please");
+    }
+
+    #[test]
+    fn test_search_projects_stops_at_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = project_with_sessions(
+            dir.path(),
+            &[
+                ("old", &["fix the bug", "fix it again"]),
+                ("new", &["fix the build", "fix the tests", "fix the docs"]),
+            ],
+        );
+        // Make sure the newer session is the one searched first
+        let old_log = project.path.join("old.jsonl");
+        let earlier = SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&old_log)
+            .unwrap()
+            .set_modified(earlier)
+            .unwrap();
+
+        let hits = search_projects(std::slice::from_ref(&project), "fix", 2);
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|hit| hit.session_id == "new"));
+        assert_eq!(search_projects(&[project], "fix", 100).len(), 5);
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::files::{FileOperation, file_operation};
+use super::types::DisplayEntry;
+use super::usage::UsageTotals;
+
+/// Calls and failures of one tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolStats {
+    pub name: String,
+    pub calls: usize,
+    pub errors: usize,
+}
+
+impl ToolStats {
+    /// Fraction of calls that returned an error, from 0.0 to 1.0
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.calls as f64
+        }
+    }
+}
+
+/// Summary of a conversation for the statistics view
+#[derive(Debug, Clone, Default)]
+pub struct SessionStats {
+    /// Per-tool counts, most used first
+    pub tools: Vec<ToolStats>,
+    pub prompts: usize,
+    /// `Task`/`Agent` calls, each of which runs a sub-agent
+    pub sub_agents: usize,
+    /// Distinct files touched by each kind of operation
+    pub files_read: usize,
+    pub files_written: usize,
+    pub files_edited: usize,
+    pub usage: UsageTotals,
+    /// Times of prompts, responses and tool calls, in log order
+    activity: Vec<DateTime<Utc>>,
+}
+
+impl SessionStats {
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a DisplayEntry>) -> Self {
+        let mut stats = Self::default();
+        let mut tools: HashMap<&str, ToolStats> = HashMap::new();
+        let mut files: HashSet<(FileOperation, String)> = HashSet::new();
+
+        for entry in entries {
+            match entry {
+                DisplayEntry::UserMessage { .. } => stats.prompts += 1,
+                DisplayEntry::ToolCall {
+                    name,
+                    input,
+                    result,
+                    ..
+                } => {
+                    let tool = tools.entry(name).or_insert_with(|| ToolStats {
+                        name: name.clone(),
+                        calls: 0,
+                        errors: 0,
+                    });
+                    tool.calls += 1;
+                    if result.as_ref().is_some_and(|r| r.is_error) {
+                        tool.errors += 1;
+                    }
+                    if name == "Task" || name == "Agent" {
+                        stats.sub_agents += 1;
+                    }
+                    if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(input)
+                        && let Some((operation, path)) = file_operation(name, &parsed)
+                    {
+                        files.insert((operation, path.to_string()));
+                    }
+                }
+                DisplayEntry::Usage { .. } => stats.usage.record_entries([entry]),
+                _ => {}
+            }
+            if matches!(
+                entry,
+                DisplayEntry::UserMessage { .. }
+                    | DisplayEntry::AssistantText { .. }
+                    | DisplayEntry::ToolCall { .. }
+                    | DisplayEntry::Thinking { .. }
+            ) && let Some(timestamp) = entry.timestamp()
+            {
+                stats.activity.push(timestamp);
+            }
+        }
+
+        stats.tools = tools.into_values().collect();
+        stats
+            .tools
+            .sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.name.cmp(&b.name)));
+        for (operation, _) in &files {
+            match operation {
+                FileOperation::Read => stats.files_read += 1,
+                FileOperation::Write => stats.files_written += 1,
+                FileOperation::Edit => stats.files_edited += 1,
+            }
+        }
+        stats
+    }
+
+    pub fn tool_calls(&self) -> usize {
+        self.tools.iter().map(|tool| tool.calls).sum()
+    }
+
+    pub fn tool_errors(&self) -> usize {
+        self.tools.iter().map(|tool| tool.errors).sum()
+    }
+
+    pub fn started(&self) -> Option<DateTime<Utc>> {
+        self.activity.iter().min().copied()
+    }
+
+    pub fn ended(&self) -> Option<DateTime<Utc>> {
+        self.activity.iter().max().copied()
+    }
+
+    /// Wall-clock time from the first to the last activity
+    pub fn duration(&self) -> Option<Duration> {
+        Some(self.ended()? - self.started()?)
+    }
+
+    /// Activity counts in `buckets` equal slices of the session's duration
+    pub fn activity(&self, buckets: usize) -> Vec<u64> {
+        let mut counts = vec![0; buckets];
+        let (Some(started), Some(duration)) = (self.started(), self.duration()) else {
+            return counts;
+        };
+        if buckets == 0 {
+            return counts;
+        }
+        let span = duration.num_milliseconds().max(1);
+        for timestamp in &self.activity {
+            let offset = (*timestamp - started).num_milliseconds();
+            let bucket = (offset * buckets as i64 / span).clamp(0, buckets as i64 - 1);
+            counts[bucket as usize] += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, input: serde_json::Value, is_error: bool, seconds: i64) -> DisplayEntry {
+        DisplayEntry::tool_call(name, input)
+            .at(DateTime::from_timestamp(1_700_000_000 + seconds, 0))
+            .with_result("", is_error, None)
+    }
+
+    #[test]
+    fn test_session_stats() {
+        let entries = vec![
+            call("Read", serde_json::json!({"file_path": "/a.rs"}), false, 0),
+            call("Read", serde_json::json!({"file_path": "/a.rs"}), false, 10),
+            call("Edit", serde_json::json!({"file_path": "/a.rs"}), true, 20),
+            call("Edit", serde_json::json!({"file_path": "/b.rs"}), false, 30),
+            call(
+                "Task",
+                serde_json::json!({"description": "explore"}),
+                false,
+                40,
+            ),
+        ];
+        let stats = SessionStats::from_entries(&entries);
+
+        assert_eq!(stats.tool_calls(), 5);
+        assert_eq!(stats.tools[0].name, "Edit");
+        assert_eq!(stats.tools[0].error_rate(), 0.5);
+        assert_eq!(stats.sub_agents, 1);
+        assert_eq!(
+            (stats.files_read, stats.files_written, stats.files_edited),
+            (1, 0, 2)
+        );
+        assert_eq!(stats.duration(), Some(Duration::seconds(40)));
+        assert_eq!(stats.activity(4), vec![1, 1, 1, 2]);
+    }
+}
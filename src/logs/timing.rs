@@ -0,0 +1,223 @@
+use chrono::{DateTime, Duration, Utc};
+
+use super::types::DisplayEntry;
+
+/// A tool call and how long it took, for the slowest-tools summary
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolTiming {
+    pub name: String,
+    /// Short description of the call: the command, file path or pattern
+    pub detail: String,
+    pub duration: Duration,
+    /// Index of the call in the entry list
+    pub entry_index: usize,
+}
+
+/// Wall-clock time between a tool call and its result, if both were timestamped
+pub fn tool_duration(entry: &DisplayEntry) -> Option<Duration> {
+    match entry {
+        DisplayEntry::ToolCall {
+            timestamp: Some(start),
+            result: Some(result),
+            ..
+        } => result
+            .timestamp
+            .map(|end| end - *start)
+            .filter(|duration| *duration >= Duration::zero()),
+        _ => None,
+    }
+}
+
+/// Entries that can sit between a prompt and the response without being part of either
+fn is_between_turns(entry: &DisplayEntry) -> bool {
+    matches!(
+        entry,
+        DisplayEntry::Usage { .. }
+            | DisplayEntry::Metadata { .. }
+            | DisplayEntry::HookEvent { .. }
+            | DisplayEntry::ApiError { .. }
+            | DisplayEntry::SystemNotice { .. }
+            | DisplayEntry::BranchPoint { .. }
+    )
+}
+
+fn is_assistant_output(entry: &DisplayEntry) -> bool {
+    matches!(
+        entry,
+        DisplayEntry::AssistantText { .. }
+            | DisplayEntry::ToolCall { .. }
+            | DisplayEntry::Thinking { .. }
+    )
+}
+
+/// Tracks the time from each user prompt to the first assistant output after it.
+///
+/// Feed entries in order; `record` returns the model's think time for the entry that
+/// ends the wait.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseTimer {
+    prompt_at: Option<DateTime<Utc>>,
+}
+
+impl ResponseTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, entry: &DisplayEntry) -> Option<Duration> {
+        match entry {
+            DisplayEntry::UserMessage { timestamp, .. } => {
+                self.prompt_at = *timestamp;
+                None
+            }
+            entry if is_between_turns(entry) => None,
+            entry => {
+                let prompt_at = self.prompt_at.take()?;
+                if !is_assistant_output(entry) {
+                    return None;
+                }
+                entry
+                    .timestamp()
+                    .map(|at| at - prompt_at)
+                    .filter(|duration| *duration >= Duration::zero())
+            }
+        }
+    }
+}
+
+/// Think time for `entry`: the time from the prompt that precedes it in `entries` (the
+/// entries before it), if `entry` is the first output after that prompt
+pub fn response_time<'a>(
+    entries: impl DoubleEndedIterator<Item = &'a DisplayEntry>,
+    entry: &DisplayEntry,
+) -> Option<Duration> {
+    if !is_assistant_output(entry) {
+        return None;
+    }
+    let previous = entries.rev().find(|e| !is_between_turns(e))?;
+    let mut timer = ResponseTimer::new();
+    timer.record(previous);
+    timer.record(entry)
+}
+
+/// The `limit` slowest tool calls, slowest first
+pub fn slowest_tools<'a>(
+    entries: impl IntoIterator<Item = &'a DisplayEntry>,
+    limit: usize,
+) -> Vec<ToolTiming> {
+    let mut timings: Vec<ToolTiming> = entries
+        .into_iter()
+        .enumerate()
+        .filter_map(|(entry_index, entry)| {
+            let duration = tool_duration(entry)?;
+            let DisplayEntry::ToolCall { name, input, .. } = entry else {
+                return None;
+            };
+            Some(ToolTiming {
+                name: name.clone(),
+                detail: tool_detail(input),
+                duration,
+                entry_index,
+            })
+        })
+        .collect();
+    timings.sort_by_key(|timing| std::cmp::Reverse(timing.duration));
+    timings.truncate(limit);
+    timings
+}
+
+/// First line of the most descriptive input field of a tool call
+fn tool_detail(input: &str) -> String {
+    let parsed: Option<serde_json::Value> = serde_json::from_str(input).ok();
+    [
+        "description",
+        "command",
+        "file_path",
+        "pattern",
+        "url",
+        "query",
+        "prompt",
+    ]
+    .iter()
+    .find_map(|key| parsed.as_ref()?.get(key)?.as_str())
+    .and_then(|text| text.lines().next())
+    .unwrap_or_default()
+    .to_string()
+}
+
+/// Formats a duration compactly: 850ms, 12.4s, 3m 05s, 1h 02m
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.num_milliseconds().max(0);
+    if millis < 1_000 {
+        format!("{}ms", millis)
+    } else if millis < 60_000 {
+        format!("{:.1}s", millis as f64 / 1_000.0)
+    } else if millis < 3_600_000 {
+        format!("{}m {:02}s", millis / 60_000, (millis / 1_000) % 60)
+    } else {
+        format!("{}h {:02}m", millis / 3_600_000, (millis / 60_000) % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(1_700_000_000 + seconds, 0)
+    }
+
+    fn call(name: &str, start: i64, end: i64) -> DisplayEntry {
+        DisplayEntry::tool_call(name, serde_json::json!({"command": "cargo test"}))
+            .at(at(start))
+            .with_result("", false, at(end))
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::milliseconds(850)), "850ms");
+        assert_eq!(format_duration(Duration::milliseconds(12_400)), "12.4s");
+        assert_eq!(format_duration(Duration::seconds(185)), "3m 05s");
+        assert_eq!(format_duration(Duration::minutes(62)), "1h 02m");
+    }
+
+    #[test]
+    fn test_response_timer() {
+        let prompt = DisplayEntry::UserMessage {
+            text: "go".to_string(),
+            timestamp: at(0),
+        };
+        let usage = DisplayEntry::Usage {
+            message_id: None,
+            model: None,
+            usage: Default::default(),
+            timestamp: at(3),
+        };
+        let reply = DisplayEntry::AssistantText {
+            text: "ok".to_string(),
+            timestamp: at(4),
+        };
+        let mut timer = ResponseTimer::new();
+        assert_eq!(timer.record(&prompt), None);
+        assert_eq!(timer.record(&usage), None);
+        assert_eq!(timer.record(&reply), Some(Duration::seconds(4)));
+        // Only the first output after a prompt counts
+        assert_eq!(timer.record(&reply), None);
+
+        assert_eq!(
+            response_time([prompt, usage].iter(), &reply),
+            Some(Duration::seconds(4))
+        );
+    }
+
+    #[test]
+    fn test_slowest_tools() {
+        let entries = vec![call("Bash", 0, 2), call("Read", 2, 3), call("Bash", 3, 13)];
+        let slowest = slowest_tools(&entries, 2);
+        assert_eq!(slowest.len(), 2);
+        assert_eq!(slowest[0].duration, Duration::seconds(10));
+        assert_eq!(slowest[0].entry_index, 2);
+        assert_eq!(slowest[0].detail, "cargo test");
+        assert_eq!(slowest[1].duration, Duration::seconds(2));
+    }
+}
@@ -0,0 +1,424 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use chrono::{DateTime, Utc};
+
+use super::types::DisplayEntry;
+
+/// Links one log line into the conversation tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryNode {
+    pub uuid: Option<String>,
+    pub parent_uuid: Option<String>,
+    /// Range of this line's entries in `ParseResult::entries`
+    pub entries: Range<usize>,
+}
+
+/// One line of the log, placed in the tree. Only the links are kept: entries are parsed from
+/// the log again whenever a branch is laid out, so the tree stays small however long the
+/// session grows.
+struct Node {
+    parent: Option<usize>,
+    /// The prompt node that starts the turn this node belongs to
+    turn: Option<usize>,
+    /// First line of the prompt, for prompt nodes
+    label: Option<String>,
+    timestamp: Option<DateTime<Utc>>,
+}
+
+/// A leaf of the conversation tree, as offered in the branch picker
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+    /// Identifies the branch when switching to it
+    pub leaf: usize,
+    /// First line of the prompt where this branch split off (or of its last prompt)
+    pub label: String,
+    /// Number of forks between the start of the conversation and this branch
+    pub depth: usize,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// The conversation as a tree of turns, built from the `uuid`/`parentUuid` links.
+///
+/// Rewinding or editing a prompt appends a new prompt whose parent is an earlier message, so
+/// one log file can hold several branches. Forks are tracked between user prompts: a turn is a
+/// prompt and everything that follows from it, and a turn with more than one follow-up prompt
+/// is a fork. Tool results and progress lines never start a branch, which keeps parallel tool
+/// calls (whose results all point at the same assistant message) from looking like forks.
+pub struct ConversationTree {
+    nodes: Vec<Node>,
+    by_uuid: HashMap<String, usize>,
+    /// Prompt nodes keyed by the turn they follow (None for prompts that start the conversation)
+    children: HashMap<Option<usize>, Vec<usize>>,
+}
+
+impl ConversationTree {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            by_uuid: HashMap::new(),
+            children: HashMap::new(),
+        }
+    }
+
+    /// Adds freshly parsed lines. Returns true if one of them started a new branch.
+    pub fn extend(&mut self, entries: &[DisplayEntry], nodes: &[EntryNode]) -> bool {
+        let mut forked = false;
+        for (i, node) in nodes.iter().enumerate() {
+            let index = self.nodes.len();
+            // A null parent starts the conversation (or an edit of its first prompt). Lines
+            // without links, or whose parent we skipped, continue from the line before them.
+            let parent = match &node.parent_uuid {
+                None if node.uuid.is_some() => None,
+                parent => parent
+                    .as_ref()
+                    .and_then(|uuid| self.by_uuid.get(uuid).copied())
+                    .or_else(|| index.checked_sub(1)),
+            };
+            let parent_turn = parent.and_then(|p| self.nodes[p].turn);
+            let entries = &entries[node_range(nodes, i, entries.len())];
+            let label = entries.iter().find_map(|entry| match entry {
+                DisplayEntry::UserMessage { text, .. } => {
+                    Some(text.lines().next().unwrap_or_default().to_string())
+                }
+                _ => None,
+            });
+            let turn = if label.is_some() {
+                let siblings = self.children.entry(parent_turn).or_default();
+                forked |= !siblings.is_empty();
+                siblings.push(index);
+                Some(index)
+            } else {
+                parent_turn
+            };
+
+            if let Some(uuid) = &node.uuid {
+                self.by_uuid.insert(uuid.clone(), index);
+            }
+            self.nodes.push(Node {
+                parent,
+                turn,
+                label,
+                timestamp: entries.first().and_then(DisplayEntry::timestamp),
+            });
+        }
+        forked
+    }
+
+    /// Number of log lines in the tree
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn has_forks(&self) -> bool {
+        self.children.values().any(|prompts| prompts.len() > 1)
+    }
+
+    /// The most recent prompt, which ends the branch that is currently being written
+    pub fn latest_leaf(&self) -> Option<usize> {
+        (0..self.nodes.len())
+            .rev()
+            .find(|&index| self.nodes[index].turn == Some(index))
+    }
+
+    /// Every branch of the conversation, depth first with siblings in file order
+    pub fn branches(&self) -> Vec<Branch> {
+        let mut branches = Vec::new();
+        let mut stack: Vec<usize> = self
+            .children
+            .get(&None)
+            .map(|roots| roots.iter().rev().copied().collect())
+            .unwrap_or_default();
+
+        while let Some(prompt) = stack.pop() {
+            match self.children.get(&Some(prompt)) {
+                Some(next) => stack.extend(next.iter().rev()),
+                None => branches.push(self.branch(prompt)),
+            }
+        }
+        branches
+    }
+
+    fn branch(&self, leaf: usize) -> Branch {
+        let forks: Vec<usize> = self
+            .turn_path(leaf)
+            .into_iter()
+            .filter(|&prompt| self.siblings(prompt).len() > 1)
+            .collect();
+        let label_node = forks.first().copied().unwrap_or(leaf);
+        Branch {
+            leaf,
+            label: self.nodes[label_node].label.clone().unwrap_or_default(),
+            depth: forks.len(),
+            timestamp: self.nodes[leaf].timestamp,
+        }
+    }
+
+    /// Prompts from `leaf` back to the start of the conversation
+    fn turn_path(&self, leaf: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut turn = Some(leaf);
+        while let Some(prompt) = turn {
+            path.push(prompt);
+            turn = self.nodes[prompt]
+                .parent
+                .and_then(|parent| self.nodes[parent].turn);
+        }
+        path
+    }
+
+    /// Prompts that follow the same turn as `prompt`, including itself
+    fn siblings(&self, prompt: usize) -> &[usize] {
+        let parent_turn = self.nodes[prompt]
+            .parent
+            .and_then(|parent| self.nodes[parent].turn);
+        self.children
+            .get(&parent_turn)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Entries along the branch ending at `leaf` (the latest branch if None), with a
+    /// `BranchPoint` marker ahead of every prompt that has siblings.
+    ///
+    /// `entries` and `nodes` are the log parsed from its start; lines past the end of the tree
+    /// are left out.
+    pub fn branch_entries(
+        &self,
+        leaf: Option<usize>,
+        mut entries: Vec<DisplayEntry>,
+        nodes: &[EntryNode],
+    ) -> Vec<DisplayEntry> {
+        let path: HashSet<usize> = leaf
+            .or_else(|| self.latest_leaf())
+            .map(|leaf| self.turn_path(leaf).into_iter().collect())
+            .unwrap_or_default();
+
+        if let Some(next) = nodes.get(self.nodes.len()) {
+            entries.truncate(next.entries.start);
+        }
+        let nodes = &nodes[..nodes.len().min(self.nodes.len())];
+        // Take each line's entries off the back so nothing has to be cloned
+        let mut grouped: Vec<Vec<DisplayEntry>> = nodes
+            .iter()
+            .rev()
+            .map(|node| entries.split_off(node.entries.start.min(entries.len())))
+            .collect();
+        grouped.reverse();
+
+        let mut branch = Vec::new();
+        let mut last_usage_idx: Option<usize> = None;
+        let mut last_metadata = None;
+        for (index, (node, line)) in self.nodes.iter().zip(grouped).enumerate() {
+            if let Some(turn) = node.turn
+                && !path.contains(&turn)
+            {
+                continue;
+            }
+            if node.turn == Some(index) {
+                let siblings = self.siblings(index);
+                if siblings.len() > 1 {
+                    branch.push(DisplayEntry::BranchPoint {
+                        branch: siblings.iter().position(|&p| p == index).unwrap_or(0) + 1,
+                        branches: siblings.len(),
+                        timestamp: node.timestamp,
+                    });
+                }
+            }
+            for entry in line {
+                // Metadata repeats when a line on another branch changed it in between
+                if let DisplayEntry::Metadata { metadata, .. } = &entry {
+                    if last_metadata.as_ref() == Some(metadata) {
+                        continue;
+                    }
+                    last_metadata = Some(metadata.clone());
+                }
+                // A message streamed across several lines repeats its usage; keep the latest
+                if let DisplayEntry::Usage { message_id, .. } = &entry {
+                    if message_id.is_some()
+                        && let Some(idx) = last_usage_idx
+                        && matches!(&branch[idx], DisplayEntry::Usage { message_id: prev, .. } if prev == message_id)
+                    {
+                        branch[idx] = entry;
+                        continue;
+                    }
+                    last_usage_idx = Some(branch.len());
+                }
+                branch.push(entry);
+            }
+        }
+        branch
+    }
+}
+
+/// Entries of the line at `nodes[index]`: up to where the next line's entries start
+fn node_range(nodes: &[EntryNode], index: usize, len: usize) -> Range<usize> {
+    let end = nodes.get(index + 1).map_or(len, |next| next.entries.start);
+    nodes[index].entries.start.min(len)..end.min(len)
+}
+
+impl Default for ConversationTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(text: &str) -> DisplayEntry {
+        DisplayEntry::UserMessage {
+            text: text.to_string(),
+            timestamp: None,
+        }
+    }
+
+    fn reply(text: &str) -> DisplayEntry {
+        DisplayEntry::AssistantText {
+            text: text.to_string(),
+            timestamp: None,
+        }
+    }
+
+    /// A parsed log and the tree built from it
+    struct Log {
+        tree: ConversationTree,
+        entries: Vec<DisplayEntry>,
+        nodes: Vec<EntryNode>,
+    }
+
+    impl Log {
+        fn branch_entries(&self, leaf: Option<usize>) -> Vec<DisplayEntry> {
+            self.tree
+                .branch_entries(leaf, self.entries.clone(), &self.nodes)
+        }
+    }
+
+    /// Builds a tree from (uuid, parent, entry) triples, one entry per line
+    fn tree(lines: &[(&str, Option<&str>, DisplayEntry)]) -> Log {
+        let nodes: Vec<EntryNode> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, (uuid, parent, _))| EntryNode {
+                uuid: Some(uuid.to_string()),
+                parent_uuid: parent.map(str::to_string),
+                entries: i..i + 1,
+            })
+            .collect();
+        let entries: Vec<DisplayEntry> = lines.iter().map(|(_, _, entry)| entry.clone()).collect();
+        let mut tree = ConversationTree::new();
+        tree.extend(&entries, &nodes);
+        Log {
+            tree,
+            entries,
+            nodes,
+        }
+    }
+
+    fn texts(entries: &[DisplayEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| match entry {
+                DisplayEntry::UserMessage { text, .. }
+                | DisplayEntry::AssistantText { text, .. } => text.clone(),
+                DisplayEntry::BranchPoint {
+                    branch, branches, ..
+                } => format!("fork {}/{}", branch, branches),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_linear_conversation_has_no_forks() {
+        let log = tree(&[
+            ("u1", None, prompt("hi")),
+            ("a1", Some("u1"), reply("hello")),
+            ("u2", Some("a1"), prompt("bye")),
+        ]);
+        assert!(!log.tree.has_forks());
+        assert_eq!(texts(&log.branch_entries(None)), ["hi", "hello", "bye"]);
+        assert_eq!(log.tree.branches().len(), 1);
+    }
+
+    #[test]
+    fn test_rewound_prompt_creates_branches() {
+        let log = tree(&[
+            ("u1", None, prompt("hi")),
+            ("a1", Some("u1"), reply("hello")),
+            ("u2", Some("a1"), prompt("first try")),
+            ("a2", Some("u2"), reply("one")),
+            ("u3", Some("a1"), prompt("second try")),
+            ("a3", Some("u3"), reply("two")),
+        ]);
+        assert!(log.tree.has_forks());
+        assert_eq!(
+            texts(&log.branch_entries(None)),
+            ["hi", "hello", "fork 2/2", "second try", "two"]
+        );
+
+        let branches = log.tree.branches();
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0].label, "first try");
+        assert_eq!(branches[0].depth, 1);
+        assert_eq!(
+            texts(&log.branch_entries(Some(branches[0].leaf))),
+            ["hi", "hello", "fork 1/2", "first try", "one"]
+        );
+    }
+
+    #[test]
+    fn test_parallel_tool_results_do_not_fork() {
+        let result = |id: &str| DisplayEntry::ToolResult {
+            tool_use_id: id.to_string(),
+            content: String::new(),
+            is_error: false,
+            timestamp: None,
+        };
+        let log = tree(&[
+            ("u1", None, prompt("go")),
+            ("a1", Some("u1"), reply("calling")),
+            ("r1", Some("a1"), result("t1")),
+            ("r2", Some("a1"), result("t2")),
+            ("u2", Some("r2"), prompt("next")),
+        ]);
+        assert!(!log.tree.has_forks());
+        assert_eq!(log.branch_entries(None).len(), 5);
+    }
+
+    #[test]
+    fn test_extend_reports_new_fork() {
+        let mut tree = tree(&[
+            ("u1", None, prompt("hi")),
+            ("a1", Some("u1"), reply("hello")),
+        ])
+        .tree;
+        let node = |uuid: &str, parent: &str| EntryNode {
+            uuid: Some(uuid.to_string()),
+            parent_uuid: Some(parent.to_string()),
+            entries: 0..1,
+        };
+        assert!(!tree.extend(&[prompt("more")], &[node("u2", "a1")]));
+        assert!(tree.extend(&[prompt("instead")], &[node("u3", "a1")]));
+    }
+
+    #[test]
+    fn test_branch_entries_leave_out_lines_past_the_tree() {
+        let mut log = tree(&[
+            ("u1", None, prompt("hi")),
+            ("a1", Some("u1"), reply("hello")),
+            ("u2", Some("a1"), prompt("bye")),
+        ]);
+        log.tree = tree(&[
+            ("u1", None, prompt("hi")),
+            ("a1", Some("u1"), reply("hello")),
+        ])
+        .tree;
+        assert_eq!(texts(&log.branch_entries(None)), ["hi", "hello"]);
+    }
+}
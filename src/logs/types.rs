@@ -11,6 +11,16 @@ pub enum LogEntry {
         timestamp: Option<DateTime<Utc>>,
         #[serde(default)]
         session_id: Option<String>,
+        #[serde(default)]
+        uuid: Option<String>,
+        #[serde(default, rename = "parentUuid")]
+        parent_uuid: Option<String>,
+        #[serde(default)]
+        cwd: Option<String>,
+        #[serde(default, rename = "gitBranch")]
+        git_branch: Option<String>,
+        #[serde(default)]
+        version: Option<String>,
     },
     #[serde(rename = "assistant")]
     Assistant {
@@ -19,6 +29,16 @@ pub enum LogEntry {
         timestamp: Option<DateTime<Utc>>,
         #[serde(default)]
         session_id: Option<String>,
+        #[serde(default)]
+        uuid: Option<String>,
+        #[serde(default, rename = "parentUuid")]
+        parent_uuid: Option<String>,
+        #[serde(default)]
+        cwd: Option<String>,
+        #[serde(default, rename = "gitBranch")]
+        git_branch: Option<String>,
+        #[serde(default)]
+        version: Option<String>,
     },
     #[serde(rename = "progress")]
     Progress {
@@ -27,6 +47,54 @@ pub enum LogEntry {
         timestamp: Option<DateTime<Utc>>,
         #[serde(default)]
         session_id: Option<String>,
+        #[serde(default)]
+        uuid: Option<String>,
+        #[serde(default, rename = "parentUuid")]
+        parent_uuid: Option<String>,
+        #[serde(default)]
+        cwd: Option<String>,
+        #[serde(default, rename = "gitBranch")]
+        git_branch: Option<String>,
+        #[serde(default)]
+        version: Option<String>,
+    },
+    /// Notices written by the CLI itself: compact boundaries, API errors and retries,
+    /// local slash commands and their output
+    #[serde(rename = "system")]
+    System {
+        #[serde(default)]
+        subtype: Option<String>,
+        #[serde(default)]
+        content: Option<serde_json::Value>,
+        #[serde(default)]
+        level: Option<String>,
+        #[serde(default, rename = "compactMetadata")]
+        compact_metadata: Option<serde_json::Value>,
+        #[serde(default)]
+        error: Option<serde_json::Value>,
+        #[serde(default, rename = "retryAttempt")]
+        retry_attempt: Option<u32>,
+        #[serde(default, rename = "maxRetries")]
+        max_retries: Option<u32>,
+        #[serde(default, rename = "retryInMs")]
+        retry_in_ms: Option<f64>,
+        #[serde(default)]
+        timestamp: Option<DateTime<Utc>>,
+        #[serde(default)]
+        session_id: Option<String>,
+        #[serde(default)]
+        uuid: Option<String>,
+        #[serde(default, rename = "parentUuid")]
+        parent_uuid: Option<String>,
+        #[serde(default)]
+        cwd: Option<String>,
+        #[serde(default, rename = "gitBranch")]
+        git_branch: Option<String>,
+        #[serde(default)]
+        version: Option<String>,
+        /// Compact boundaries start a new chain; this links them to the message before
+        #[serde(default, rename = "logicalParentUuid")]
+        logical_parent_uuid: Option<String>,
     },
     #[serde(other)]
     Unknown,
@@ -40,6 +108,75 @@ pub struct MessageContent {
     pub content: Option<ContentValue>,
     #[serde(default)]
     pub model: Option<String>,
+    /// API message ID; streamed responses repeat it on every content-block line
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+}
+
+/// Token counts reported by the API for a single assistant message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Total tokens across all four buckets
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+impl std::ops::Add for TokenUsage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            input_tokens: self.input_tokens + other.input_tokens,
+            output_tokens: self.output_tokens + other.output_tokens,
+            cache_creation_input_tokens: self.cache_creation_input_tokens
+                + other.cache_creation_input_tokens,
+            cache_read_input_tokens: self.cache_read_input_tokens + other.cache_read_input_tokens,
+        }
+    }
+}
+
+impl std::ops::AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::Sub for TokenUsage {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            input_tokens: self.input_tokens.saturating_sub(other.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(other.output_tokens),
+            cache_creation_input_tokens: self
+                .cache_creation_input_tokens
+                .saturating_sub(other.cache_creation_input_tokens),
+            cache_read_input_tokens: self
+                .cache_read_input_tokens
+                .saturating_sub(other.cache_read_input_tokens),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -93,11 +230,47 @@ pub struct ToolResultBlock {
     pub text: Option<String>,
 }
 
+/// Where and with what a log line was written, from its `cwd`, `gitBranch` and `version`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionMetadata {
+    pub cwd: Option<String>,
+    pub git_branch: Option<String>,
+    /// Claude Code version
+    pub version: Option<String>,
+}
+
+impl SessionMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.cwd.is_none() && self.git_branch.is_none() && self.version.is_none()
+    }
+}
+
+impl std::fmt::Display for SessionMetadata {
+    /// `cwd: /src/app · branch: main · Claude Code 1.0.80`, skipping unknown fields
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            self.cwd.as_ref().map(|cwd| format!("cwd: {}", cwd)),
+            self.git_branch
+                .as_ref()
+                .map(|branch| format!("branch: {}", branch)),
+            self.version
+                .as_ref()
+                .map(|version| format!("Claude Code {}", version)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        write!(f, "{}", parts.join(" · "))
+    }
+}
+
 /// Embedded result for a tool call (merged from a subsequent ToolResult entry)
 #[derive(Debug, Clone)]
 pub struct ToolCallResult {
     pub content: String,
     pub is_error: bool,
+    /// When the result was logged, for timing the call
+    pub timestamp: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone)]
@@ -126,7 +299,6 @@ pub enum DisplayEntry {
     },
     Thinking {
         text: String,
-        collapsed: bool,
         timestamp: Option<DateTime<Utc>>,
     },
     HookEvent {
@@ -140,6 +312,55 @@ pub enum DisplayEntry {
         description: String,
         timestamp: Option<DateTime<Utc>>,
     },
+    /// Token usage for an assistant message. Emitted ahead of the message's content
+    /// blocks; streamed lines sharing a `message_id` are collapsed into one entry.
+    Usage {
+        message_id: Option<String>,
+        model: Option<String>,
+        usage: TokenUsage,
+        timestamp: Option<DateTime<Utc>>,
+    },
+    /// Marks where the displayed branch leaves a fork in the conversation tree:
+    /// this is branch `branch` (1-based) of `branches` prompts that follow the same turn
+    BranchPoint {
+        branch: usize,
+        branches: usize,
+        timestamp: Option<DateTime<Utc>>,
+    },
+    /// The context was compacted here: everything above was replaced by a summary
+    CompactBoundary {
+        /// `auto` or `manual`
+        trigger: Option<String>,
+        /// Context size just before compacting
+        pre_tokens: Option<u64>,
+        timestamp: Option<DateTime<Utc>>,
+    },
+    /// A failed API request, usually followed by a retry
+    ApiError {
+        message: String,
+        retry_attempt: Option<u32>,
+        max_retries: Option<u32>,
+        retry_in_ms: Option<u64>,
+        timestamp: Option<DateTime<Utc>>,
+    },
+    /// A local slash command such as `/model opus`, or the output it printed
+    LocalCommand {
+        text: String,
+        timestamp: Option<DateTime<Utc>>,
+    },
+    /// Working directory, git branch and CLI version, emitted when any of them changes
+    Metadata {
+        metadata: SessionMetadata,
+        timestamp: Option<DateTime<Utc>>,
+    },
+    /// Any other system notice that carries text
+    SystemNotice {
+        subtype: Option<String>,
+        /// `info`, `warning` or `error`
+        level: Option<String>,
+        text: String,
+        timestamp: Option<DateTime<Utc>>,
+    },
 }
 
 impl DisplayEntry {
@@ -152,7 +373,112 @@ impl DisplayEntry {
             DisplayEntry::Thinking { timestamp, .. } => *timestamp,
             DisplayEntry::HookEvent { timestamp, .. } => *timestamp,
             DisplayEntry::AgentSpawn { timestamp, .. } => *timestamp,
+            DisplayEntry::Usage { timestamp, .. } => *timestamp,
+            DisplayEntry::BranchPoint { timestamp, .. } => *timestamp,
+            DisplayEntry::CompactBoundary { timestamp, .. } => *timestamp,
+            DisplayEntry::ApiError { timestamp, .. } => *timestamp,
+            DisplayEntry::LocalCommand { timestamp, .. } => *timestamp,
+            DisplayEntry::Metadata { timestamp, .. } => *timestamp,
+            DisplayEntry::SystemNotice { timestamp, .. } => *timestamp,
+        }
+    }
+
+    /// Text that conversation search looks through: message text, tool names,
+    /// inputs and results, thinking, hook and agent details
+    pub fn search_fragments(&self) -> Vec<&str> {
+        match self {
+            DisplayEntry::UserMessage { text, .. }
+            | DisplayEntry::AssistantText { text, .. }
+            | DisplayEntry::Thinking { text, .. } => vec![text],
+            DisplayEntry::ToolCall {
+                name,
+                input,
+                result,
+                ..
+            } => {
+                let mut fragments = vec![name.as_str(), input.as_str()];
+                if let Some(result) = result {
+                    fragments.push(&result.content);
+                }
+                fragments
+            }
+            DisplayEntry::ToolResult { content, .. } => vec![content],
+            DisplayEntry::HookEvent {
+                event,
+                hook_name,
+                command,
+                ..
+            } => {
+                let mut fragments = vec![event.as_str()];
+                fragments.extend(hook_name.as_deref());
+                fragments.extend(command.as_deref());
+                fragments
+            }
+            DisplayEntry::AgentSpawn {
+                agent_type,
+                description,
+                ..
+            } => vec![agent_type, description],
+            DisplayEntry::ApiError { message: text, .. }
+            | DisplayEntry::LocalCommand { text, .. }
+            | DisplayEntry::SystemNotice { text, .. } => vec![text],
+            DisplayEntry::Metadata { metadata, .. } => {
+                let mut fragments = Vec::new();
+                fragments.extend(metadata.cwd.as_deref());
+                fragments.extend(metadata.git_branch.as_deref());
+                fragments
+            }
+            DisplayEntry::Usage { .. }
+            | DisplayEntry::BranchPoint { .. }
+            | DisplayEntry::CompactBoundary { .. } => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl DisplayEntry {
+    /// A tool call without a result or timestamp
+    pub fn tool_call(name: &str, input: serde_json::Value) -> Self {
+        DisplayEntry::ToolCall {
+            name: name.to_string(),
+            input: input.to_string(),
+            id: "toolu_1".to_string(),
+            timestamp: None,
+            result: None,
+        }
+    }
+
+    /// The tool call with another ID, for sessions with more than one call
+    pub fn with_id(mut self, tool_use_id: &str) -> Self {
+        if let DisplayEntry::ToolCall { id, .. } = &mut self {
+            *id = tool_use_id.to_string();
+        }
+        self
+    }
+
+    /// The tool call with a result logged at `timestamp`
+    pub fn with_result(
+        mut self,
+        content: &str,
+        is_error: bool,
+        timestamp: Option<DateTime<Utc>>,
+    ) -> Self {
+        if let DisplayEntry::ToolCall { result, .. } = &mut self {
+            *result = Some(ToolCallResult {
+                content: content.to_string(),
+                is_error,
+                timestamp,
+            });
+        }
+        self
+    }
+
+    /// The tool call, logged at `timestamp`
+    pub fn at(mut self, at: Option<DateTime<Utc>>) -> Self {
+        if let DisplayEntry::ToolCall { timestamp, .. } = &mut self {
+            *timestamp = at;
         }
+        self
     }
 }
 
@@ -209,6 +535,7 @@ mod tests {
                 message,
                 timestamp,
                 session_id,
+                ..
             } => {
                 assert_eq!(message.role.as_deref(), Some("user"));
                 assert!(timestamp.is_some());
@@ -227,6 +554,7 @@ mod tests {
                 message,
                 timestamp,
                 session_id,
+                ..
             } => {
                 assert!(message.role.is_none());
                 assert!(message.content.is_none());
@@ -246,6 +574,7 @@ mod tests {
                 message,
                 timestamp,
                 session_id,
+                ..
             } => {
                 assert!(message.role.is_none());
                 assert!(message.content.is_none());
@@ -272,6 +601,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_system_variant() {
+        let value = json!({
+            "type": "system",
+            "subtype": "compact_boundary",
+            "content": "Conversation compacted",
+            "compactMetadata": {"trigger": "auto", "preTokens": 155000},
+            "parentUuid": null,
+            "logicalParentUuid": "a1"
+        });
+        let entry: LogEntry = serde_json::from_value(value).unwrap();
+        match entry {
+            LogEntry::System {
+                subtype,
+                compact_metadata,
+                parent_uuid,
+                logical_parent_uuid,
+                ..
+            } => {
+                assert_eq!(subtype.as_deref(), Some("compact_boundary"));
+                assert_eq!(compact_metadata.unwrap()["preTokens"], 155000);
+                assert!(parent_uuid.is_none());
+                assert_eq!(logical_parent_uuid.as_deref(), Some("a1"));
+            }
+            _ => panic!("expected System variant"),
+        }
+    }
+
     #[test]
     fn test_unknown_variant() {
         let value = json!({"type": "completely_unknown", "foo": "bar"});
@@ -470,6 +827,44 @@ mod tests {
         assert_eq!(block, ContentBlock::Unknown);
     }
 
+    #[test]
+    fn test_message_usage() {
+        let value = json!({
+            "type": "assistant",
+            "message": {
+                "id": "msg_01",
+                "usage": {
+                    "input_tokens": 4,
+                    "output_tokens": 120,
+                    "cache_creation_input_tokens": 2000,
+                    "cache_read_input_tokens": 15000,
+                    "service_tier": "standard"
+                }
+            }
+        });
+        let entry: LogEntry = serde_json::from_value(value).unwrap();
+        match entry {
+            LogEntry::Assistant { message, .. } => {
+                assert_eq!(message.id.as_deref(), Some("msg_01"));
+                let usage = message.usage.expect("usage should be Some");
+                assert_eq!(usage.input_tokens, 4);
+                assert_eq!(usage.output_tokens, 120);
+                assert_eq!(usage.cache_creation_input_tokens, 2000);
+                assert_eq!(usage.cache_read_input_tokens, 15000);
+                assert_eq!(usage.total(), 17124);
+            }
+            _ => panic!("expected Assistant"),
+        }
+    }
+
+    #[test]
+    fn test_message_usage_partial() {
+        let value = json!({"input_tokens": 7});
+        let usage: TokenUsage = serde_json::from_value(value).unwrap();
+        assert_eq!(usage.input_tokens, 7);
+        assert_eq!(usage.output_tokens, 0);
+    }
+
     // ── Category 3: Optional field handling ──────────────────────────────────
 
     #[test]
@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use super::types::{DisplayEntry, TokenUsage};
+
+/// Model name used when a message does not report one
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// Running token totals for a conversation, broken down by model.
+///
+/// Claude Code writes one JSONL line per content block of a streamed response, and each
+/// line repeats the message's `usage` block. Recording a message ID that matches the
+/// previous one replaces that message's usage instead of adding to it, so the same
+/// response is never counted twice.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageTotals {
+    by_model: BTreeMap<String, TokenUsage>,
+    /// Last recorded message: (message_id, model, usage)
+    last: Option<(String, String, TokenUsage)>,
+    messages: usize,
+}
+
+impl UsageTotals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build totals from the `Usage` entries of a conversation
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a DisplayEntry>) -> Self {
+        let mut totals = Self::new();
+        totals.record_entries(entries);
+        totals
+    }
+
+    /// Record every `Usage` entry in `entries`, ignoring all other entry kinds
+    pub fn record_entries<'a>(&mut self, entries: impl IntoIterator<Item = &'a DisplayEntry>) {
+        for entry in entries {
+            if let DisplayEntry::Usage {
+                message_id,
+                model,
+                usage,
+                ..
+            } = entry
+            {
+                self.record(message_id.as_deref(), model.as_deref(), *usage);
+            }
+        }
+    }
+
+    /// Record usage for one assistant message
+    pub fn record(&mut self, message_id: Option<&str>, model: Option<&str>, usage: TokenUsage) {
+        let model = model.unwrap_or(UNKNOWN_MODEL);
+
+        // A repeated message ID is a later line of the same streamed response:
+        // back out the previously recorded usage before adding the new one.
+        if let Some(id) = message_id
+            && let Some((last_id, last_model, last_usage)) = &self.last
+            && last_id == id
+        {
+            if let Some(existing) = self.by_model.get_mut(last_model) {
+                *existing = *existing - *last_usage;
+            }
+        } else {
+            self.messages += 1;
+        }
+
+        *self.by_model.entry(model.to_string()).or_default() += usage;
+        self.last = message_id.map(|id| (id.to_string(), model.to_string(), usage));
+    }
+
+    /// Add another set of totals (e.g. a sub-agent) into this one
+    pub fn merge(&mut self, other: &UsageTotals) {
+        for (model, usage) in &other.by_model {
+            *self.by_model.entry(model.clone()).or_default() += *usage;
+        }
+        self.messages += other.messages;
+    }
+
+    /// Combined usage across all models
+    pub fn total(&self) -> TokenUsage {
+        self.by_model
+            .values()
+            .fold(TokenUsage::default(), |acc, usage| acc + *usage)
+    }
+
+    /// Usage per model name, sorted by model
+    pub fn by_model(&self) -> impl Iterator<Item = (&str, &TokenUsage)> {
+        self.by_model
+            .iter()
+            .map(|(model, usage)| (model.as_str(), usage))
+    }
+
+    /// Number of distinct assistant messages recorded
+    pub fn messages(&self) -> usize {
+        self.messages
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages == 0
+    }
+}
+
+/// Sums token usage from a JSONL log without building display entries.
+///
+/// Only lines mentioning `"usage"` are deserialized, and only into the handful of
+/// fields needed, so this is cheap enough to run over every session in a project.
+pub fn scan_usage(path: &Path) -> Result<UsageTotals> {
+    #[derive(Deserialize)]
+    struct UsageLine {
+        #[serde(rename = "type")]
+        kind: Option<String>,
+        message: Option<UsageMessage>,
+    }
+
+    #[derive(Deserialize)]
+    struct UsageMessage {
+        id: Option<String>,
+        model: Option<String>,
+        usage: Option<TokenUsage>,
+    }
+
+    let file = std::fs::File::open(path)?;
+    let reader = BufReader::new(file);
+    let mut totals = UsageTotals::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if !line.contains("\"usage\"") {
+            continue;
+        }
+        let Ok(parsed) = serde_json::from_str::<UsageLine>(&line) else {
+            continue;
+        };
+        if parsed.kind.as_deref() != Some("assistant") {
+            continue;
+        }
+        if let Some(message) = parsed.message
+            && let Some(usage) = message.usage
+            && !usage.is_empty()
+        {
+            totals.record(message.id.as_deref(), message.model.as_deref(), usage);
+        }
+    }
+
+    Ok(totals)
+}
+
+/// Formats a token count compactly: 950, 12.3k, 4.1M
+pub fn format_token_count(count: u64) -> String {
+    if count >= 1_000_000 {
+        format!("{:.1}M", count as f64 / 1_000_000.0)
+    } else if count >= 1_000 {
+        format!("{:.1}k", count as f64 / 1_000.0)
+    } else {
+        count.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn usage(input: u64, output: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            output_tokens: output,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_distinct_messages_accumulate() {
+        let mut totals = UsageTotals::new();
+        totals.record(Some("msg-1"), Some("opus"), usage(10, 5));
+        totals.record(Some("msg-2"), Some("opus"), usage(20, 7));
+        assert_eq!(totals.total(), usage(30, 12));
+        assert_eq!(totals.messages(), 2);
+    }
+
+    #[test]
+    fn test_record_repeated_message_replaces() {
+        // Streamed content-block lines repeat the message usage with growing output
+        let mut totals = UsageTotals::new();
+        totals.record(Some("msg-1"), Some("opus"), usage(10, 1));
+        totals.record(Some("msg-1"), Some("opus"), usage(10, 40));
+        assert_eq!(totals.total(), usage(10, 40));
+        assert_eq!(totals.messages(), 1);
+    }
+
+    #[test]
+    fn test_record_without_id_always_adds() {
+        let mut totals = UsageTotals::new();
+        totals.record(None, None, usage(1, 1));
+        totals.record(None, None, usage(1, 1));
+        assert_eq!(totals.total(), usage(2, 2));
+        assert_eq!(
+            totals.by_model().next().map(|(m, _)| m),
+            Some(UNKNOWN_MODEL)
+        );
+    }
+
+    #[test]
+    fn test_merge_combines_models() {
+        let mut main = UsageTotals::new();
+        main.record(Some("a"), Some("opus"), usage(10, 10));
+        let mut sub = UsageTotals::new();
+        sub.record(Some("b"), Some("haiku"), usage(5, 5));
+        main.merge(&sub);
+        assert_eq!(main.total(), usage(15, 15));
+        assert_eq!(main.by_model().count(), 2);
+        assert_eq!(main.messages(), 2);
+    }
+
+    #[test]
+    fn test_scan_usage_dedupes_streamed_lines() {
+        let mut file = NamedTempFile::new().unwrap();
+        let line = |output: u64| {
+            serde_json::json!({
+                "type": "assistant",
+                "message": {
+                    "id": "msg-1",
+                    "model": "claude-sonnet-4-5",
+                    "content": [],
+                    "usage": {"input_tokens": 3, "output_tokens": output, "cache_read_input_tokens": 100}
+                }
+            })
+            .to_string()
+        };
+        writeln!(file, "{}", line(1)).unwrap();
+        writeln!(file, "{}", line(50)).unwrap();
+        writeln!(
+            file,
+            "{}",
+            serde_json::json!({"type": "user", "message": {"content": "mentions \"usage\""}})
+        )
+        .unwrap();
+        file.flush().unwrap();
+
+        let totals = scan_usage(file.path()).unwrap();
+        assert_eq!(totals.messages(), 1);
+        assert_eq!(totals.total().output_tokens, 50);
+        assert_eq!(totals.total().cache_read_input_tokens, 100);
+    }
+
+    #[test]
+    fn test_format_token_count() {
+        assert_eq!(format_token_count(950), "950");
+        assert_eq!(format_token_count(12_345), "12.3k");
+        assert_eq!(format_token_count(4_100_000), "4.1M");
+    }
+}
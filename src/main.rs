@@ -1,8 +1,15 @@
 #![allow(dead_code)]
 
 mod app;
+mod clipboard;
+mod config;
+mod editor;
+mod export;
 mod input;
 mod logs;
+mod patch;
+mod pricing;
+mod tail;
 mod themes;
 mod ui;
 
@@ -10,7 +17,7 @@ use std::io;
 use std::time::Duration;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
@@ -19,47 +26,163 @@ use crossterm::{
 use ratatui::{
     Frame, Terminal,
     backend::CrosstermBackend,
-    layout::Rect,
+    layout::{Constraint, Layout, Rect},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph, StatefulWidget},
 };
 
 use app::App;
-use input::{Action, handle_key_event};
+use config::Config;
+use input::{Action, KeyAction, Keymap, Scope, handle_key_event, handle_mouse_event};
+use logs::{UsageTotals, format_token_count};
+use pricing::{PriceTable, format_cost};
 use ui::{
-    AgentList, AppLayout, ConversationView, FocusedPane, LayoutConfig, ProjectList, SessionList,
+    AgentList, AppLayout, BranchPickerView, ConversationView, FileIndexView, FocusedPane,
+    GlobalSearchView, LayoutConfig, ProjectList, SessionList, SlowestToolsView, StatsView,
 };
 
 #[derive(Parser)]
 #[command(name = "claude-tail")]
 #[command(about = "TUI for viewing Claude.ai conversation logs")]
 struct Args {
-    /// Color theme to use
-    #[arg(short, long, default_value = "tokyonight-storm")]
-    theme: String,
+    /// Color theme to use [default: tokyonight-storm]
+    #[arg(short, long, global = true)]
+    theme: Option<String>,
 
     /// List available themes and exit
     #[arg(long)]
     list_themes: bool,
 
+    /// Print the effective settings, from ~/.config/claude-tail/config.toml and these flags,
+    /// and exit
+    #[arg(long)]
+    print_config: bool,
+
     /// Enable automatic switching to project/session/agent with most recent activity
-    #[arg(short = 's', long)]
+    #[arg(short = 's', long, overrides_with = "no_super_follow")]
     super_follow: bool,
 
+    /// Disable super-follow, even if the config file enables it
+    #[arg(long, overrides_with = "super_follow")]
+    no_super_follow: bool,
+
     /// Enable logging to ~/.claude/logs/claude-tail.log (off by default)
     /// Levels: trace, debug, info, warn, error. Can also set via RUST_LOG env var.
     #[arg(long, value_name = "LEVEL")]
     log_level: Option<String>,
+
+    /// Capture the mouse for clicks and scrolling, even if the config file turns it off
+    #[arg(long, overrides_with = "no_mouse")]
+    mouse: bool,
+
+    /// Leave the mouse to the terminal, so its native text selection works
+    #[arg(long, overrides_with = "mouse")]
+    no_mouse: bool,
+
+    /// Show thinking blocks at startup
+    #[arg(long, overrides_with = "no_show_thinking", help_heading = "Display")]
+    show_thinking: bool,
+
+    /// Hide thinking blocks at startup
+    #[arg(long, overrides_with = "show_thinking", help_heading = "Display")]
+    no_show_thinking: bool,
+
+    /// Show tool output at startup
+    #[arg(long, overrides_with = "no_expand_tools", help_heading = "Display")]
+    expand_tools: bool,
+
+    /// Collapse tool output at startup
+    #[arg(long, overrides_with = "expand_tools", help_heading = "Display")]
+    no_expand_tools: bool,
+
+    /// Show Edit diffs in full at startup
+    #[arg(long, overrides_with = "no_full_diffs", help_heading = "Display")]
+    full_diffs: bool,
+
+    /// Show a preview of Edit diffs at startup
+    #[arg(long, overrides_with = "full_diffs", help_heading = "Display")]
+    no_full_diffs: bool,
+
+    /// Show working directory, git branch and version changes at startup
+    #[arg(long, overrides_with = "no_show_metadata", help_heading = "Display")]
+    show_metadata: bool,
+
+    /// Hide working directory, git branch and version changes at startup
+    #[arg(long, overrides_with = "show_metadata", help_heading = "Display")]
+    no_show_metadata: bool,
+
+    /// Command `R` runs to resume the selected session; `{id}` is replaced by its ID
+    /// [default: claude --resume {id}]
+    #[arg(long, value_name = "COMMAND")]
+    resume_command: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+impl Args {
+    /// Override the config file with the flags that were given
+    fn apply_to(&self, config: &mut Config) {
+        if let Some(theme) = &self.theme {
+            config.theme = theme.clone();
+        }
+        if let Some(level) = &self.log_level {
+            config.log_level = Some(level.clone());
+        }
+        if let Some(command) = &self.resume_command {
+            config.resume_command = command.clone();
+        }
+        let switches = [
+            (
+                self.super_follow,
+                self.no_super_follow,
+                &mut config.super_follow,
+            ),
+            (self.mouse, self.no_mouse, &mut config.mouse),
+            (
+                self.show_thinking,
+                self.no_show_thinking,
+                &mut config.display.show_thinking,
+            ),
+            (
+                self.expand_tools,
+                self.no_expand_tools,
+                &mut config.display.expand_tools,
+            ),
+            (
+                self.full_diffs,
+                self.no_full_diffs,
+                &mut config.display.full_diffs,
+            ),
+            (
+                self.show_metadata,
+                self.no_show_metadata,
+                &mut config.display.show_metadata,
+            ),
+        ];
+        // Each pair overrides itself, so at most one of the two is set: the one given last
+        for (on, off, setting) in switches {
+            if on || off {
+                *setting = on;
+            }
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Export a session's conversation to a file or stdout
+    Export(export::ExportArgs),
+    /// Print a conversation as plain text and follow it like `tail -f`
+    Tail(tail::TailArgs),
+    /// Print the changes a session made to files as a unified diff
+    Patch(patch::PatchArgs),
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
-    // Initialize logging to ~/.claude/logs/claude-tail.log
-    // Only enabled if --log-level is specified or RUST_LOG env var is set
-    init_logging(args.log_level.as_deref());
-
     // Handle --list-themes
     if args.list_themes {
         println!("Available themes:");
@@ -75,29 +198,64 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Subcommands only take the theme from the config file, so a mistake elsewhere in it
+    // doesn't stop them
+    if let Some(command) = &args.command {
+        init_logging(args.log_level.as_deref());
+        let theme_name = args.theme.clone().unwrap_or_else(Config::load_theme);
+        let theme = themes::load_theme(&theme_name)?;
+        return match command {
+            Command::Export(export_args) => export::run(export_args, &theme),
+            Command::Tail(tail_args) => tail::run(tail_args, &theme).await,
+            Command::Patch(patch_args) => patch::run(patch_args),
+        };
+    }
+
+    // Settings from ~/.config/claude-tail/config.toml, overridden by flags
+    let mut config = Config::load()?;
+    args.apply_to(&mut config);
+
+    // Initialize logging to ~/.claude/logs/claude-tail.log
+    // Only enabled if a log level is configured or RUST_LOG env var is set
+    init_logging(config.log_level.as_deref());
+
+    if args.print_config {
+        if let Some(path) = config::config_file_path() {
+            let status = if path.exists() { "" } else { " (not found)" };
+            println!("# {}{}", path.display(), status);
+        }
+        print!("{}", config.to_toml()?);
+        return Ok(());
+    }
+
     // Load theme
-    let theme = themes::load_theme(&args.theme)?;
+    let theme = themes::load_theme(&config.theme)?;
+
+    // Load model prices (built-in table plus ~/.config/claude-tail/pricing.yaml)
+    let prices = PriceTable::load()?;
 
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if config.mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
-    let mut app = App::new(theme, args.super_follow)?;
+    let mut app = App::new(theme, prices, &config)?;
 
     // Run main loop
     let result = run_app(&mut terminal, &mut app).await;
 
     // Restore terminal
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    if config.mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
     if let Err(e) = result {
@@ -114,7 +272,7 @@ async fn run_app<B: ratatui::backend::Backend>(
 where
     B::Error: Send + Sync + 'static,
 {
-    let mut list_refresh_interval = tokio::time::interval(Duration::from_secs(5));
+    let mut list_refresh_interval = tokio::time::interval(app.config.list_refresh_interval());
     // Don't let missed ticks accumulate
     list_refresh_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
@@ -133,16 +291,38 @@ where
         // Use biased to prioritize keyboard input over file events
         tokio::select! {
             biased;
-            // Poll for keyboard events
+            // Poll for keyboard and mouse events
             _ = tokio::time::sleep(Duration::from_millis(100)) => {
-                if event::poll(Duration::from_millis(0))?
-                    && let Event::Key(key) = event::read()? {
-                        match handle_key_event(app, key) {
-                            Action::Quit => return Ok(()),
-                            Action::Redraw => continue,
-                            Action::None => {}
+                if event::poll(Duration::from_millis(0))? {
+                    let action = match event::read()? {
+                        Event::Key(key) => handle_key_event(app, key),
+                        Event::Mouse(mouse) => handle_mouse_event(app, mouse),
+                        _ => Action::None,
+                    };
+                    match action {
+                        Action::Quit => return Ok(()),
+                        Action::Redraw => continue,
+                        Action::Suspend(mut command) => {
+                            let program = command.get_program().to_string_lossy().into_owned();
+                            match run_suspended(terminal, app.config.mouse, &mut command) {
+                                Ok(status) if !status.success() => {
+                                    app.error_message =
+                                        Some(format!("{} exited with {}", program, status));
+                                }
+                                Ok(_) => {}
+                                Err(e) => {
+                                    app.error_message =
+                                        Some(format!("Failed to run {}: {}", program, e));
+                                }
+                            }
+                            // A resumed session writes new logs; pick them up
+                            app.refresh_projects();
+                            app.refresh_sessions();
+                            continue;
                         }
+                        Action::None => {}
                     }
+                }
             }
 
             // Watch for file changes
@@ -158,6 +338,9 @@ where
                     app::ParseMessage::Complete { path, result } => {
                         app.handle_parse_complete(path, result);
                     }
+                    app::ParseMessage::Branch { path, result, reset_view } => {
+                        app.handle_branch_parsed(path, result, reset_view);
+                    }
                 }
             }
 
@@ -172,6 +355,15 @@ where
                         app.handle_sessions_discovered(project_path, result);
                         app.auto_switch_to_most_recent();
                     }
+                    app::DiscoveryMessage::UsageScanned(scans) => {
+                        app.handle_usage_scanned(scans);
+                    }
+                    app::DiscoveryMessage::GlobalSearchComplete { query, hits } => {
+                        app.handle_global_search_complete(query, hits);
+                    }
+                    app::DiscoveryMessage::FilesIndexed { session_log_path, index } => {
+                        app.handle_files_indexed(session_log_path, index);
+                    }
                 }
             }
 
@@ -190,6 +382,35 @@ where
     }
 }
 
+/// Hand the terminal to a child process, such as an editor, and take it back once it exits
+fn run_suspended<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    mouse_capture: bool,
+    command: &mut std::process::Command,
+) -> Result<std::process::ExitStatus>
+where
+    B::Error: Send + Sync + 'static,
+{
+    let mut stdout = io::stdout();
+    if mouse_capture {
+        execute!(stdout, DisableMouseCapture)?;
+    }
+    execute!(stdout, LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+    terminal.show_cursor()?;
+
+    let status = command.status();
+
+    enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if mouse_capture {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    // The child drew over the screen; repaint all of it
+    terminal.clear()?;
+    Ok(status?)
+}
+
 fn draw(frame: &mut Frame, app: &mut App) {
     let size = frame.area();
 
@@ -211,12 +432,14 @@ fn draw(frame: &mut Frame, app: &mut App) {
         max_project_width,
         max_session_width,
         max_agent_width,
+        collapsed_width: app.config.layout.collapsed_width,
     };
 
     let layout = AppLayout::new(size, layout_config);
 
     // Update viewport height for scrolling calculations
     app.viewport_height = Some(layout.conversation.height.saturating_sub(2) as usize);
+    app.layout = Some(layout);
 
     // Draw header
     draw_header(frame, layout.header, app);
@@ -240,8 +463,10 @@ fn draw(frame: &mut Frame, app: &mut App) {
     // Draw sessions pane
     let sessions_focused = app.focus == app::FocusPane::Sessions;
     let sessions_collapsed = app.focus != app::FocusPane::Sessions;
+    let session_costs = app.session_costs();
     let session_list = SessionList::new(
         &app.sessions,
+        &session_costs,
         sessions_focused,
         sessions_collapsed,
         &app.theme,
@@ -264,29 +489,85 @@ fn draw(frame: &mut Frame, app: &mut App) {
         &mut app.agent_state.list_state,
     );
 
-    // Draw conversation pane
+    // Draw conversation pane, or its statistics
     let conversation_focused = app.focus == app::FocusPane::Conversation;
-    let conversation_view = ConversationView::new(
-        &app.conversation,
-        conversation_focused,
-        &app.theme,
-        app.show_thinking,
-        app.expand_tools,
-        app.is_parsing,
-    );
-    StatefulWidget::render(
-        conversation_view,
-        layout.conversation,
-        frame.buffer_mut(),
-        &mut app.conversation_state,
-    );
+    if app.show_stats {
+        frame.render_widget(
+            StatsView::new(
+                &app.stats,
+                conversation_focused,
+                &app.theme,
+                app.entries_truncated,
+            ),
+            layout.conversation,
+        );
+    } else {
+        let conversation_view = ConversationView::new(
+            &app.conversation,
+            conversation_focused,
+            &app.theme,
+            app.show_thinking,
+            app.expand_tools,
+            app.full_diffs,
+            app.show_metadata,
+            app.is_parsing,
+            &app.search,
+            &app.config.keys,
+        );
+        StatefulWidget::render(
+            conversation_view,
+            layout.conversation,
+            frame.buffer_mut(),
+            &mut app.conversation_state,
+        );
+    }
 
     // Draw status bar
     draw_status_bar(frame, layout.status_bar, app);
 
+    // Draw cross-project search overlay if open
+    if app.global_search.open {
+        StatefulWidget::render(
+            GlobalSearchView::new(&app.theme),
+            size,
+            frame.buffer_mut(),
+            &mut app.global_search,
+        );
+    }
+
+    // Draw branch picker overlay if open
+    if app.branch_picker.open {
+        StatefulWidget::render(
+            BranchPickerView::new(&app.theme),
+            size,
+            frame.buffer_mut(),
+            &mut app.branch_picker,
+        );
+    }
+
+    // Draw slowest-tools overlay if open
+    if app.slowest_tools.open {
+        StatefulWidget::render(
+            SlowestToolsView::new(&app.theme),
+            size,
+            frame.buffer_mut(),
+            &mut app.slowest_tools,
+        );
+    }
+
+    // Draw files-touched overlay if open
+    if app.file_index.open {
+        StatefulWidget::render(
+            FileIndexView::new(&app.theme),
+            size,
+            frame.buffer_mut(),
+            &mut app.file_index,
+        );
+    }
+
     // Draw help overlay if enabled
     if app.show_help {
-        draw_help_overlay(frame, size);
+        draw_help_overlay(frame, size, &app.config.keys);
     }
 }
 
@@ -315,33 +596,64 @@ fn draw_header(frame: &mut Frame, area: Rect, app: &App) {
         spans.push(Span::styled(agent_name, app.theme.tool_name));
     }
 
+    // Session-wide token totals (main agent plus all sub-agents)
+    if let Some(totals) = app.selected_session_usage()
+        && !totals.is_empty()
+    {
+        spans.push(Span::styled(" │ ", app.theme.border));
+        spans.push(Span::styled(
+            format!("session {}", format_usage_summary(&totals, &app.prices)),
+            app.theme.timestamp,
+        ));
+    }
+
     let header = Paragraph::new(Line::from(spans));
     frame.render_widget(header, area);
 }
 
 fn draw_status_bar(frame: &mut Frame, area: Rect, app: &App) {
-    let follow_indicator = if app.conversation_state.follow_mode {
-        "[F]ollow ON"
-    } else {
-        "[f]ollow off"
-    };
-
-    let thinking_indicator = if app.show_thinking {
-        "[T]hinking ON"
-    } else {
-        "[t]hinking off"
-    };
-
-    let expand_indicator = if app.expand_tools {
-        "[E]xpand ON"
-    } else {
-        "[e]xpand off"
+    let keymap = &app.config.keys;
+    // "[j/k] nav", from the first key of each action; hints for unbound actions are left out
+    let hint = |scope: Scope, actions: &[KeyAction], label: &str| -> Option<String> {
+        let keys: Vec<String> = actions
+            .iter()
+            .filter_map(|&action| keymap.key_for(scope, action))
+            .collect();
+        (!keys.is_empty()).then(|| format!("[{}] {}", keys.join("/"), label))
     };
-
-    let status_text = format!(
-        " [q]uit [Tab] pane [j/k] nav [g/G] top/bottom  {}  {}  {}  [?] help ",
-        follow_indicator, thinking_indicator, expand_indicator
-    );
+    let on_off = |on: bool| if on { "ON" } else { "off" };
+
+    let hints: Vec<String> = [
+        hint(Scope::Global, &[KeyAction::Quit], "quit"),
+        hint(Scope::Global, &[KeyAction::NextPane], "pane"),
+        hint(Scope::Lists, &[KeyAction::Down, KeyAction::Up], "nav"),
+        hint(
+            Scope::Lists,
+            &[KeyAction::Top, KeyAction::Bottom],
+            "top/bottom",
+        ),
+        hint(Scope::Conversation, &[KeyAction::SearchForward], "search"),
+        hint(
+            Scope::Global,
+            &[KeyAction::ToggleFollow],
+            &format!("follow {}", on_off(app.conversation_state.follow_mode)),
+        ),
+        hint(
+            Scope::Global,
+            &[KeyAction::ToggleThinking],
+            &format!("thinking {}", on_off(app.show_thinking)),
+        ),
+        hint(
+            Scope::Global,
+            &[KeyAction::ToggleToolExpansion],
+            &format!("expand {}", on_off(app.expand_tools)),
+        ),
+        hint(Scope::Global, &[KeyAction::ToggleHelp], "help"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let status_text = format!(" {} ", hints.join(" "));
 
     // Build warning indicators
     let mut warnings = Vec::new();
@@ -365,21 +677,131 @@ fn draw_status_bar(frame: &mut Frame, area: Rect, app: &App) {
     };
 
     let mut spans = Vec::new();
+
+    // The search prompt replaces the status bar while typing, as in vim
+    if let Some(prompt) = &app.search.prompt {
+        spans.push(Span::styled(
+            format!("{}{}", app.search.direction.prompt_char(), prompt),
+            app.theme.status_bar,
+        ));
+        if let Some(status) = app.search.status() {
+            spans.push(Span::styled(format!("  [{}]", status), app.theme.timestamp));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+        return;
+    }
+
     if !error_text.is_empty() {
         spans.push(Span::styled(error_text, app.theme.tool_error));
     }
+    if let Some(message) = &app.status_message {
+        spans.push(Span::styled(format!(" {} ", message), app.theme.key_hint));
+    }
     if !warning_text.is_empty() {
         spans.push(Span::styled(warning_text, app.theme.thinking));
     }
+    if let Some(status) = app.search.status() {
+        spans.push(Span::styled(
+            format!(" {} ", status),
+            app.theme.search_current,
+        ));
+    }
     spans.push(Span::styled(status_text, app.theme.status_bar));
 
+    // Running token totals for the loaded agent
+    if !app.agent_usage.is_empty() {
+        spans.push(Span::styled(
+            format!(
+                " agent {} ",
+                format_usage_summary(&app.agent_usage, &app.prices)
+            ),
+            app.theme.status_bar,
+        ));
+    }
+
     let status_bar = Paragraph::new(Line::from(spans));
     frame.render_widget(status_bar, area);
 }
 
-fn draw_help_overlay(frame: &mut Frame, area: Rect) {
-    let help_width = 50;
-    let help_height = 18;
+/// Formats token totals as "1.2M tok (45.1k in / 12.3k out / 1.1M cache) $3.40"
+fn format_usage_summary(totals: &UsageTotals, prices: &PriceTable) -> String {
+    let total = totals.total();
+    let cached = total.cache_read_input_tokens + total.cache_creation_input_tokens;
+    let mut summary = format!(
+        "{} tok ({} in / {} out / {} cache)",
+        format_token_count(total.total()),
+        format_token_count(total.input_tokens),
+        format_token_count(total.output_tokens),
+        format_token_count(cached),
+    );
+    if let Some(cost) = prices.cost(totals) {
+        summary.push(' ');
+        summary.push_str(&format_cost(cost));
+    }
+    summary
+}
+
+/// The bindings of the active keymap, one section per scope, in two columns
+fn draw_help_overlay(frame: &mut Frame, area: Rect, keymap: &Keymap) {
+    let sections = keymap.sections();
+    let key_width = sections
+        .iter()
+        .flat_map(|(_, actions)| actions.iter().map(|(keys, _)| keys.chars().count()))
+        .max()
+        .unwrap_or(0)
+        .clamp(10, 20);
+    let row = |keys: &str, description: &str| {
+        Line::from(format!(
+            "  {:<width$}  {}",
+            keys,
+            description,
+            width = key_width
+        ))
+    };
+    let heading = |title: &str| {
+        vec![
+            Line::from(""),
+            Line::from(format!("  {}", title)),
+            Line::from(format!("  {}", "─".repeat(title.chars().count()))),
+        ]
+    };
+
+    let mut blocks: Vec<Vec<Line>> = sections
+        .iter()
+        .map(|(scope, actions)| {
+            let mut lines = heading(scope.title());
+            lines.extend(
+                actions
+                    .iter()
+                    .map(|(keys, action)| row(keys, action.description())),
+            );
+            lines
+        })
+        .collect();
+    let mut mouse = heading("Mouse");
+    mouse.push(row("click", "Focus, select / toggle entry"));
+    mouse.push(row("wheel", "Scroll the conversation"));
+    blocks.push(mouse);
+
+    // Fill the left column to about half, then the right
+    let total: usize = blocks.iter().map(Vec::len).sum();
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    for block in blocks {
+        if left.len() < total / 2 {
+            left.extend(block);
+        } else {
+            right.extend(block);
+        }
+    }
+
+    let footer = vec![
+        Line::from(""),
+        Line::from("  ctrl-c always quits; rebind keys under [keys] in config.toml"),
+    ];
+    let column_width = 4 + key_width as u16 + 30;
+    let help_width = (column_width * 2 + 2).min(area.width);
+    let help_height =
+        (left.len().max(right.len()) + footer.len() + 2).min(area.height as usize) as u16;
     let x = (area.width.saturating_sub(help_width)) / 2;
     let y = (area.height.saturating_sub(help_height)) / 2;
 
@@ -388,34 +810,21 @@ fn draw_help_overlay(frame: &mut Frame, area: Rect) {
     // Clear the area
     frame.render_widget(Clear, help_area);
 
-    let help_text = vec![
-        Line::from(""),
-        Line::from("  Navigation"),
-        Line::from("  ──────────"),
-        Line::from("  Tab / Shift+Tab   Cycle panes"),
-        Line::from("  j / Down          Move down / scroll"),
-        Line::from("  k / Up            Move up / scroll"),
-        Line::from("  g                 Go to top"),
-        Line::from("  G                 Go to bottom"),
-        Line::from("  Enter             Select / enter pane"),
-        Line::from(""),
-        Line::from("  Display"),
-        Line::from("  ───────"),
-        Line::from("  t                 Toggle thinking blocks"),
-        Line::from("  e                 Toggle tool expansion"),
-        Line::from("  f                 Toggle follow mode"),
-        Line::from(""),
-        Line::from("  q / Ctrl+C        Quit"),
-    ];
-
-    let help = Paragraph::new(help_text).block(
-        Block::default()
-            .title(" Help ")
-            .borders(Borders::ALL)
-            .border_style(ratatui::style::Style::default().fg(ratatui::style::Color::Cyan)),
-    );
-
-    frame.render_widget(help, help_area);
+    let block = Block::default()
+        .title(" Help ")
+        .borders(Borders::ALL)
+        .border_style(ratatui::style::Style::default().fg(ratatui::style::Color::Cyan));
+    let inner = block.inner(help_area);
+    frame.render_widget(block, help_area);
+
+    let [body, footer_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(footer.len() as u16)])
+            .areas(inner);
+    let [left_area, right_area] =
+        Layout::horizontal([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)]).areas(body);
+    frame.render_widget(Paragraph::new(left), left_area);
+    frame.render_widget(Paragraph::new(right), right_area);
+    frame.render_widget(Paragraph::new(footer), footer_area);
 }
 
 fn init_logging(log_level: Option<&str>) {
@@ -462,3 +871,30 @@ fn init_logging(log_level: Option<&str>) {
         .with(EnvFilter::new("off"))
         .init();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_override_config_both_ways() {
+        let mut config = Config {
+            super_follow: true,
+            mouse: false,
+            ..Config::default()
+        };
+        let args =
+            Args::try_parse_from(["claude-tail", "--no-super-follow", "--mouse", "--no-mouse"])
+                .unwrap();
+        args.apply_to(&mut config);
+        assert!(!config.super_follow);
+        assert!(!config.mouse);
+        assert!(config.display.expand_tools);
+
+        let args = Args::try_parse_from(["claude-tail", "--no-expand-tools", "--mouse"]).unwrap();
+        args.apply_to(&mut config);
+        assert!(!config.display.expand_tools);
+        assert!(config.mouse);
+        assert!(!config.super_follow);
+    }
+}
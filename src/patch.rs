@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use similar::TextDiff;
+
+use crate::export::load_entries;
+use crate::logs::diff::{FileEdit, tool_edits};
+use crate::logs::{DisplayEntry, discover_agents, find_session, latest_session};
+
+/// Lines of unchanged context around each change
+const CONTEXT_LINES: usize = 3;
+
+/// Result text of a `Write` that created its file, rather than overwriting one
+const CREATED_PREFIX: &str = "File created successfully";
+
+/// Lines a `Read` without a `limit` returns; a result this long may have stopped early
+const READ_LINE_LIMIT: usize = 2000;
+
+/// Characters a `Read` keeps of each line; a line this long may have been cut short
+const READ_LINE_LENGTH_LIMIT: usize = 2000;
+
+/// Arguments for `claude-tail patch`
+#[derive(Debug, clap::Args)]
+pub struct PatchArgs {
+    /// Session ID (or unique prefix), or path to a session .jsonl file
+    #[arg(required_unless_present = "latest")]
+    session: Option<String>,
+
+    /// Use the session with the most recent activity
+    #[arg(long, conflicts_with = "session")]
+    latest: bool,
+
+    /// Apply the edits to the files as they are on disk now, instead of to their content
+    /// before the session
+    #[arg(long)]
+    against_disk: bool,
+
+    /// File to write; prints to stdout if omitted
+    #[arg(short, long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+/// Runs `claude-tail patch`: rebuilds the net change a session made to files from its
+/// `Write`, `Edit` and `MultiEdit` calls and prints it as a unified diff
+pub fn run(args: &PatchArgs) -> Result<()> {
+    let session = match &args.session {
+        Some(query) if !args.latest => find_session(query)?,
+        _ => latest_session()?,
+    };
+
+    // Sub-agents edit files too; replay every agent's changes in the order they happened
+    let mut operations = Vec::new();
+    let mut root = None;
+    for agent in discover_agents(&session)? {
+        let entries = load_entries(&agent.log_path)?;
+        if agent.is_main {
+            root = session_cwd(&entries);
+        }
+        operations.extend(file_operations(&entries));
+    }
+    operations.sort_by_key(|op| op.timestamp);
+
+    let base = if args.against_disk {
+        Base::Disk
+    } else {
+        Base::Inferred
+    };
+    let mut patch = String::new();
+    for (path, ops) in group_by_file(operations) {
+        let disk = std::fs::read_to_string(&path).ok();
+        match file_patch(&ops, disk.as_deref(), base) {
+            Ok((before, after)) => {
+                patch.push_str(&file_diff(
+                    &display_path(&path, root.as_deref()),
+                    before.as_deref(),
+                    &after,
+                ));
+            }
+            Err(reason) => eprintln!("Skipping {}: {}", path, reason),
+        }
+    }
+
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, patch)?;
+            eprintln!("Wrote patch to {}", path.display());
+        }
+        None => print!("{}", patch),
+    }
+    Ok(())
+}
+
+/// What a successful tool call did to a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Change {
+    /// Replaced the whole file; `created` when the file did not exist before
+    Write {
+        content: String,
+        created: bool,
+    },
+    Edit(Vec<FileEdit>),
+    /// Read the whole file, which tells us its content at that point
+    Read(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileOp {
+    path: String,
+    change: Change,
+    timestamp: Option<DateTime<Utc>>,
+}
+
+/// Where the replay starts from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Base {
+    /// The file's content before the session, worked out from the log and the disk
+    Inferred,
+    /// The file as it is on disk now
+    Disk,
+}
+
+/// Working directory the session started in, used to make paths in the patch relative
+fn session_cwd(entries: &[DisplayEntry]) -> Option<String> {
+    entries.iter().find_map(|entry| match entry {
+        DisplayEntry::Metadata { metadata, .. } => metadata.cwd.clone(),
+        _ => None,
+    })
+}
+
+/// File reads and changes that succeeded, in log order. Calls that failed or never got a
+/// result are left out, since they didn't change the file.
+fn file_operations(entries: &[DisplayEntry]) -> Vec<FileOp> {
+    // Results of parallel calls are logged apart from their calls
+    let results: HashMap<&str, (&str, bool)> = entries
+        .iter()
+        .filter_map(|entry| match entry {
+            DisplayEntry::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+                ..
+            } => Some((tool_use_id.as_str(), (content.as_str(), *is_error))),
+            _ => None,
+        })
+        .collect();
+
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let DisplayEntry::ToolCall {
+                name,
+                input,
+                id,
+                timestamp,
+                result,
+            } = entry
+            else {
+                return None;
+            };
+            let (content, is_error) = result
+                .as_ref()
+                .map(|r| (r.content.as_str(), r.is_error))
+                .or_else(|| results.get(id.as_str()).copied())?;
+            if is_error {
+                return None;
+            }
+            let input: serde_json::Value = serde_json::from_str(input).ok()?;
+            let path = input.get("file_path")?.as_str()?.to_string();
+            let change = match name.as_str() {
+                "Write" => Change::Write {
+                    content: input.get("content")?.as_str()?.to_string(),
+                    created: content.starts_with(CREATED_PREFIX),
+                },
+                "Edit" | "MultiEdit" => Change::Edit(tool_edits(name, &input)?.1),
+                // Partial reads don't show the whole file
+                "Read" if input.get("offset").is_none() && input.get("limit").is_none() => {
+                    Change::Read(read_content(content)?)
+                }
+                _ => return None,
+            };
+            Some(FileOp {
+                path,
+                change,
+                timestamp: *timestamp,
+            })
+        })
+        .collect()
+}
+
+/// Operations per file, files in the order they were first touched
+fn group_by_file(operations: Vec<FileOp>) -> Vec<(String, Vec<FileOp>)> {
+    let mut files: Vec<(String, Vec<FileOp>)> = Vec::new();
+    for op in operations {
+        match files.iter_mut().find(|(path, _)| *path == op.path) {
+            Some((_, ops)) => ops.push(op),
+            None => files.push((op.path.clone(), vec![op])),
+        }
+    }
+    // Files that were only read have nothing to show
+    files.retain(|(_, ops)| ops.iter().any(|op| !matches!(op.change, Change::Read(_))));
+    files
+}
+
+/// The file's content before and after the session. `before` is None for a created file.
+fn file_patch(
+    ops: &[FileOp],
+    disk: Option<&str>,
+    base: Base,
+) -> Result<(Option<String>, String), String> {
+    let before = match base {
+        Base::Disk => disk.map(str::to_string),
+        Base::Inferred => infer_before(ops, disk).ok_or_else(|| {
+            "its content before the session can't be worked out from the log or the disk"
+                .to_string()
+        })?,
+    };
+    let after = replay(before.as_deref().unwrap_or(""), ops).map_err(|old| {
+        format!(
+            "an edit no longer applies (can't find {:?})",
+            first_line(&old)
+        )
+    })?;
+    if before.as_deref() == Some(after.as_str()) {
+        return Err("the session left it unchanged".to_string());
+    }
+    Ok((before, after))
+}
+
+/// Content of the file before the first change, or None inside the Option for a file the
+/// session created
+fn infer_before(ops: &[FileOp], disk: Option<&str>) -> Option<Option<String>> {
+    let first_change = ops
+        .iter()
+        .position(|op| !matches!(op.change, Change::Read(_)))?;
+
+    // Created by the session
+    if let Change::Write { created: true, .. } = ops[first_change].change {
+        return Some(None);
+    }
+
+    // Undo the session's edits on the current file, and check that replaying them gets back
+    if let Some(disk) = disk
+        && let Some(before) = undo(disk, &ops[first_change..])
+        && replay(&before, ops).as_deref() == Ok(disk)
+    {
+        return Some(Some(before));
+    }
+
+    // A full read before the first change shows the original content
+    ops[..first_change]
+        .iter()
+        .rev()
+        .find_map(|op| match &op.change {
+            Change::Read(content) => Some(Some(content.clone())),
+            _ => None,
+        })
+}
+
+/// Applies the changes in order. Returns the `old_string` that could not be found if an
+/// edit doesn't apply.
+fn replay(before: &str, ops: &[FileOp]) -> Result<String, String> {
+    let mut content = before.to_string();
+    for op in ops {
+        match &op.change {
+            Change::Write { content: new, .. } => content = new.clone(),
+            Change::Edit(edits) => {
+                for edit in edits {
+                    if !content.contains(&edit.old) {
+                        return Err(edit.old.clone());
+                    }
+                    content = if edit.replace_all {
+                        content.replace(&edit.old, &edit.new)
+                    } else {
+                        content.replacen(&edit.old, &edit.new, 1)
+                    };
+                }
+            }
+            Change::Read(_) => {}
+        }
+    }
+    Ok(content)
+}
+
+/// Reverts the changes from the last to the first. Fails when a `Write` replaced content the
+/// log doesn't show, or an edit's replacement is missing or ambiguous.
+fn undo(after: &str, ops: &[FileOp]) -> Option<String> {
+    let mut content = after.to_string();
+    for op in ops.iter().rev() {
+        match &op.change {
+            Change::Write { .. } => return None,
+            Change::Edit(edits) => {
+                for edit in edits.iter().rev() {
+                    if edit.new.is_empty() || content.matches(&edit.new).count() != 1 {
+                        return None;
+                    }
+                    content = content.replacen(&edit.new, &edit.old, 1);
+                }
+            }
+            Change::Read(_) => {}
+        }
+    }
+    Some(content)
+}
+
+/// File content from a `Read` result, which numbers each line as `     1→text`. Returns None
+/// when a line isn't numbered, e.g. an error message, or the result may not be the whole file.
+/// The result doesn't show whether the file ends with a newline, so the content doesn't.
+fn read_content(result: &str) -> Option<String> {
+    let mut lines = Vec::new();
+    for line in result.lines() {
+        // Every line of the file is numbered, even blank ones; an unnumbered blank line ends
+        // the content, and Claude Code may append reminders after it
+        if line.is_empty() || line.starts_with("<system-reminder>") {
+            break;
+        }
+        let line = line.trim_start();
+        let (number, text) = line.split_once('→').or_else(|| line.split_once('\t'))?;
+        if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        if text.chars().count() >= READ_LINE_LENGTH_LIMIT {
+            return None;
+        }
+        lines.push(text);
+    }
+    if lines.is_empty() || lines.len() >= READ_LINE_LIMIT {
+        return None;
+    }
+    Some(lines.join("\n"))
+}
+
+/// Path as shown in the patch: relative to the session's directory when it is inside it
+fn display_path(path: &str, root: Option<&str>) -> String {
+    root.and_then(|root| Path::new(path).strip_prefix(root).ok())
+        .map(|relative| relative.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.trim_start_matches('/').to_string())
+}
+
+/// Unified diff with git-style headers; a missing `before` is a new file
+fn file_diff(path: &str, before: Option<&str>, after: &str) -> String {
+    let old_header = match before {
+        Some(_) => format!("a/{}", path),
+        None => "/dev/null".to_string(),
+    };
+    TextDiff::from_lines(before.unwrap_or(""), after)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&old_header, &format!("b/{}", path))
+        .to_string()
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(path: &str, old: &str, new: &str) -> FileOp {
+        FileOp {
+            path: path.to_string(),
+            change: Change::Edit(vec![FileEdit {
+                old: old.to_string(),
+                new: new.to_string(),
+                replace_all: false,
+            }]),
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_infers_original_by_undoing_edits_on_disk() {
+        let ops = vec![
+            edit("/r/a.rs", "let a = 1;", "let a = 2;"),
+            edit("/r/a.rs", "let a = 2;", "let a = 3;"),
+        ];
+        let (before, after) =
+            file_patch(&ops, Some("fn f() {\n    let a = 3;\n}\n"), Base::Inferred).unwrap();
+        assert_eq!(before.as_deref(), Some("fn f() {\n    let a = 1;\n}\n"));
+        assert_eq!(after, "fn f() {\n    let a = 3;\n}\n");
+    }
+
+    #[test]
+    fn test_falls_back_to_read_result() {
+        let read = FileOp {
+            path: "/r/a.rs".to_string(),
+            change: Change::Read(read_content("     1→one\n     2→two").unwrap()),
+            timestamp: None,
+        };
+        let ops = vec![read, edit("/r/a.rs", "two", "three")];
+        // The file changed again after the session, so undoing doesn't reproduce it
+        let (before, after) = file_patch(&ops, Some("zero\n"), Base::Inferred).unwrap();
+        assert_eq!(before.as_deref(), Some("one\ntwo"));
+        assert_eq!(after, "one\nthree");
+    }
+
+    #[test]
+    fn test_created_file_diffs_against_dev_null() {
+        let ops = vec![FileOp {
+            path: "/r/new.rs".to_string(),
+            change: Change::Write {
+                content: "hello\n".to_string(),
+                created: true,
+            },
+            timestamp: None,
+        }];
+        let (before, after) = file_patch(&ops, None, Base::Inferred).unwrap();
+        assert_eq!(before, None);
+        assert_eq!(
+            file_diff(
+                &display_path("/r/new.rs", Some("/r")),
+                before.as_deref(),
+                &after
+            ),
+            "--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+hello\n"
+        );
+    }
+
+    #[test]
+    fn test_read_content_strips_line_numbers() {
+        assert_eq!(
+            read_content("     1→a\n     2→\n\n<system-reminder>x</system-reminder>").as_deref(),
+            Some("a\n")
+        );
+        assert_eq!(read_content("     1→a\nb"), None);
+        assert_eq!(read_content("File does not exist."), None);
+    }
+
+    #[test]
+    fn test_read_content_rejects_truncated_results() {
+        let long_line = format!("     1→{}", "x".repeat(READ_LINE_LENGTH_LIMIT));
+        assert_eq!(read_content(&long_line), None);
+
+        let numbered = |count: usize| {
+            (1..=count)
+                .map(|n| format!("{:>6}→line", n))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        assert!(read_content(&numbered(READ_LINE_LIMIT - 1)).is_some());
+        assert_eq!(read_content(&numbered(READ_LINE_LIMIT)), None);
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::logs::{TokenUsage, UsageTotals};
+
+/// Price of a model in US dollars per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    /// Cache write price; defaults to 1.25x input (5 minute cache)
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+    /// Cache read price; defaults to 0.1x input
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+}
+
+impl ModelPrice {
+    const fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write: None,
+            cache_read: None,
+        }
+    }
+
+    /// Dollar cost of the given usage at this price
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let cache_write = self.cache_write.unwrap_or(self.input * 1.25);
+        let cache_read = self.cache_read.unwrap_or(self.input * 0.1);
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * cache_write
+            + usage.cache_read_input_tokens as f64 * cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Built-in prices, keyed by model ID prefix
+const DEFAULT_PRICES: &[(&str, ModelPrice)] = &[
+    ("claude-opus-4-5", ModelPrice::new(5.0, 25.0)),
+    ("claude-opus-4-1", ModelPrice::new(15.0, 75.0)),
+    ("claude-opus-4", ModelPrice::new(15.0, 75.0)),
+    ("claude-sonnet-4", ModelPrice::new(3.0, 15.0)),
+    ("claude-3-7-sonnet", ModelPrice::new(3.0, 15.0)),
+    ("claude-3-5-sonnet", ModelPrice::new(3.0, 15.0)),
+    ("claude-haiku-4", ModelPrice::new(1.0, 5.0)),
+    ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0)),
+];
+
+/// Per-model price table used to estimate dollar costs from token usage.
+///
+/// Models are matched by the longest prefix in the table, so `claude-opus-4-5` wins over
+/// `claude-opus-4` for `claude-opus-4-5-20251101`.
+#[derive(Debug, Clone)]
+pub struct PriceTable {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        Self {
+            prices: DEFAULT_PRICES
+                .iter()
+                .map(|(prefix, price)| (prefix.to_string(), *price))
+                .collect(),
+        }
+    }
+}
+
+impl PriceTable {
+    /// Load the built-in table, overlaid with `~/.config/claude-tail/pricing.yaml` if present
+    pub fn load() -> Result<Self> {
+        let mut table = Self::default();
+        if let Some(path) = pricing_file_path()
+            && path.exists()
+        {
+            let content = std::fs::read_to_string(&path)?;
+            let overrides: HashMap<String, ModelPrice> = serde_yaml::from_str(&content)
+                .map_err(|e| anyhow::anyhow!("Invalid {}: {}", path.display(), e))?;
+            table.prices.extend(overrides);
+        }
+        Ok(table)
+    }
+
+    /// Find the price for a model ID by longest matching prefix
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| price)
+    }
+
+    /// Estimated dollar cost of the totals. Models without a known price are skipped;
+    /// returns None when none of the recorded models are priced.
+    pub fn cost(&self, totals: &UsageTotals) -> Option<f64> {
+        let mut cost = None;
+        for (model, usage) in totals.by_model() {
+            if let Some(price) = self.price_for(model) {
+                *cost.get_or_insert(0.0) += price.cost(usage);
+            }
+        }
+        cost
+    }
+}
+
+/// Formats a dollar amount for display: $0.004, $0.42, $12.30
+pub fn format_cost(cost: f64) -> String {
+    if cost < 0.01 {
+        format!("${:.3}", cost)
+    } else {
+        format!("${:.2}", cost)
+    }
+}
+
+fn pricing_file_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("claude-tail").join("pricing.yaml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let table = PriceTable::default();
+        let price = table.price_for("claude-opus-4-5-20251101").unwrap();
+        assert_eq!(price.input, 5.0);
+        let price = table.price_for("claude-opus-4-20250514").unwrap();
+        assert_eq!(price.input, 15.0);
+        assert!(table.price_for("gpt-4").is_none());
+    }
+
+    #[test]
+    fn test_cost_uses_cache_multipliers() {
+        let price = ModelPrice::new(3.0, 15.0);
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+            cache_creation_input_tokens: 1_000_000,
+            cache_read_input_tokens: 1_000_000,
+        };
+        let cost = price.cost(&usage);
+        assert!((cost - (3.0 + 15.0 + 3.75 + 0.3)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cost_skips_unpriced_models() {
+        let table = PriceTable::default();
+        let mut totals = UsageTotals::new();
+        totals.record(
+            None,
+            Some("<synthetic>"),
+            TokenUsage {
+                input_tokens: 10,
+                ..Default::default()
+            },
+        );
+        assert_eq!(table.cost(&totals), None);
+
+        totals.record(
+            None,
+            Some("claude-sonnet-4-5"),
+            TokenUsage {
+                output_tokens: 1_000_000,
+                ..Default::default()
+            },
+        );
+        assert_eq!(table.cost(&totals), Some(15.0));
+    }
+}
@@ -0,0 +1,298 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::logs::{DisplayEntry, TokenUsage, tool_duration};
+
+/// Version of the `--output json` record schema, bumped whenever a field is renamed,
+/// removed or changes meaning. Adding fields or kinds does not bump it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// One line of `claude-tail tail --output json`.
+///
+/// Every record carries `schema_version`, `kind`, `timestamp`, `session_id` and `agent_id`;
+/// the remaining fields are only present for the kinds they apply to. See
+/// `docs/json-output.md` for the full schema.
+#[derive(Debug, Serialize)]
+pub struct Record<'a> {
+    pub schema_version: u32,
+    pub kind: &'static str,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub session_id: &'a str,
+    pub agent_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_error: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook_event: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_type: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branches: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtype: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_attempt: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_in_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<&'a str>,
+}
+
+impl<'a> Record<'a> {
+    fn new(
+        kind: &'static str,
+        timestamp: Option<DateTime<Utc>>,
+        session_id: &'a str,
+        agent_id: &'a str,
+    ) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            kind,
+            timestamp,
+            session_id,
+            agent_id,
+            text: None,
+            tool_name: None,
+            tool_use_id: None,
+            input: None,
+            result: None,
+            is_error: None,
+            duration_ms: None,
+            hook_event: None,
+            hook_name: None,
+            command: None,
+            agent_type: None,
+            description: None,
+            message_id: None,
+            model: None,
+            usage: None,
+            branch: None,
+            branches: None,
+            subtype: None,
+            level: None,
+            trigger: None,
+            pre_tokens: None,
+            retry_attempt: None,
+            max_retries: None,
+            retry_in_ms: None,
+            cwd: None,
+            git_branch: None,
+            version: None,
+        }
+    }
+
+    /// Normalizes a display entry into a record
+    pub fn from_entry(entry: &'a DisplayEntry, session_id: &'a str, agent_id: &'a str) -> Self {
+        let base = |kind| Record::new(kind, entry.timestamp(), session_id, agent_id);
+        match entry {
+            DisplayEntry::UserMessage { text, .. } => Record {
+                text: Some(text),
+                ..base("user_message")
+            },
+            DisplayEntry::AssistantText { text, .. } => Record {
+                text: Some(text),
+                ..base("assistant_text")
+            },
+            DisplayEntry::Thinking { text, .. } => Record {
+                text: Some(text),
+                ..base("thinking")
+            },
+            DisplayEntry::ToolCall {
+                name,
+                input,
+                id,
+                result,
+                ..
+            } => Record {
+                tool_name: Some(name),
+                tool_use_id: Some(id),
+                // Inputs are stored pretty-printed; anything unparseable is passed through as a string
+                input: Some(
+                    serde_json::from_str(input).unwrap_or_else(|_| Value::String(input.clone())),
+                ),
+                result: result.as_ref().map(|r| r.content.as_str()),
+                is_error: result.as_ref().map(|r| r.is_error),
+                duration_ms: tool_duration(entry).map(|d| d.num_milliseconds()),
+                ..base("tool_call")
+            },
+            DisplayEntry::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+                ..
+            } => Record {
+                tool_use_id: Some(tool_use_id),
+                result: Some(content),
+                is_error: Some(*is_error),
+                ..base("tool_result")
+            },
+            DisplayEntry::HookEvent {
+                event,
+                hook_name,
+                command,
+                ..
+            } => Record {
+                hook_event: Some(event),
+                hook_name: hook_name.as_deref(),
+                command: command.as_deref(),
+                ..base("hook_event")
+            },
+            DisplayEntry::AgentSpawn {
+                agent_type,
+                description,
+                ..
+            } => Record {
+                agent_type: Some(agent_type),
+                description: Some(description),
+                ..base("agent_spawn")
+            },
+            DisplayEntry::Usage {
+                message_id,
+                model,
+                usage,
+                ..
+            } => Record {
+                message_id: message_id.as_deref(),
+                model: model.as_deref(),
+                usage: Some(*usage),
+                ..base("usage")
+            },
+            DisplayEntry::BranchPoint {
+                branch, branches, ..
+            } => Record {
+                branch: Some(*branch),
+                branches: Some(*branches),
+                ..base("branch_point")
+            },
+            DisplayEntry::CompactBoundary {
+                trigger,
+                pre_tokens,
+                ..
+            } => Record {
+                trigger: trigger.as_deref(),
+                pre_tokens: *pre_tokens,
+                ..base("compact_boundary")
+            },
+            DisplayEntry::ApiError {
+                message,
+                retry_attempt,
+                max_retries,
+                retry_in_ms,
+                ..
+            } => Record {
+                text: Some(message),
+                retry_attempt: *retry_attempt,
+                max_retries: *max_retries,
+                retry_in_ms: *retry_in_ms,
+                ..base("api_error")
+            },
+            DisplayEntry::LocalCommand { text, .. } => Record {
+                text: Some(text),
+                ..base("local_command")
+            },
+            DisplayEntry::Metadata { metadata, .. } => Record {
+                cwd: metadata.cwd.as_deref(),
+                git_branch: metadata.git_branch.as_deref(),
+                version: metadata.version.as_deref(),
+                ..base("metadata")
+            },
+            DisplayEntry::SystemNotice {
+                subtype,
+                level,
+                text,
+                ..
+            } => Record {
+                subtype: subtype.as_deref(),
+                level: level.as_deref(),
+                text: Some(text),
+                ..base("system")
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_call_record() {
+        let entry = DisplayEntry::tool_call("Bash", json!({"command": "ls"}))
+            .with_result("a.txt", false, None);
+        let value = serde_json::to_value(Record::from_entry(&entry, "s1", "main")).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "schema_version": SCHEMA_VERSION,
+                "kind": "tool_call",
+                "timestamp": null,
+                "session_id": "s1",
+                "agent_id": "main",
+                "tool_name": "Bash",
+                "tool_use_id": "toolu_1",
+                "input": {"command": "ls"},
+                "result": "a.txt",
+                "is_error": false
+            })
+        );
+    }
+
+    #[test]
+    fn test_usage_record() {
+        let entry = DisplayEntry::Usage {
+            message_id: Some("msg_1".to_string()),
+            model: None,
+            usage: TokenUsage {
+                input_tokens: 3,
+                output_tokens: 7,
+                ..Default::default()
+            },
+            timestamp: "2025-01-02T03:04:05Z".parse().ok(),
+        };
+        let value = serde_json::to_value(Record::from_entry(&entry, "s1", "a1")).unwrap();
+        assert_eq!(value["kind"], "usage");
+        assert_eq!(value["timestamp"], "2025-01-02T03:04:05Z");
+        assert_eq!(value["message_id"], "msg_1");
+        assert_eq!(value["usage"]["output_tokens"], 7);
+        assert!(value.get("model").is_none());
+    }
+}
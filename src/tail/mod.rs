@@ -0,0 +1,438 @@
+pub mod json;
+
+use std::collections::VecDeque;
+use std::io::{self, IsTerminal, Write};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+
+use crate::input::Keymap;
+use crate::logs::{
+    DisplayEntry, ResponseTimer, SessionMetadata, SessionWatcher, TokenUsage, WatcherEvent,
+    find_agent, find_session, latest_session, merge_tool_results, parse_jsonl_file,
+    parse_jsonl_from_position_async,
+};
+use crate::ui::{ConversationView, SearchState, Theme};
+
+use json::Record;
+
+/// Width used to lay out entries when stdout is not a terminal (effectively no wrapping)
+const UNWRAPPED_WIDTH: usize = u16::MAX as usize;
+
+/// When to color headless output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// Color when stdout is a terminal and NO_COLOR is unset
+    Auto,
+    Always,
+    Never,
+}
+
+/// Output format for `claude-tail tail`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Formatted like the conversation pane
+    Text,
+    /// One JSON object per entry (NDJSON), see docs/json-output.md
+    Json,
+}
+
+/// Arguments for `claude-tail tail`
+#[derive(Debug, clap::Args)]
+pub struct TailArgs {
+    /// Session ID (or unique prefix), or path to a session .jsonl file
+    #[arg(long, conflicts_with = "latest")]
+    session: Option<String>,
+
+    /// Follow the session with the most recent activity (the default)
+    #[arg(long)]
+    latest: bool,
+
+    /// Agent to follow: "main" (default), or a sub-agent ID or name
+    #[arg(short, long, default_value = "main")]
+    agent: String,
+
+    /// Number of existing entries to print before following
+    #[arg(short = 'n', long, default_value_t = 10)]
+    entries: usize,
+
+    /// Show thinking blocks
+    #[arg(long)]
+    thinking: bool,
+
+    /// Collapse tool calls to a one-line summary
+    #[arg(long)]
+    collapse_tools: bool,
+
+    /// Show working directory, git branch and version changes
+    #[arg(long)]
+    metadata: bool,
+
+    /// Print the existing entries and exit instead of following the file
+    #[arg(long)]
+    no_follow: bool,
+
+    /// When to use colors
+    #[arg(long, value_enum, default_value_t = ColorMode::Auto)]
+    color: ColorMode,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+/// Runs `claude-tail tail`: prints a conversation as plain text and follows it like `tail -f`
+pub async fn run(args: &TailArgs, theme: &Theme) -> Result<()> {
+    let session = match &args.session {
+        Some(query) => find_session(query)?,
+        None => latest_session()?,
+    };
+    let agent = find_agent(&session, &args.agent)?;
+
+    let color = match args.color {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+    };
+    let width = if io::stdout().is_terminal() {
+        crossterm::terminal::size()
+            .map(|(w, _)| w as usize)
+            .unwrap_or(UNWRAPPED_WIDTH)
+    } else {
+        UNWRAPPED_WIDTH
+    };
+    let search = SearchState::new();
+    let keys = Keymap::default();
+    let mut printer = Printer {
+        view: ConversationView::new(
+            &EMPTY,
+            false,
+            theme,
+            args.thinking,
+            !args.collapse_tools,
+            true,
+            args.metadata,
+            false,
+            &search,
+            &keys,
+        ),
+        output: args.output,
+        session_id: &session.id,
+        agent_id: &agent.id,
+        show_thinking: args.thinking,
+        color,
+        width,
+        last_usage: None,
+        last_metadata: None,
+        response_timer: ResponseTimer::new(),
+    };
+
+    let initial = parse_jsonl_file(&agent.log_path)?;
+    let bytes_read = initial.bytes_read;
+    let entries = merge_tool_results(initial.latest_branch());
+    let skip = entries.len().saturating_sub(args.entries);
+    // Metadata is only printed when it changes, including from entries that were skipped
+    printer.last_metadata = entries[..skip].iter().rev().find_map(|entry| match entry {
+        DisplayEntry::Metadata { metadata, .. } => Some(metadata.clone()),
+        _ => None,
+    });
+    if !printer.print_all(&entries[skip..])? || args.no_follow {
+        return Ok(());
+    }
+
+    let mut watcher = SessionWatcher::new();
+    watcher.watch(agent.log_path.clone())?;
+    watcher.set_file_position(bytes_read);
+
+    loop {
+        tokio::select! {
+            event = watcher.next_event() => match event {
+                Some(WatcherEvent::FileModified(_)) => {
+                    let result =
+                        parse_jsonl_from_position_async(agent.log_path.clone(), watcher.file_position())
+                            .await?;
+                    watcher.set_file_position(result.bytes_read);
+                    if !printer.print_all(&merge_tool_results(result.entries))? {
+                        return Ok(());
+                    }
+                }
+                Some(WatcherEvent::Error(e)) => eprintln!("Watch error: {}", e),
+                None => return Ok(()),
+            },
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+static EMPTY: VecDeque<DisplayEntry> = VecDeque::new();
+
+/// Formats entries with the conversation pane's layout and writes them to stdout
+struct Printer<'a> {
+    view: ConversationView<'a>,
+    output: OutputFormat,
+    session_id: &'a str,
+    agent_id: &'a str,
+    show_thinking: bool,
+    color: bool,
+    width: usize,
+    /// A streamed response repeats its usage on every line; only print it when it changed.
+    /// A response split across two reads logs partial usage first, so the last line printed
+    /// for a message carries its final usage.
+    last_usage: Option<(String, TokenUsage)>,
+    /// Each batch of new lines starts with their metadata; only print it when it changed
+    last_metadata: Option<SessionMetadata>,
+    /// Times the wait between each prompt and the first output after it
+    response_timer: ResponseTimer,
+}
+
+impl Printer<'_> {
+    /// Prints entries, returning false once stdout has been closed (e.g. piped into `head`)
+    fn print_all(&mut self, entries: &[DisplayEntry]) -> Result<bool> {
+        self.write_all(&mut io::stdout().lock(), entries)
+    }
+
+    /// Writes entries to `out`, returning false once it has been closed
+    fn write_all(&mut self, out: &mut impl Write, entries: &[DisplayEntry]) -> Result<bool> {
+        for entry in entries {
+            match entry {
+                DisplayEntry::Thinking { .. } if !self.show_thinking => continue,
+                DisplayEntry::Usage {
+                    message_id, usage, ..
+                } => {
+                    let current = message_id.clone().map(|id| (id, *usage));
+                    if current.is_some() && current == self.last_usage {
+                        continue;
+                    }
+                    self.last_usage = current;
+                }
+                DisplayEntry::Metadata { metadata, .. } => {
+                    if self.last_metadata.as_ref() == Some(metadata) {
+                        continue;
+                    }
+                    self.last_metadata = Some(metadata.clone());
+                }
+                _ => {}
+            }
+            // Hidden thinking doesn't count as the response; time the first printed output
+            let response_time = self.response_timer.record(entry);
+
+            let text = match self.output {
+                OutputFormat::Json => serde_json::to_string(&Record::from_entry(
+                    entry,
+                    self.session_id,
+                    self.agent_id,
+                ))?,
+                OutputFormat::Text => {
+                    let lines = self.view.entry_lines(entry, response_time, self.width);
+                    if lines.is_empty() {
+                        continue;
+                    }
+                    lines
+                        .iter()
+                        .map(|line| {
+                            if self.color {
+                                ansi_line(line)
+                            } else {
+                                plain_line(line)
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }
+            };
+            match writeln!(out, "{}", text) {
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(false),
+                result => result?,
+            }
+        }
+        match out.flush() {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(false),
+            result => result.map(|_| true).map_err(Into::into),
+        }
+    }
+}
+
+fn plain_line(line: &Line) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+/// Renders a line with ANSI SGR escapes for each span's style
+fn ansi_line(line: &Line) -> String {
+    let mut out = String::new();
+    for span in &line.spans {
+        let codes = sgr_codes(line.style.patch(span.style));
+        if codes.is_empty() {
+            out.push_str(&span.content);
+        } else {
+            out.push_str(&format!("\x1b[{}m{}\x1b[0m", codes.join(";"), span.content));
+        }
+    }
+    out
+}
+
+fn sgr_codes(style: Style) -> Vec<String> {
+    let mut codes = Vec::new();
+    for (modifier, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+    ] {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    if let Some(fg) = style.fg.and_then(|c| color_code(c, false)) {
+        codes.push(fg);
+    }
+    if let Some(bg) = style.bg.and_then(|c| color_code(c, true)) {
+        codes.push(bg);
+    }
+    codes
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let (base, extended) = if background { (40, 48) } else { (30, 38) };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Indexed(i) => return Some(format!("{};5;{}", extended, i)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", extended, r, g, b)),
+    };
+    Some(code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::text::Span;
+
+    fn usage(message_id: &str, output_tokens: u64) -> DisplayEntry {
+        DisplayEntry::Usage {
+            message_id: Some(message_id.to_string()),
+            model: None,
+            usage: TokenUsage {
+                input_tokens: 5,
+                output_tokens,
+                ..TokenUsage::default()
+            },
+            timestamp: None,
+        }
+    }
+
+    /// Writes each batch as `tail` does, returning the output lines
+    fn print_batches(output: OutputFormat, batches: &[Vec<DisplayEntry>]) -> Vec<String> {
+        let theme = Theme::default();
+        let search = SearchState::new();
+        let keys = Keymap::default();
+        let mut printer = Printer {
+            view: ConversationView::new(
+                &EMPTY, false, &theme, false, true, true, false, false, &search, &keys,
+            ),
+            output,
+            session_id: "s1",
+            agent_id: "main",
+            show_thinking: false,
+            color: false,
+            width: 80,
+            last_usage: None,
+            last_metadata: None,
+            response_timer: ResponseTimer::new(),
+        };
+        let mut out = Vec::new();
+        for batch in batches {
+            assert!(printer.write_all(&mut out, batch).unwrap());
+        }
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_last_json_usage_record_is_final() {
+        let batches = [
+            vec![usage("msg_1", 1)],
+            vec![usage("msg_1", 1), usage("msg_1", 40), usage("msg_2", 3)],
+        ];
+        let records: Vec<serde_json::Value> = print_batches(OutputFormat::Json, &batches)
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let last = records
+            .iter()
+            .rfind(|record| record["message_id"] == "msg_1")
+            .unwrap();
+        assert_eq!(last["usage"]["output_tokens"], 40);
+        assert_eq!(records.len(), 3);
+    }
+
+    #[test]
+    fn test_usage_reprinted_when_it_grows() {
+        let text = DisplayEntry::AssistantText {
+            text: "Done".to_string(),
+            timestamp: None,
+        };
+        // Partial usage in the first read, final usage for the same message in the next
+        let batches = [
+            vec![usage("msg_1", 1), text.clone()],
+            vec![usage("msg_1", 1), usage("msg_1", 40)],
+        ];
+        let lines = print_batches(OutputFormat::Text, &batches);
+        let usage_lines: Vec<&String> = lines.iter().filter(|l| l.contains(" out")).collect();
+        assert_eq!(usage_lines.len(), 2, "{:?}", lines);
+        assert!(usage_lines[1].contains("40 out"));
+    }
+
+    #[test]
+    fn test_ansi_line_styles_spans() {
+        let line = Line::from(vec![
+            Span::styled(
+                "User",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(": "),
+            Span::styled("hi", Style::default().fg(Color::Rgb(1, 2, 3))),
+        ]);
+        assert_eq!(
+            ansi_line(&line),
+            "\x1b[1;32mUser\x1b[0m: \x1b[38;2;1;2;3mhi\x1b[0m"
+        );
+        assert_eq!(plain_line(&line), "User: hi");
+    }
+
+    #[test]
+    fn test_color_codes() {
+        assert_eq!(color_code(Color::DarkGray, false).as_deref(), Some("90"));
+        assert_eq!(color_code(Color::White, true).as_deref(), Some("107"));
+        assert_eq!(
+            color_code(Color::Indexed(200), true).as_deref(),
+            Some("48;5;200")
+        );
+        assert_eq!(color_code(Color::Reset, false), None);
+    }
+}
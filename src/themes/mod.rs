@@ -69,7 +69,7 @@ impl Base16Scheme {
         // base0E - Keywords, Storage, Selector, Diff Changed
         // base0F - Deprecated, Special
 
-        let _bg = Self::parse_hex(&self.base00)?;
+        let bg = Self::parse_hex(&self.base00)?;
         let bg_light = Self::parse_hex(&self.base01)?;
         let selection = Self::parse_hex(&self.base02)?;
         let comment = Self::parse_hex(&self.base03)?;
@@ -77,7 +77,7 @@ impl Base16Scheme {
         let fg = Self::parse_hex(&self.base05)?;
         let _fg_light = Self::parse_hex(&self.base06)?;
         let red = Self::parse_hex(&self.base08)?;
-        let _orange = Self::parse_hex(&self.base09)?;
+        let orange = Self::parse_hex(&self.base09)?;
         let yellow = Self::parse_hex(&self.base0a)?;
         let green = Self::parse_hex(&self.base0b)?;
         let cyan = Self::parse_hex(&self.base0c)?;
@@ -85,6 +85,7 @@ impl Base16Scheme {
         let purple = Self::parse_hex(&self.base0e)?;
 
         Ok(Theme {
+            background: Style::default().bg(bg).fg(fg),
             border: Style::default().fg(comment),
             border_focused: Style::default().fg(cyan),
             title: Style::default().fg(fg),
@@ -102,9 +103,31 @@ impl Base16Scheme {
             thinking_collapsed: Style::default().fg(comment).add_modifier(Modifier::ITALIC),
             hook_event: Style::default().fg(blue),
             agent_spawn: Style::default().fg(purple),
+            compact_boundary: Style::default().fg(orange).add_modifier(Modifier::BOLD),
+            api_error: Style::default().fg(red),
+            local_command: Style::default().fg(cyan),
+            system_notice: Style::default().fg(fg_dark),
+            metadata: Style::default().fg(comment).add_modifier(Modifier::ITALIC),
             status_bar: Style::default().bg(bg_light).fg(fg),
             key_hint: Style::default().fg(cyan),
             timestamp: Style::default().fg(comment),
+            search_match: Style::default().bg(yellow).fg(bg),
+            search_current: Style::default()
+                .bg(orange)
+                .fg(bg)
+                .add_modifier(Modifier::BOLD),
+            syntax_comment: Style::default().fg(comment).add_modifier(Modifier::ITALIC),
+            syntax_keyword: Style::default().fg(purple),
+            syntax_string: Style::default().fg(green),
+            syntax_constant: Style::default().fg(orange),
+            syntax_type: Style::default().fg(yellow),
+            syntax_function: Style::default().fg(blue),
+            syntax_variable: Style::default().fg(red),
+            syntax_special: Style::default().fg(cyan),
+            markdown_heading: Style::default().fg(blue).add_modifier(Modifier::BOLD),
+            markdown_code: Style::default().fg(green),
+            markdown_link: Style::default().fg(red).add_modifier(Modifier::UNDERLINED),
+            markdown_quote: Style::default().fg(comment).add_modifier(Modifier::ITALIC),
         })
     }
 }
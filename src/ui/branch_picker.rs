@@ -0,0 +1,150 @@
+use chrono::Local;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, StatefulWidget, Widget},
+};
+
+use super::styles::Theme;
+use crate::logs::Branch;
+
+/// State of the branch picker overlay
+pub struct BranchPickerState {
+    /// Whether the overlay is shown
+    pub open: bool,
+    pub branches: Vec<Branch>,
+    /// Leaf of the branch currently shown in the conversation pane
+    pub current: Option<usize>,
+    pub list_state: ListState,
+}
+
+impl BranchPickerState {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            branches: Vec::new(),
+            current: None,
+            list_state: ListState::default(),
+        }
+    }
+
+    /// Show the overlay with the current branch selected
+    pub fn show(&mut self, branches: Vec<Branch>, current: Option<usize>) {
+        let selected = branches
+            .iter()
+            .position(|branch| Some(branch.leaf) == current)
+            .unwrap_or(0);
+        self.branches = branches;
+        self.current = current;
+        self.list_state = ListState::default();
+        self.list_state.select(Some(selected));
+        self.open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn selected_leaf(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.branches.get(i))
+            .map(|branch| branch.leaf)
+    }
+
+    pub fn next(&mut self) {
+        if self.branches.is_empty() {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(i) if i + 1 < self.branches.len() => i + 1,
+            Some(_) => 0,
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        if self.branches.is_empty() {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(0) | None => self.branches.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.list_state.select(Some(i));
+    }
+}
+
+impl Default for BranchPickerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Centered overlay listing the branches of the conversation, indented by fork depth
+pub struct BranchPickerView<'a> {
+    theme: &'a Theme,
+}
+
+impl<'a> BranchPickerView<'a> {
+    pub fn new(theme: &'a Theme) -> Self {
+        Self { theme }
+    }
+}
+
+impl<'a> StatefulWidget for BranchPickerView<'a> {
+    type State = BranchPickerState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let width = area.width.saturating_sub(8).clamp(20, 100).min(area.width);
+        let height = (state.branches.len() as u16 + 2)
+            .max(5)
+            .min(area.height.saturating_sub(4))
+            .min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        Clear.render(popup, buf);
+
+        let title = format!(" Branches ({}) ", state.branches.len());
+        let block = Block::default()
+            .title(Span::styled(title, self.theme.title_focused))
+            .borders(Borders::ALL)
+            .border_style(self.theme.border_focused);
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+
+        let items: Vec<ListItem> = state
+            .branches
+            .iter()
+            .map(|branch| {
+                let timestamp = branch
+                    .timestamp
+                    .map(|t| t.with_timezone(&Local).format("%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                let marker = if Some(branch.leaf) == state.current {
+                    "● "
+                } else {
+                    "  "
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<12}", timestamp), self.theme.timestamp),
+                    Span::styled(marker, self.theme.key_hint),
+                    Span::raw("  ".repeat(branch.depth.saturating_sub(1))),
+                    Span::styled(branch.label.clone(), self.theme.user_message),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        StatefulWidget::render(list, inner, buf, &mut state.list_state);
+    }
+}
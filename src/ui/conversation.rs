@@ -1,35 +1,60 @@
+use chrono::Duration;
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
+    style::{Modifier, Style},
     text::{Line, Span, Text},
     widgets::{
         Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
         Widget,
     },
 };
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use syntect::parsing::SyntaxReference;
 use unicode_width::UnicodeWidthStr;
 
+use super::highlight::{Highlighter, shell_syntax, syntax_for_path};
+use super::markdown::{render_markdown, split_spans};
+use super::search::{SearchState, highlight_line};
 use super::styles::Theme;
-use crate::logs::{DisplayEntry, ToolCallResult};
+use crate::input::{KeyAction, Keymap, Scope};
+use crate::logs::diff::{DiffHunk, DiffLineKind, diff_hunks, tool_edits};
+use crate::logs::timing::response_time;
+use crate::logs::{
+    DisplayEntry, ResponseTimer, TokenUsage, ToolCallResult, format_duration, format_token_count,
+    tool_duration,
+};
+
+/// Diff lines shown for an `Edit`/`MultiEdit` call until full diffs are toggled on
+const DIFF_PREVIEW_LINES: usize = 12;
 
+#[derive(Clone, Copy)]
 pub struct ConversationView<'a> {
     entries: &'a VecDeque<DisplayEntry>,
     focused: bool,
     theme: &'a Theme,
     show_thinking: bool,
     expand_tools: bool,
+    full_diffs: bool,
+    show_metadata: bool,
     is_loading: bool,
+    search: &'a SearchState,
+    keys: &'a Keymap,
 }
 
 impl<'a> ConversationView<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         entries: &'a VecDeque<DisplayEntry>,
         focused: bool,
         theme: &'a Theme,
         show_thinking: bool,
         expand_tools: bool,
+        full_diffs: bool,
+        show_metadata: bool,
         is_loading: bool,
+        search: &'a SearchState,
+        keys: &'a Keymap,
     ) -> Self {
         Self {
             entries,
@@ -37,7 +62,21 @@ impl<'a> ConversationView<'a> {
             theme,
             show_thinking,
             expand_tools,
+            full_diffs,
+            show_metadata,
             is_loading,
+            search,
+            keys,
+        }
+    }
+
+    /// The view as it applies to one entry: thinking and tool output expansion are flipped
+    /// for entries the user toggled individually
+    fn for_entry(&self, toggled: bool) -> Self {
+        Self {
+            show_thinking: self.show_thinking != toggled,
+            expand_tools: self.expand_tools != toggled,
+            ..*self
         }
     }
 
@@ -47,16 +86,20 @@ impl<'a> ConversationView<'a> {
         name: &str,
         input: &str,
         result: Option<&ToolCallResult>,
+        duration: Option<Duration>,
         content_width: usize,
     ) {
         // Parse the JSON input to extract relevant fields
         let parsed: Option<serde_json::Value> = serde_json::from_str(input).ok();
 
+        let header = lines.len();
         match name {
             "Bash" => self.render_bash_tool(lines, parsed.as_ref(), content_width),
             "Read" => self.render_read_tool(lines, parsed.as_ref(), content_width),
             "Write" => self.render_write_tool(lines, parsed.as_ref(), content_width),
-            "Edit" => self.render_edit_tool(lines, parsed.as_ref(), content_width),
+            "Edit" | "MultiEdit" => {
+                self.render_edit_tool(lines, name, parsed.as_ref(), content_width)
+            }
             "Grep" => self.render_grep_tool(lines, parsed.as_ref(), content_width),
             "Glob" => self.render_glob_tool(lines, parsed.as_ref(), content_width),
             "Task" => self.render_agent_like_tool(lines, "Task", parsed.as_ref(), content_width),
@@ -66,9 +109,20 @@ impl<'a> ConversationView<'a> {
             _ => self.render_generic_tool(lines, name, input, content_width),
         }
 
-        // Render inline result if present
+        // How long the call took, after its header ("$ Run tests  12.4s")
+        if let Some(duration) = duration
+            && let Some(line) = lines.get_mut(header)
+        {
+            line.spans.push(Span::styled(
+                format!("  {}", format_duration(duration)),
+                self.theme.timestamp,
+            ));
+        }
+
+        // Render inline result if present, highlighting file contents returned by Read
         if let Some(res) = result {
-            self.render_inline_result(lines, res, content_width);
+            let syntax = result_syntax(name, parsed.as_ref(), res);
+            self.render_inline_result(lines, res, syntax, content_width);
         }
     }
 
@@ -76,6 +130,7 @@ impl<'a> ConversationView<'a> {
         &self,
         lines: &mut Vec<Line<'a>>,
         result: &ToolCallResult,
+        syntax: Option<&SyntaxReference>,
         content_width: usize,
     ) {
         if !self.expand_tools {
@@ -123,8 +178,30 @@ impl<'a> ConversationView<'a> {
             } else {
                 result.content.clone()
             };
-            for line in wrap_text(&display_content, content_width.saturating_sub(2)) {
-                lines.push(Line::from(Span::styled(format!("  {}", line), style)));
+            let width = content_width.saturating_sub(2);
+            match syntax {
+                Some(syntax) => {
+                    // Highlight whole lines, so wrapping doesn't split a token's context, then
+                    // wrap the styled text
+                    let mut highlighter = Highlighter::new(syntax, self.theme, style);
+                    for line in display_content.lines() {
+                        // Read output prefixes each line with its number, e.g. "    12→"
+                        let (number, code) = split_line_number(line);
+                        let mut spans =
+                            vec![Span::styled(number.to_string(), self.theme.timestamp)];
+                        spans.extend(highlighter.highlight_line(code));
+                        for chunk in split_spans(spans, width) {
+                            let mut spans = vec![Span::raw("  ")];
+                            spans.extend(chunk);
+                            lines.push(Line::from(spans));
+                        }
+                    }
+                }
+                None => {
+                    for line in wrap_text(&display_content, width) {
+                        lines.push(Line::from(Span::styled(format!("  {}", line), style)));
+                    }
+                }
             }
         }
     }
@@ -155,13 +232,17 @@ impl<'a> ConversationView<'a> {
             lines.push(Line::from(Span::styled("$ Bash", self.theme.tool_name)));
         }
 
-        // Command with syntax highlighting style
+        // Command, highlighted as shell
         if self.expand_tools && !command.is_empty() {
+            let mut highlighter = shell_syntax()
+                .map(|syntax| Highlighter::new(syntax, self.theme, self.theme.tool_input));
             for line in wrap_text(&command, content_width.saturating_sub(2)) {
-                lines.push(Line::from(Span::styled(
-                    format!("  {}", line),
-                    self.theme.tool_input,
-                )));
+                let mut spans = vec![Span::raw("  ")];
+                match highlighter.as_mut() {
+                    Some(highlighter) => spans.extend(highlighter.highlight_line(&line)),
+                    None => spans.push(Span::styled(line, self.theme.tool_input)),
+                }
+                lines.push(Line::from(spans));
             }
         }
     }
@@ -241,19 +322,22 @@ impl<'a> ConversationView<'a> {
             ),
         ]));
 
-        // Show preview of content
+        // Show preview of content, highlighted by file type
         if self.expand_tools && !content.is_empty() {
-            let preview_lines: Vec<&str> = content.lines().take(5).collect();
-            for line in &preview_lines {
-                let truncated = if line.len() > content_width.saturating_sub(4) {
-                    format!("{}…", &line[..content_width.saturating_sub(5)])
-                } else {
-                    line.to_string()
+            let mut highlighter = syntax_for_path(&file_path)
+                .map(|syntax| Highlighter::new(syntax, self.theme, self.theme.tool_input));
+            for line in content.lines().take(5) {
+                let code = match highlighter.as_mut() {
+                    Some(highlighter) => highlighter.highlight_line(line),
+                    None => vec![Span::styled(line.to_string(), self.theme.tool_input)],
                 };
-                lines.push(Line::from(Span::styled(
-                    format!("  │ {}", truncated),
+                let mut spans = vec![Span::styled("  │ ", self.theme.tool_input)];
+                spans.extend(truncate_spans(
+                    code,
+                    content_width.saturating_sub(4),
                     self.theme.tool_input,
-                )));
+                ));
+                lines.push(Line::from(spans));
             }
             if line_count > 5 {
                 lines.push(Line::from(Span::styled(
@@ -267,71 +351,88 @@ impl<'a> ConversationView<'a> {
     fn render_edit_tool(
         &self,
         lines: &mut Vec<Line<'a>>,
+        name: &str,
         parsed: Option<&serde_json::Value>,
         content_width: usize,
     ) {
-        let file_path = parsed
-            .and_then(|v| v.get("file_path"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("<unknown>")
-            .to_string();
-        let old_string = parsed
-            .and_then(|v| v.get("old_string"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-        let new_string = parsed
-            .and_then(|v| v.get("new_string"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
-
-        // Abbreviate the path for display
-        let display_path = abbreviate_path(&file_path);
+        let (file_path, hunks) = edit_hunks(name, parsed);
+        let edit_count = parsed
+            .filter(|_| name == "MultiEdit")
+            .and_then(|v| v.get("edits"))
+            .and_then(|v| v.as_array())
+            .map(|edits| edits.len());
+        let count = |kind| {
+            hunks
+                .iter()
+                .flat_map(|hunk| &hunk.lines)
+                .filter(|line| line.kind == kind)
+                .count()
+        };
+        let mut summary = format!(
+            "+{} -{}",
+            count(DiffLineKind::Added),
+            count(DiffLineKind::Removed)
+        );
+        if let Some(edit_count) = edit_count {
+            summary = format!("{} edits, {}", edit_count, summary);
+        }
 
         lines.push(Line::from(vec![
-            Span::styled("Edit: ", self.theme.tool_name),
-            Span::styled(display_path, self.theme.tool_input),
+            Span::styled(format!("{}: ", name), self.theme.tool_name),
+            Span::styled(abbreviate_path(&file_path), self.theme.tool_input),
+            Span::styled(format!(" ({})", summary), self.theme.thinking_collapsed),
         ]));
 
-        if self.expand_tools {
-            // Show old string (what's being replaced)
-            if !old_string.is_empty() {
-                let old_preview: Vec<&str> = old_string.lines().take(3).collect();
-                lines.push(Line::from(Span::styled("  - old:", self.theme.tool_error)));
-                for line in old_preview {
-                    let truncated = truncate_line(line, content_width.saturating_sub(6));
-                    lines.push(Line::from(Span::styled(
-                        format!("    {}", truncated),
-                        self.theme.tool_error,
-                    )));
-                }
-                if old_string.lines().count() > 3 {
-                    lines.push(Line::from(Span::styled(
-                        format!("    ... ({} more lines)", old_string.lines().count() - 3),
-                        self.theme.thinking_collapsed,
-                    )));
-                }
-            }
+        if !self.expand_tools {
+            return;
+        }
 
-            // Show new string (the replacement)
-            if !new_string.is_empty() {
-                let new_preview: Vec<&str> = new_string.lines().take(3).collect();
-                lines.push(Line::from(Span::styled("  + new:", self.theme.tool_result)));
-                for line in new_preview {
-                    let truncated = truncate_line(line, content_width.saturating_sub(6));
-                    lines.push(Line::from(Span::styled(
-                        format!("    {}", truncated),
-                        self.theme.tool_result,
-                    )));
-                }
-                if new_string.lines().count() > 3 {
-                    lines.push(Line::from(Span::styled(
-                        format!("    ... ({} more lines)", new_string.lines().count() - 3),
-                        self.theme.thinking_collapsed,
-                    )));
-                }
-            }
+        let body_len = diff_body_len(&hunks);
+        let shown = if self.full_diffs {
+            body_len
+        } else {
+            body_len.min(DIFF_PREVIEW_LINES)
+        };
+        let max_width = content_width.saturating_sub(4);
+        let body = hunks.iter().flat_map(|hunk| {
+            std::iter::once(Line::from(Span::styled(
+                format!("  {}", hunk.header),
+                self.theme.timestamp,
+            )))
+            .chain(hunk.lines.iter().map(move |line| {
+                let (sign, style) = match line.kind {
+                    DiffLineKind::Context => (" ", self.theme.tool_input),
+                    DiffLineKind::Added => ("+", self.theme.tool_result),
+                    DiffLineKind::Removed => ("-", self.theme.tool_error),
+                };
+                let segments = line
+                    .segments
+                    .iter()
+                    .map(|(emphasized, text)| {
+                        let style = if *emphasized {
+                            style.add_modifier(Modifier::REVERSED)
+                        } else {
+                            style
+                        };
+                        Span::styled(text.clone(), style)
+                    })
+                    .collect();
+                let mut spans = vec![Span::styled(format!("  {} ", sign), style)];
+                spans.extend(truncate_spans(segments, max_width, style));
+                Line::from(spans)
+            }))
+        });
+        lines.extend(body.take(shown));
+
+        if shown < body_len {
+            let hint = match self.keys.key_for(Scope::Global, KeyAction::ToggleFullDiffs) {
+                Some(key) => format!(", press '{}' for full diffs", key),
+                None => String::new(),
+            };
+            lines.push(Line::from(Span::styled(
+                format!("  ... ({} more lines{})", body_len - shown, hint),
+                self.theme.thinking_collapsed,
+            )));
         }
     }
 
@@ -598,14 +699,30 @@ impl<'a> ConversationView<'a> {
         }
     }
 
-    /// Calculate how many lines an entry would generate without actually rendering
-    fn calculate_entry_lines(&self, entry: &DisplayEntry, content_width: usize) -> usize {
+    /// The settings an entry's layout depends on, besides the width
+    fn layout_key(&self) -> LayoutKey {
+        LayoutKey {
+            show_thinking: self.show_thinking,
+            expand_tools: self.expand_tools,
+            full_diffs: self.full_diffs,
+            show_metadata: self.show_metadata,
+        }
+    }
+
+    /// Calculate how many lines an entry would generate without actually rendering.
+    /// `diff_len` caches the diff length of an `Edit`/`MultiEdit` call, which doesn't depend
+    /// on the view settings or the width.
+    fn calculate_entry_lines(
+        &self,
+        entry: &DisplayEntry,
+        content_width: usize,
+        diff_len: &mut Option<usize>,
+    ) -> usize {
         match entry {
-            DisplayEntry::UserMessage { text, .. } => {
-                1 + wrap_text(text, content_width).len() + 1 // header + wrapped lines + blank
-            }
-            DisplayEntry::AssistantText { text, .. } => {
-                1 + wrap_text(text, content_width).len() + 1 // header + wrapped lines + blank
+            DisplayEntry::UserMessage { text, .. } | DisplayEntry::AssistantText { text, .. } => {
+                // header + Markdown lines (highlighting doesn't change the layout) + blank
+                let style = self.message_style(entry);
+                1 + render_markdown(text, content_width, self.theme, style, false).len() + 1
             }
             DisplayEntry::ToolCall {
                 name,
@@ -613,8 +730,13 @@ impl<'a> ConversationView<'a> {
                 result,
                 ..
             } => {
-                let count =
-                    self.calculate_tool_call_lines(name, input, result.as_ref(), content_width);
+                let count = self.calculate_tool_call_lines(
+                    name,
+                    input,
+                    result.as_ref(),
+                    content_width,
+                    diff_len,
+                );
                 count + 1 // blank line
             }
             DisplayEntry::ToolResult {
@@ -663,6 +785,33 @@ impl<'a> ConversationView<'a> {
                 }
                 count + 1 // blank line
             }
+            DisplayEntry::Usage { .. } => {
+                if self.expand_tools {
+                    1 // token summary line, directly above the message it belongs to
+                } else {
+                    0
+                }
+            }
+            DisplayEntry::BranchPoint { .. } => 2, // marker + blank line
+            DisplayEntry::Metadata { metadata, .. } => {
+                if self.show_metadata {
+                    wrap_text(
+                        &metadata.to_string(),
+                        content_width.saturating_sub(2).max(1),
+                    )
+                    .len()
+                } else {
+                    0
+                }
+            }
+            DisplayEntry::CompactBoundary { .. }
+            | DisplayEntry::ApiError { .. }
+            | DisplayEntry::LocalCommand { .. }
+            | DisplayEntry::SystemNotice { .. } => {
+                let mut lines = Vec::new();
+                self.render_system_entry(&mut lines, entry, content_width);
+                lines.len()
+            }
         }
     }
 
@@ -673,6 +822,7 @@ impl<'a> ConversationView<'a> {
         input: &str,
         result: Option<&ToolCallResult>,
         content_width: usize,
+        diff_len: &mut Option<usize>,
     ) -> usize {
         let parsed: Option<serde_json::Value> = serde_json::from_str(input).ok();
 
@@ -680,7 +830,7 @@ impl<'a> ConversationView<'a> {
             "Bash" => self.calculate_bash_tool_lines(parsed.as_ref(), content_width),
             "Read" => self.calculate_read_tool_lines(parsed.as_ref()),
             "Write" => self.calculate_write_tool_lines(parsed.as_ref(), content_width),
-            "Edit" => self.calculate_edit_tool_lines(parsed.as_ref()),
+            "Edit" | "MultiEdit" => self.calculate_edit_tool_lines(name, parsed.as_ref(), diff_len),
             "Grep" => self.calculate_grep_tool_lines(parsed.as_ref()),
             "Glob" => self.calculate_glob_tool_lines(parsed.as_ref()),
             "Task" | "Agent" => self.calculate_task_tool_lines(parsed.as_ref(), content_width),
@@ -691,7 +841,8 @@ impl<'a> ConversationView<'a> {
 
         // Add inline result lines if present
         if let Some(res) = result {
-            count += self.calculate_inline_result_lines(res, content_width);
+            let highlighted = result_syntax(name, parsed.as_ref(), res).is_some();
+            count += self.calculate_inline_result_lines(res, highlighted, content_width);
         }
 
         count
@@ -750,30 +901,20 @@ impl<'a> ConversationView<'a> {
         count
     }
 
-    fn calculate_edit_tool_lines(&self, parsed: Option<&serde_json::Value>) -> usize {
+    fn calculate_edit_tool_lines(
+        &self,
+        name: &str,
+        parsed: Option<&serde_json::Value>,
+        diff_len: &mut Option<usize>,
+    ) -> usize {
         let mut count = 1; // header
         if self.expand_tools {
-            if let Some(old_string) = parsed
-                .and_then(|v| v.get("old_string"))
-                .and_then(|v| v.as_str())
-                && !old_string.is_empty()
-            {
-                count += 1; // "- old:" label
-                count += old_string.lines().count().min(3);
-                if old_string.lines().count() > 3 {
-                    count += 1; // "more lines" indicator
-                }
-            }
-            if let Some(new_string) = parsed
-                .and_then(|v| v.get("new_string"))
-                .and_then(|v| v.as_str())
-                && !new_string.is_empty()
-            {
-                count += 1; // "+ new:" label
-                count += new_string.lines().count().min(3);
-                if new_string.lines().count() > 3 {
-                    count += 1; // "more lines" indicator
-                }
+            let body_len =
+                *diff_len.get_or_insert_with(|| diff_body_len(&edit_hunks(name, parsed).1));
+            if self.full_diffs || body_len <= DIFF_PREVIEW_LINES {
+                count += body_len;
+            } else {
+                count += DIFF_PREVIEW_LINES + 1; // preview + "more lines" indicator
             }
         }
         count
@@ -870,6 +1011,7 @@ impl<'a> ConversationView<'a> {
     fn calculate_inline_result_lines(
         &self,
         result: &ToolCallResult,
+        highlighted: bool,
         content_width: usize,
     ) -> usize {
         if !self.expand_tools {
@@ -892,18 +1034,388 @@ impl<'a> ConversationView<'a> {
             } else {
                 result.content.as_str()
             };
-            count += wrap_text(display_content, content_width.saturating_sub(2)).len();
+            let width = content_width.saturating_sub(2);
+            count += if highlighted {
+                display_content
+                    .lines()
+                    .map(|line| split_spans(vec![Span::raw(line.to_string())], width).len())
+                    .sum()
+            } else {
+                wrap_text(display_content, width).len()
+            };
         }
         count
     }
 
-    /// Calculate total lines that would be rendered for all entries
-    fn calculate_total_lines(&self, width: usize) -> usize {
+    /// Calculate the start line and line count of every entry. Line counts are cached in
+    /// `state`, so only entries that are new or changed since the last render are laid out.
+    fn calculate_entry_info(
+        &self,
+        width: usize,
+        state: &mut ConversationState,
+    ) -> Vec<(usize, usize)> {
         let content_width = width.saturating_sub(4);
-        self.entries
+        if state.layout_width != content_width {
+            state.layout_width = content_width;
+            // Diff lengths don't depend on the width and stay cached
+            for layout in &mut state.entry_layouts {
+                layout.line_counts.clear();
+            }
+        }
+        state
+            .entry_layouts
+            .resize_with(self.entries.len(), EntryLayout::default);
+
+        let mut info = Vec::with_capacity(self.entries.len());
+        let mut current_line = 0;
+        let mut timer = ResponseTimer::new();
+        for (entry_idx, (entry, layout)) in self
+            .entries
             .iter()
-            .map(|entry| self.calculate_entry_lines(entry, content_width))
-            .sum()
+            .zip(&mut state.entry_layouts)
+            .enumerate()
+        {
+            let view = self.for_entry(state.toggled_entries.contains(&entry_idx));
+            let mut line_count =
+                *layout
+                    .line_counts
+                    .entry(view.layout_key())
+                    .or_insert_with(|| {
+                        view.calculate_entry_lines(entry, content_width, &mut layout.diff_len)
+                    });
+            if timer.record(entry).is_some() {
+                line_count += 1; // response time
+            }
+            info.push((current_line, line_count));
+            current_line += line_count;
+        }
+        info
+    }
+
+    /// Lines for a single entry at the given total width, as the conversation pane shows it.
+    /// `response_time` is the wait since the prompt, for the first output after it.
+    pub fn entry_lines(
+        &self,
+        entry: &DisplayEntry,
+        response_time: Option<Duration>,
+        width: usize,
+    ) -> Vec<Line<'a>> {
+        let mut lines = Vec::new();
+        self.render_response_time(&mut lines, response_time);
+        self.render_entry(&mut lines, entry, width.saturating_sub(4));
+        lines
+    }
+
+    fn message_style(&self, entry: &DisplayEntry) -> Style {
+        match entry {
+            DisplayEntry::UserMessage { .. } => self.theme.user_message,
+            _ => self.theme.assistant_text,
+        }
+    }
+
+    /// User and assistant text, rendered as Markdown and indented under the label
+    fn render_message_text(
+        &self,
+        lines: &mut Vec<Line<'a>>,
+        entry: &DisplayEntry,
+        text: &str,
+        content_width: usize,
+    ) {
+        let style = self.message_style(entry);
+        for line in render_markdown(text, content_width, self.theme, style, true) {
+            let mut spans = vec![Span::raw("  ")];
+            spans.extend(line.spans);
+            lines.push(Line::from(spans));
+        }
+    }
+
+    /// Time from a prompt to the first output after it, above that output
+    fn render_response_time(&self, lines: &mut Vec<Line<'a>>, response_time: Option<Duration>) {
+        if let Some(duration) = response_time {
+            lines.push(Line::from(Span::styled(
+                format!("⏱ {} to respond", format_duration(duration)),
+                self.theme.timestamp,
+            )));
+        }
+    }
+
+    /// Appends the lines for one entry
+    fn render_entry(&self, lines: &mut Vec<Line<'a>>, entry: &DisplayEntry, content_width: usize) {
+        match entry {
+            DisplayEntry::UserMessage { text, .. } => {
+                lines.push(Line::from(vec![
+                    Span::styled("User", self.theme.user_label),
+                    Span::raw(": "),
+                ]));
+                self.render_message_text(lines, entry, text, content_width);
+                lines.push(Line::from(""));
+            }
+            DisplayEntry::AssistantText { text, .. } => {
+                lines.push(Line::from(vec![
+                    Span::styled("Assistant", self.theme.assistant_label),
+                    Span::raw(": "),
+                ]));
+                self.render_message_text(lines, entry, text, content_width);
+                lines.push(Line::from(""));
+            }
+            DisplayEntry::ToolCall {
+                name,
+                input,
+                result,
+                ..
+            } => {
+                self.render_tool_call(
+                    lines,
+                    name,
+                    input,
+                    result.as_ref(),
+                    tool_duration(entry),
+                    content_width,
+                );
+                lines.push(Line::from(""));
+            }
+            DisplayEntry::ToolResult {
+                content, is_error, ..
+            } => {
+                let (label, style) = if *is_error {
+                    ("Error", self.theme.tool_error)
+                } else {
+                    ("Result", self.theme.tool_result)
+                };
+                lines.push(Line::from(Span::styled(format!("[{}]", label), style)));
+                if self.expand_tools && !content.is_empty() {
+                    // Truncate very long results (respecting char boundaries)
+                    let display_content = if content.len() > 500 {
+                        let truncate_at = content
+                            .char_indices()
+                            .take_while(|(i, _)| *i < 500)
+                            .last()
+                            .map(|(i, c)| i + c.len_utf8())
+                            .unwrap_or(0);
+                        format!("{}...", &content[..truncate_at])
+                    } else {
+                        content.clone()
+                    };
+                    for line in wrap_text(&display_content, content_width) {
+                        lines.push(Line::from(Span::styled(format!("  {}", line), style)));
+                    }
+                }
+                lines.push(Line::from(""));
+            }
+            DisplayEntry::Thinking { text, .. } => {
+                if self.show_thinking {
+                    lines.push(Line::from(Span::styled(
+                        "Thinking:",
+                        self.theme.thinking_collapsed,
+                    )));
+                    for line in wrap_text(text, content_width) {
+                        lines.push(Line::from(Span::styled(
+                            format!("  {}", line),
+                            self.theme.thinking,
+                        )));
+                    }
+                    lines.push(Line::from(""));
+                } else {
+                    let text = match self.keys.key_for(Scope::Global, KeyAction::ToggleThinking) {
+                        Some(key) => format!("[Thinking collapsed - press '{}' to show]", key),
+                        None => "[Thinking collapsed]".to_string(),
+                    };
+                    lines.push(Line::from(Span::styled(
+                        text,
+                        self.theme.thinking_collapsed,
+                    )));
+                }
+            }
+            DisplayEntry::HookEvent {
+                event,
+                hook_name,
+                command,
+                ..
+            } => {
+                // Extract tool name from hook_name if present (e.g., "PostToolUse:Read" -> "Read")
+                let tool_info = hook_name
+                    .as_ref()
+                    .and_then(|name| name.split(':').nth(1).map(|s| s.to_string()));
+
+                // Build the header line
+                let header = if let Some(tool) = &tool_info {
+                    format!("Hook: {} ({})", event, tool)
+                } else {
+                    format!("Hook: {}", event)
+                };
+
+                lines.push(Line::from(Span::styled(header, self.theme.hook_event)));
+
+                // Show command if expanded and it's a real command (not just "callback")
+                if self.expand_tools
+                    && let Some(cmd) = command
+                    && cmd != "callback"
+                {
+                    // Abbreviate long commands (respecting char boundaries)
+                    let display_cmd = if cmd.len() > 60 {
+                        let truncate_at = cmd
+                            .char_indices()
+                            .take_while(|(i, _)| *i < 57)
+                            .last()
+                            .map(|(i, c)| i + c.len_utf8())
+                            .unwrap_or(0);
+                        format!("{}...", &cmd[..truncate_at])
+                    } else {
+                        cmd.clone()
+                    };
+                    lines.push(Line::from(Span::styled(
+                        format!("  → {}", display_cmd),
+                        self.theme.hook_event,
+                    )));
+                }
+                lines.push(Line::from(""));
+            }
+            DisplayEntry::AgentSpawn {
+                agent_type,
+                description,
+                ..
+            } => {
+                lines.push(Line::from(vec![
+                    Span::styled("Agent: ", self.theme.agent_spawn),
+                    Span::styled(agent_type.clone(), self.theme.agent_spawn),
+                ]));
+                if !description.is_empty() {
+                    lines.push(Line::from(Span::styled(
+                        format!("  {}", description),
+                        self.theme.agent_spawn,
+                    )));
+                }
+                lines.push(Line::from(""));
+            }
+            DisplayEntry::Usage { model, usage, .. } => {
+                if self.expand_tools {
+                    let mut spans = vec![Span::styled(
+                        format!("Tokens: {}", format_usage(usage)),
+                        self.theme.timestamp,
+                    )];
+                    if let Some(model) = model {
+                        spans.push(Span::styled(
+                            format!(" ({})", model),
+                            self.theme.thinking_collapsed,
+                        ));
+                    }
+                    lines.push(Line::from(spans));
+                }
+            }
+            DisplayEntry::BranchPoint {
+                branch, branches, ..
+            } => {
+                lines.push(Line::from(Span::styled(
+                    format!("⎇ Branch {} of {}", branch, branches),
+                    self.theme.key_hint,
+                )));
+                lines.push(Line::from(""));
+            }
+            DisplayEntry::Metadata { metadata, .. } => {
+                // A gutter line above the entries written with the new values
+                if self.show_metadata {
+                    for (i, line) in wrap_text(
+                        &metadata.to_string(),
+                        content_width.saturating_sub(2).max(1),
+                    )
+                    .into_iter()
+                    .enumerate()
+                    {
+                        let lead = if i == 0 { "» " } else { "  " };
+                        lines.push(Line::from(Span::styled(
+                            format!("{}{}", lead, line),
+                            self.theme.metadata,
+                        )));
+                    }
+                }
+            }
+            DisplayEntry::CompactBoundary { .. }
+            | DisplayEntry::ApiError { .. }
+            | DisplayEntry::LocalCommand { .. }
+            | DisplayEntry::SystemNotice { .. } => {
+                self.render_system_entry(lines, entry, content_width);
+            }
+        }
+    }
+
+    /// Appends the lines for a system entry. These are short, so their line count is
+    /// taken from rendering them rather than calculated separately.
+    fn render_system_entry(
+        &self,
+        lines: &mut Vec<Line<'a>>,
+        entry: &DisplayEntry,
+        content_width: usize,
+    ) {
+        let (text, style, prefix) = match entry {
+            DisplayEntry::CompactBoundary {
+                trigger,
+                pre_tokens,
+                ..
+            } => {
+                let details: Vec<String> = trigger
+                    .iter()
+                    .cloned()
+                    .chain(pre_tokens.map(|t| format!("{} tokens", format_token_count(t))))
+                    .collect();
+                let mut text = "── Conversation compacted".to_string();
+                if !details.is_empty() {
+                    text.push_str(&format!(" ({})", details.join(", ")));
+                }
+                text.push_str(" ──");
+                (text, self.theme.compact_boundary, "")
+            }
+            DisplayEntry::ApiError {
+                message,
+                retry_attempt,
+                max_retries,
+                retry_in_ms,
+                ..
+            } => {
+                let mut text = format!("API error: {}", message);
+                if let Some(attempt) = retry_attempt {
+                    text.push_str(&format!(" · retry {}", attempt));
+                    if let Some(max) = max_retries {
+                        text.push_str(&format!("/{}", max));
+                    }
+                    if let Some(ms) = retry_in_ms {
+                        text.push_str(&format!(" in {:.1}s", *ms as f64 / 1000.0));
+                    }
+                }
+                (text, self.theme.api_error, "")
+            }
+            DisplayEntry::LocalCommand { text, .. } => {
+                // Command output can be long (e.g. `/context`); show only the first line when collapsed
+                let text = if self.expand_tools {
+                    text.clone()
+                } else {
+                    text.lines().next().unwrap_or("").to_string()
+                };
+                (text, self.theme.local_command, "› ")
+            }
+            DisplayEntry::SystemNotice { level, text, .. } => {
+                let style = if level.as_deref() == Some("error") {
+                    self.theme.api_error
+                } else {
+                    self.theme.system_notice
+                };
+                (format!("System: {}", text), style, "")
+            }
+            _ => return,
+        };
+
+        let indent = prefix.width();
+        for (i, line) in wrap_text(&text, content_width.saturating_sub(indent).max(1))
+            .into_iter()
+            .enumerate()
+        {
+            let lead = if i == 0 {
+                prefix.to_string()
+            } else {
+                " ".repeat(indent)
+            };
+            lines.push(Line::from(Span::styled(format!("{}{}", lead, line), style)));
+        }
+        lines.push(Line::from(""));
     }
 
     /// Renders entries visible in the viewport plus a small buffer.
@@ -915,28 +1427,20 @@ impl<'a> ConversationView<'a> {
     ///
     /// # Arguments
     /// * `width` - Total content width for text wrapping
+    /// * `entry_info` - Start line and line count of each entry, from `calculate_entry_info`
     /// * `viewport_start` - First line to include (scroll offset)
     /// * `viewport_height` - Number of lines in the visible viewport
+    /// * `toggled` - Entries whose expansion is flipped from the global settings
     fn render_entries(
         &self,
         width: usize,
+        entry_info: &[(usize, usize)],
         viewport_start: usize,
         viewport_height: usize,
+        toggled: &HashSet<usize>,
     ) -> (Vec<Line<'a>>, usize) {
         let content_width = width.saturating_sub(4); // Account for borders and padding
 
-        // Single pass: calculate both start positions AND line counts to avoid redundant work
-        let entry_info: Vec<(usize, usize)> = {
-            let mut info = Vec::with_capacity(self.entries.len());
-            let mut current_line = 0;
-            for entry in self.entries.iter() {
-                let line_count = self.calculate_entry_lines(entry, content_width);
-                info.push((current_line, line_count));
-                current_line += line_count;
-            }
-            info
-        };
-
         // Determine which entries intersect with viewport (with buffer)
         let viewport_end = viewport_start + viewport_height;
         let buffer = viewport_height / 4; // Buffer proportional to viewport height
@@ -966,151 +1470,32 @@ impl<'a> ConversationView<'a> {
             0
         };
 
-        for entry in &self.entries.iter().collect::<Vec<_>>()[first_entry..last_entry] {
-            match entry {
-                DisplayEntry::UserMessage { text, .. } => {
-                    lines.push(Line::from(vec![
-                        Span::styled("User", self.theme.user_label),
-                        Span::raw(": "),
-                    ]));
-                    for line in wrap_text(text, content_width) {
-                        lines.push(Line::from(Span::styled(
-                            format!("  {}", line),
-                            self.theme.user_message,
-                        )));
-                    }
-                    lines.push(Line::from(""));
-                }
-                DisplayEntry::AssistantText { text, .. } => {
-                    lines.push(Line::from(vec![
-                        Span::styled("Assistant", self.theme.assistant_label),
-                        Span::raw(": "),
-                    ]));
-                    for line in wrap_text(text, content_width) {
-                        lines.push(Line::from(Span::styled(
-                            format!("  {}", line),
-                            self.theme.assistant_text,
-                        )));
-                    }
-                    lines.push(Line::from(""));
-                }
-                DisplayEntry::ToolCall {
-                    name,
-                    input,
-                    result,
-                    ..
-                } => {
-                    self.render_tool_call(&mut lines, name, input, result.as_ref(), content_width);
-                    lines.push(Line::from(""));
-                }
-                DisplayEntry::ToolResult {
-                    content, is_error, ..
-                } => {
-                    let (label, style) = if *is_error {
-                        ("Error", self.theme.tool_error)
-                    } else {
-                        ("Result", self.theme.tool_result)
-                    };
-                    lines.push(Line::from(Span::styled(format!("[{}]", label), style)));
-                    if self.expand_tools && !content.is_empty() {
-                        // Truncate very long results (respecting char boundaries)
-                        let display_content = if content.len() > 500 {
-                            let truncate_at = content
-                                .char_indices()
-                                .take_while(|(i, _)| *i < 500)
-                                .last()
-                                .map(|(i, c)| i + c.len_utf8())
-                                .unwrap_or(0);
-                            format!("{}...", &content[..truncate_at])
-                        } else {
-                            content.clone()
-                        };
-                        for line in wrap_text(&display_content, content_width) {
-                            lines.push(Line::from(Span::styled(format!("  {}", line), style)));
-                        }
-                    }
-                    lines.push(Line::from(""));
-                }
-                DisplayEntry::Thinking { text, .. } => {
-                    if self.show_thinking {
-                        lines.push(Line::from(Span::styled(
-                            "Thinking:",
-                            self.theme.thinking_collapsed,
-                        )));
-                        for line in wrap_text(text, content_width) {
-                            lines.push(Line::from(Span::styled(
-                                format!("  {}", line),
-                                self.theme.thinking,
-                            )));
-                        }
-                        lines.push(Line::from(""));
-                    } else {
-                        lines.push(Line::from(Span::styled(
-                            "[Thinking collapsed - press 't' to show]",
-                            self.theme.thinking_collapsed,
-                        )));
-                    }
-                }
-                DisplayEntry::HookEvent {
-                    event,
-                    hook_name,
-                    command,
-                    ..
-                } => {
-                    // Extract tool name from hook_name if present (e.g., "PostToolUse:Read" -> "Read")
-                    let tool_info = hook_name
-                        .as_ref()
-                        .and_then(|name| name.split(':').nth(1).map(|s| s.to_string()));
-
-                    // Build the header line
-                    let header = if let Some(tool) = &tool_info {
-                        format!("Hook: {} ({})", event, tool)
-                    } else {
-                        format!("Hook: {}", event)
-                    };
+        let pattern = self.search.pattern();
+        let current_match = self.search.current_entry();
 
-                    lines.push(Line::from(Span::styled(header, self.theme.hook_event)));
+        for (offset, entry) in self.entries.iter().skip(first_entry).enumerate() {
+            let entry_idx = first_entry + offset;
+            if entry_idx >= last_entry {
+                break;
+            }
+            let entry_first_line = lines.len();
+            let waited = response_time(self.entries.range(..entry_idx), entry);
+            self.render_response_time(&mut lines, waited);
+            self.for_entry(toggled.contains(&entry_idx)).render_entry(
+                &mut lines,
+                entry,
+                content_width,
+            );
 
-                    // Show command if expanded and it's a real command (not just "callback")
-                    if self.expand_tools
-                        && let Some(cmd) = command
-                        && cmd != "callback"
-                    {
-                        // Abbreviate long commands (respecting char boundaries)
-                        let display_cmd = if cmd.len() > 60 {
-                            let truncate_at = cmd
-                                .char_indices()
-                                .take_while(|(i, _)| *i < 57)
-                                .last()
-                                .map(|(i, c)| i + c.len_utf8())
-                                .unwrap_or(0);
-                            format!("{}...", &cmd[..truncate_at])
-                        } else {
-                            cmd.clone()
-                        };
-                        lines.push(Line::from(Span::styled(
-                            format!("  → {}", display_cmd),
-                            self.theme.hook_event,
-                        )));
-                    }
-                    lines.push(Line::from(""));
-                }
-                DisplayEntry::AgentSpawn {
-                    agent_type,
-                    description,
-                    ..
-                } => {
-                    lines.push(Line::from(vec![
-                        Span::styled("Agent: ", self.theme.agent_spawn),
-                        Span::styled(agent_type.clone(), self.theme.agent_spawn),
-                    ]));
-                    if !description.is_empty() {
-                        lines.push(Line::from(Span::styled(
-                            format!("  {}", description),
-                            self.theme.agent_spawn,
-                        )));
-                    }
-                    lines.push(Line::from(""));
+            // Highlight search matches within this entry's lines
+            if !pattern.is_empty() && self.search.matches.binary_search(&entry_idx).is_ok() {
+                let style = if current_match == Some(entry_idx) {
+                    self.theme.search_current
+                } else {
+                    self.theme.search_match
+                };
+                for line in &mut lines[entry_first_line..] {
+                    *line = highlight_line(std::mem::take(line), pattern, style);
                 }
             }
         }
@@ -1129,10 +1514,21 @@ impl<'a> StatefulWidget for ConversationView<'a> {
             (self.theme.border, self.theme.title)
         };
 
-        let block = Block::default()
+        let mut block = Block::default()
             .title(Span::styled(" Conversation ", title_style))
             .borders(Borders::ALL)
             .border_style(border_style);
+        // Where the conversation is now, from the most recent metadata
+        if self.show_metadata
+            && let Some(DisplayEntry::Metadata { metadata, .. }) = self
+                .entries
+                .iter()
+                .rev()
+                .find(|entry| matches!(entry, DisplayEntry::Metadata { .. }))
+        {
+            block =
+                block.title_bottom(Span::styled(format!(" {} ", metadata), self.theme.metadata));
+        }
 
         let inner = block.inner(area);
         block.render(area, buf);
@@ -1156,14 +1552,25 @@ impl<'a> StatefulWidget for ConversationView<'a> {
             return;
         }
 
-        // Calculate total lines first for follow mode and scrolling
-        let total_lines = self.calculate_total_lines(padded.width as usize);
+        // Calculate entry positions first for follow mode, jumps and scrolling
+        let entry_info = self.calculate_entry_info(padded.width as usize, state);
+        let total_lines = entry_info
+            .last()
+            .map(|(start, count)| start + count)
+            .unwrap_or(0);
 
         // Update state with total lines for scrollbar
         state.total_lines = total_lines;
+        state.entry_offsets = entry_info.iter().map(|(start, _)| *start).collect();
 
-        // Auto-scroll to bottom if follow mode is enabled
-        if state.follow_mode && total_lines > inner.height as usize {
+        if let Some(entry_idx) = state.pending_jump.take()
+            && let Some((start, _)) = entry_info.get(entry_idx)
+        {
+            // Jump requested by search: put the entry at the top of the viewport
+            state.follow_mode = false;
+            state.scroll_offset = *start;
+        } else if state.follow_mode && total_lines > inner.height as usize {
+            // Auto-scroll to bottom if follow mode is enabled
             state.scroll_offset = total_lines.saturating_sub(inner.height as usize);
         }
 
@@ -1174,8 +1581,10 @@ impl<'a> StatefulWidget for ConversationView<'a> {
         // Render entries in viewport range (with small buffer)
         let (lines, render_offset) = self.render_entries(
             padded.width as usize,
+            &entry_info,
             state.scroll_offset,
             inner.height as usize,
+            &state.toggled_entries,
         );
 
         // Calculate how many lines to skip from the rendered content
@@ -1192,6 +1601,21 @@ impl<'a> StatefulWidget for ConversationView<'a> {
         let paragraph = Paragraph::new(Text::from(visible_lines));
         paragraph.render(padded, buf);
 
+        // Mark the lines of the entry under the cursor in the left padding column
+        if let Some(&(start, count)) = state.selected_entry.and_then(|idx| entry_info.get(idx)) {
+            let style = if self.focused {
+                self.theme.border_focused
+            } else {
+                self.theme.border
+            };
+            let first = start.max(state.scroll_offset);
+            let last = (start + count).min(state.scroll_offset + inner.height as usize);
+            for line in first..last {
+                let y = inner.y + (line - state.scroll_offset) as u16;
+                buf.set_string(inner.x, y, "▌", style);
+            }
+        }
+
         // Render scrollbar
         if total_lines > inner.height as usize {
             let scrollbar = Scrollbar::default()
@@ -1216,6 +1640,122 @@ impl<'a> StatefulWidget for ConversationView<'a> {
     }
 }
 
+/// True for entries that can be expanded or collapsed one at a time
+pub fn is_expandable(entry: &DisplayEntry) -> bool {
+    matches!(
+        entry,
+        DisplayEntry::ToolCall { .. }
+            | DisplayEntry::ToolResult { .. }
+            | DisplayEntry::Thinking { .. }
+            | DisplayEntry::HookEvent { .. }
+    )
+}
+
+/// Formats token usage as "3 in · 1.2k out · 45.1k cache read · 2.0k cache write",
+/// omitting empty cache buckets
+fn format_usage(usage: &TokenUsage) -> String {
+    let mut parts = vec![
+        format!("{} in", format_token_count(usage.input_tokens)),
+        format!("{} out", format_token_count(usage.output_tokens)),
+    ];
+    if usage.cache_read_input_tokens > 0 {
+        parts.push(format!(
+            "{} cache read",
+            format_token_count(usage.cache_read_input_tokens)
+        ));
+    }
+    if usage.cache_creation_input_tokens > 0 {
+        parts.push(format!(
+            "{} cache write",
+            format_token_count(usage.cache_creation_input_tokens)
+        ));
+    }
+    parts.join(" · ")
+}
+
+/// Diff hunks for every replacement made by an `Edit` or `MultiEdit` call, and the file path
+fn edit_hunks(name: &str, parsed: Option<&serde_json::Value>) -> (String, Vec<DiffHunk>) {
+    match parsed.and_then(|v| tool_edits(name, v)) {
+        Some((path, edits)) => (
+            path,
+            edits
+                .iter()
+                .flat_map(|edit| diff_hunks(&edit.old, &edit.new))
+                .collect(),
+        ),
+        None => ("<unknown>".to_string(), Vec::new()),
+    }
+}
+
+/// Number of lines a diff takes: each hunk's header plus its lines
+fn diff_body_len(hunks: &[DiffHunk]) -> usize {
+    hunks.iter().map(|hunk| 1 + hunk.lines.len()).sum()
+}
+
+/// Splits a Read output line into its line number prefix ("    12→") and the code after it.
+/// Lines without a prefix (e.g. wrapped continuations) are returned whole.
+fn split_line_number(line: &str) -> (&str, &str) {
+    let digits_end = line.len() - line.trim_start().len();
+    let rest = &line[digits_end..];
+    let number_len = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if number_len == 0 {
+        return ("", line);
+    }
+    let after = &rest[number_len..];
+    match after.chars().next() {
+        Some(sep @ ('→' | '\t')) => line.split_at(digits_end + number_len + sep.len_utf8()),
+        _ => ("", line),
+    }
+}
+
+/// Syntax for highlighting a tool result: the file type of a successful `Read`
+fn result_syntax(
+    name: &str,
+    parsed: Option<&serde_json::Value>,
+    result: &ToolCallResult,
+) -> Option<&'static SyntaxReference> {
+    parsed
+        .filter(|_| name == "Read" && !result.is_error)
+        .and_then(|v| v.get("file_path"))
+        .and_then(|v| v.as_str())
+        .and_then(syntax_for_path)
+}
+
+/// Truncates styled spans to fit within a given width, adding ellipsis if needed
+pub(super) fn truncate_spans<'a>(
+    spans: Vec<Span<'a>>,
+    max_width: usize,
+    ellipsis_style: Style,
+) -> Vec<Span<'a>> {
+    let total: usize = spans.iter().map(|span| span.content.width()).sum();
+    if total <= max_width {
+        return spans;
+    }
+
+    let mut remaining = max_width.saturating_sub(1);
+    let mut truncated = Vec::new();
+    for span in spans {
+        let mut kept = String::new();
+        for c in span.content.chars() {
+            let width = c.to_string().width();
+            if width > remaining {
+                break;
+            }
+            remaining -= width;
+            kept.push(c);
+        }
+        let cut = kept.len() < span.content.len();
+        if !kept.is_empty() {
+            truncated.push(Span::styled(kept, span.style));
+        }
+        if cut {
+            break;
+        }
+    }
+    truncated.push(Span::styled("…", ellipsis_style));
+    truncated
+}
+
 /// Truncates a line to fit within a given width, adding ellipsis if needed
 fn truncate_line(line: &str, max_width: usize) -> String {
     if line.len() <= max_width {
@@ -1296,10 +1836,41 @@ fn wrap_text(text: &str, width: usize) -> Vec<String> {
     lines
 }
 
+/// The view settings that change how many lines an entry takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct LayoutKey {
+    show_thinking: bool,
+    expand_tools: bool,
+    full_diffs: bool,
+    show_metadata: bool,
+}
+
+/// What is known about an entry's layout from earlier renders
+#[derive(Debug, Default)]
+struct EntryLayout {
+    /// Lines the entry takes at `ConversationState::layout_width`, per view settings
+    line_counts: HashMap<LayoutKey, usize>,
+    /// Diff lines of an `Edit`/`MultiEdit` call, the same at every width
+    diff_len: Option<usize>,
+}
+
 pub struct ConversationState {
     pub scroll_offset: usize,
     pub total_lines: usize,
     pub follow_mode: bool,
+    /// Entry to scroll to on the next render, once line positions are known
+    pub pending_jump: Option<usize>,
+    /// Start line of each entry as of the last render
+    pub entry_offsets: Vec<usize>,
+    /// Entries expanded or collapsed individually, against the global thinking and tool
+    /// settings
+    pub toggled_entries: HashSet<usize>,
+    /// Entry under the cursor, once the user has moved it
+    pub selected_entry: Option<usize>,
+    /// Cached layout of each entry, in conversation order
+    entry_layouts: Vec<EntryLayout>,
+    /// Content width the cached line counts were taken at
+    layout_width: usize,
 }
 
 impl ConversationState {
@@ -1308,9 +1879,122 @@ impl ConversationState {
             scroll_offset: 0,
             total_lines: 0,
             follow_mode: true, // Start with follow mode enabled
+            pending_jump: None,
+            entry_offsets: Vec::new(),
+            toggled_entries: HashSet::new(),
+            selected_entry: None,
+            entry_layouts: Vec::new(),
+            layout_width: 0,
+        }
+    }
+
+    /// Scroll so that the given entry is at the top of the viewport on the next render
+    pub fn jump_to_entry(&mut self, entry_idx: usize) {
+        self.follow_mode = false;
+        self.pending_jump = Some(entry_idx);
+    }
+
+    /// Index of the entry containing the given content line, as of the last render
+    pub fn entry_at_line(&self, line: usize) -> Option<usize> {
+        if self.entry_offsets.is_empty() {
+            return None;
+        }
+        Some(
+            self.entry_offsets
+                .partition_point(|&start| start <= line)
+                .saturating_sub(1),
+        )
+    }
+
+    /// Expand or collapse one entry, leaving the rest of the conversation alone
+    pub fn toggle_entry(&mut self, entry_idx: usize) {
+        if !self.toggled_entries.remove(&entry_idx) {
+            self.toggled_entries.insert(entry_idx);
+        }
+    }
+
+    /// Lines the entry took up in the last render; hidden entries take none
+    fn entry_height(&self, entry_idx: usize) -> usize {
+        let Some(&start) = self.entry_offsets.get(entry_idx) else {
+            return 0;
+        };
+        let end = self
+            .entry_offsets
+            .get(entry_idx + 1)
+            .copied()
+            .unwrap_or(self.total_lines);
+        end - start
+    }
+
+    /// Move the cursor to an entry, scrolling just enough to bring it into view
+    pub fn select_entry(&mut self, entry_idx: usize, viewport_height: usize) {
+        self.selected_entry = Some(entry_idx);
+        let Some(&start) = self.entry_offsets.get(entry_idx) else {
+            return;
+        };
+        let end = start + self.entry_height(entry_idx);
+        if start < self.scroll_offset {
+            self.follow_mode = false;
+            self.scroll_offset = start;
+        } else if end > self.scroll_offset + viewport_height {
+            // Show as much of a tall entry as fits, starting from its top
+            self.follow_mode = false;
+            self.scroll_offset = start.min(end.saturating_sub(viewport_height));
+        }
+    }
+
+    /// Move the cursor to the next visible entry; starts at the top of the viewport
+    pub fn select_next_entry(&mut self, viewport_height: usize) {
+        let from = match self.selected_entry {
+            Some(idx) => idx + 1,
+            None => self.top_entry(),
+        };
+        if let Some(idx) = (from..self.entry_offsets.len()).find(|&i| self.entry_height(i) > 0) {
+            self.select_entry(idx, viewport_height);
+        }
+    }
+
+    /// Move the cursor to the previous visible entry; starts at the top of the viewport
+    pub fn select_previous_entry(&mut self, viewport_height: usize) {
+        let before = match self.selected_entry {
+            Some(idx) => idx,
+            None => self.top_entry() + 1,
+        };
+        if let Some(idx) = (0..before.min(self.entry_offsets.len()))
+            .rev()
+            .find(|&i| self.entry_height(i) > 0)
+        {
+            self.select_entry(idx, viewport_height);
         }
     }
 
+    /// Account for `count` entries dropped from the front of the conversation, so the cursor
+    /// and individually expanded entries stay on the same entries
+    pub fn entries_dropped(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        self.toggled_entries = self
+            .toggled_entries
+            .iter()
+            .filter_map(|idx| idx.checked_sub(count))
+            .collect();
+        self.selected_entry = self.selected_entry.and_then(|idx| idx.checked_sub(count));
+        self.entry_layouts
+            .drain(..count.min(self.entry_layouts.len()));
+    }
+
+    /// Forget the cached layout of the entries from `entry_idx` on, after they were changed in
+    /// place (e.g. a tool result merged into its call)
+    pub fn entries_changed(&mut self, entry_idx: usize) {
+        self.entry_layouts.truncate(entry_idx);
+    }
+
+    /// Index of the entry at the top of the viewport
+    pub fn top_entry(&self) -> usize {
+        self.entry_at_line(self.scroll_offset).unwrap_or(0)
+    }
+
     pub fn scroll_down(&mut self, amount: usize, viewport_height: usize) {
         self.follow_mode = false;
         let max_scroll = self.total_lines.saturating_sub(viewport_height);
@@ -1342,3 +2026,197 @@ impl Default for ConversationState {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_diff_line_count_matches_render() {
+        let old: String = (0..20).map(|i| format!("line {}\n", i)).collect();
+        let new = old
+            .replace("line 3\n", "line three\n")
+            .replace("line 15\n", "");
+        let entry = DisplayEntry::tool_call(
+            "MultiEdit",
+            serde_json::json!({"file_path": "/a.rs", "edits": [
+                {"old_string": old, "new_string": new},
+                {"old_string": "x", "new_string": "y"}
+            ]}),
+        );
+        let entries = VecDeque::new();
+        let theme = Theme::default();
+        let search = SearchState::new();
+        let keys = Keymap::default();
+        for full_diffs in [false, true] {
+            let view = ConversationView::new(
+                &entries, false, &theme, false, true, full_diffs, false, false, &search, &keys,
+            );
+            assert_eq!(
+                view.entry_lines(&entry, None, 80).len(),
+                view.calculate_entry_lines(&entry, 76, &mut None)
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_result_wraps_after_highlighting() {
+        let content = format!(
+            "     1→fn main() {{}}\n     2→let s = \"{}\";",
+            "x".repeat(60)
+        );
+        let entry = DisplayEntry::tool_call("Read", serde_json::json!({"file_path": "/a.rs"}))
+            .with_result(&content, false, None);
+        let entries = VecDeque::new();
+        let theme = Theme::default();
+        let search = SearchState::new();
+        let keys = Keymap::default();
+        let view = ConversationView::new(
+            &entries, false, &theme, false, true, false, false, false, &search, &keys,
+        );
+        let lines = view.entry_lines(&entry, None, 40);
+        assert_eq!(
+            lines.len(),
+            view.calculate_entry_lines(&entry, 36, &mut None)
+        );
+
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        let second = text.iter().position(|line| line.contains("2→")).unwrap();
+        // The wrapped string continues on the next line, as highlighted as the rest of it
+        assert!(text[second + 1].trim_start().starts_with("xxx"));
+        assert_eq!(
+            lines[second].spans.last().unwrap().style,
+            lines[second + 1].spans.last().unwrap().style
+        );
+    }
+
+    #[test]
+    fn test_markdown_line_count_matches_render() {
+        let entry = DisplayEntry::AssistantText {
+            text: "## Plan\n\n1. Read `main.rs`\n2. Fix it\n\n```rust\nfn main() {}\n```\n\n| a | b |\n|---|---|\n| 1 | 2 |".to_string(),
+            timestamp: None,
+        };
+        let entries = VecDeque::new();
+        let theme = Theme::default();
+        let search = SearchState::new();
+        let keys = Keymap::default();
+        let view = ConversationView::new(
+            &entries, false, &theme, false, true, false, false, false, &search, &keys,
+        );
+        for width in [12, 40, 80] {
+            assert_eq!(
+                view.entry_lines(&entry, None, width).len(),
+                view.calculate_entry_lines(&entry, width - 4, &mut None)
+            );
+        }
+    }
+
+    #[test]
+    fn test_line_counts_follow_width_and_changes() {
+        let text = |text: &str| DisplayEntry::AssistantText {
+            text: text.to_string(),
+            timestamp: None,
+        };
+        let mut entries = VecDeque::from([text("short"), text(&"word ".repeat(20))]);
+        let theme = Theme::default();
+        let search = SearchState::new();
+        let keys = Keymap::default();
+        let mut state = ConversationState::new();
+        let counts = |entries: &VecDeque<DisplayEntry>, width, state: &mut ConversationState| {
+            ConversationView::new(
+                entries, false, &theme, false, true, false, false, false, &search, &keys,
+            )
+            .calculate_entry_info(width, state)
+            .iter()
+            .map(|&(_, count)| count)
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(counts(&entries, 104, &mut state), [3, 3]);
+        assert_eq!(counts(&entries, 54, &mut state), [3, 4]);
+
+        entries[0] = text("one\n\ntwo");
+        assert_eq!(counts(&entries, 54, &mut state), [3, 4]);
+        state.entries_changed(0);
+        assert_eq!(counts(&entries, 54, &mut state), [5, 4]);
+    }
+
+    #[test]
+    fn test_split_line_number() {
+        assert_eq!(
+            split_line_number("    12→fn main() {"),
+            ("    12→", "fn main() {")
+        );
+        assert_eq!(split_line_number("  3\tx"), ("  3\t", "x"));
+        assert_eq!(
+            split_line_number("  wrapped 12 words"),
+            ("", "  wrapped 12 words")
+        );
+    }
+
+    #[test]
+    fn test_truncate_spans() {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let spans = vec![Span::raw("let x = "), Span::styled("22;", bold)];
+        assert_eq!(truncate_spans(spans.clone(), 11, Style::default()), spans);
+        assert_eq!(
+            truncate_spans(spans, 10, Style::default()),
+            vec![
+                Span::raw("let x = "),
+                Span::styled("2", bold),
+                Span::raw("…")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entry_cursor_skips_hidden_entries() {
+        let mut state = ConversationState::new();
+        // Entry 1 is hidden (e.g. collapsed usage); entry 3 is taller than the viewport
+        state.entry_offsets = vec![0, 3, 3, 5];
+        state.total_lines = 20;
+
+        state.select_next_entry(10);
+        assert_eq!(state.selected_entry, Some(0));
+        state.select_next_entry(10);
+        assert_eq!(state.selected_entry, Some(2));
+        state.select_next_entry(10);
+        assert_eq!(state.selected_entry, Some(3));
+        assert_eq!(state.scroll_offset, 5);
+        state.select_previous_entry(10);
+        assert_eq!(state.selected_entry, Some(2));
+        assert_eq!(state.scroll_offset, 3);
+
+        state.toggle_entry(2);
+        state.entries_dropped(2);
+        assert_eq!(state.selected_entry, Some(0));
+        assert!(state.toggled_entries.contains(&0));
+    }
+
+    #[test]
+    fn test_thinking_hint_follows_keymap() {
+        let entry = DisplayEntry::Thinking {
+            text: "hmm".to_string(),
+            timestamp: None,
+        };
+        let entries = VecDeque::new();
+        let theme = Theme::default();
+        let search = SearchState::new();
+        let hint = |keys: &str| {
+            let keys: Keymap = toml::from_str(keys).unwrap();
+            let view = ConversationView::new(
+                &entries, false, &theme, false, true, false, false, false, &search, &keys,
+            );
+            view.entry_lines(&entry, None, 80)[0].to_string()
+        };
+
+        assert_eq!(
+            hint("[global]\ntoggle_thinking = \"T\"\n"),
+            "[Thinking collapsed - press 'T' to show]"
+        );
+        assert_eq!(
+            hint("[global]\ntoggle_thinking = []\n"),
+            "[Thinking collapsed]"
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, StatefulWidget, Widget},
+};
+
+use super::styles::Theme;
+use crate::logs::{FileIndex, FileOperation, TouchedFile};
+
+/// State of the files-touched overlay
+pub struct FileIndexState {
+    /// Whether the overlay is shown
+    pub open: bool,
+    /// Session the index is for (or is being built for)
+    pub session_log_path: Option<PathBuf>,
+    pub index: FileIndex,
+    pub list_state: ListState,
+    /// True while the session's logs are being indexed in the background
+    pub is_loading: bool,
+}
+
+impl FileIndexState {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            session_log_path: None,
+            index: FileIndex::default(),
+            list_state: ListState::default(),
+            is_loading: false,
+        }
+    }
+
+    /// Show the overlay while the index for `session_log_path` is built
+    pub fn show_loading(&mut self, session_log_path: PathBuf) {
+        self.session_log_path = Some(session_log_path);
+        self.index = FileIndex::default();
+        self.list_state = ListState::default();
+        self.is_loading = true;
+        self.open = true;
+    }
+
+    pub fn set_index(&mut self, index: FileIndex) {
+        self.list_state
+            .select(if index.is_empty() { None } else { Some(0) });
+        self.index = index;
+        self.is_loading = false;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn selected_file(&self) -> Option<&TouchedFile> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.index.files.get(i))
+    }
+
+    pub fn next(&mut self) {
+        if self.index.is_empty() {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(i) if i + 1 < self.index.files.len() => i + 1,
+            Some(_) => 0,
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        if self.index.is_empty() {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(0) | None => self.index.files.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn first(&mut self) {
+        if !self.index.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    pub fn last(&mut self) {
+        if !self.index.is_empty() {
+            self.list_state.select(Some(self.index.files.len() - 1));
+        }
+    }
+}
+
+impl Default for FileIndexState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Centered overlay listing every file the session read, wrote or edited, with counts
+pub struct FileIndexView<'a> {
+    theme: &'a Theme,
+}
+
+impl<'a> FileIndexView<'a> {
+    pub fn new(theme: &'a Theme) -> Self {
+        Self { theme }
+    }
+
+    /// "3 read", dimmed when zero so the columns stay aligned
+    fn count_span(&self, file: &TouchedFile, operation: FileOperation, label: &str) -> Span<'a> {
+        let count = file.count(operation);
+        let style = if count == 0 {
+            self.theme.timestamp
+        } else {
+            self.theme.tool_name
+        };
+        Span::styled(format!("{:>3} {:<6}", count, label), style)
+    }
+}
+
+impl<'a> StatefulWidget for FileIndexView<'a> {
+    type State = FileIndexState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let width = area.width.saturating_sub(8).clamp(20, 120).min(area.width);
+        let height = (state.index.files.len() as u16 + 2)
+            .max(5)
+            .min(area.height.saturating_sub(4))
+            .min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        Clear.render(popup, buf);
+
+        let title = format!(" Files touched ({}) ", state.index.files.len());
+        let block = Block::default()
+            .title(Span::styled(title, self.theme.title_focused))
+            .borders(Borders::ALL)
+            .border_style(self.theme.border_focused);
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+
+        if state.is_loading || state.index.is_empty() {
+            let message = if state.is_loading {
+                "Indexing session..."
+            } else {
+                "No files were read, written or edited in this session"
+            };
+            Line::from(Span::styled(message, self.theme.timestamp)).render(inner, buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = state
+            .index
+            .files
+            .iter()
+            .map(|file| {
+                let mut spans = vec![
+                    self.count_span(file, FileOperation::Read, "read"),
+                    self.count_span(file, FileOperation::Write, "write"),
+                    self.count_span(file, FileOperation::Edit, "edit"),
+                    Span::raw(" "),
+                    Span::styled(file.path.clone(), self.theme.tool_input),
+                ];
+                let agents = file.agents();
+                if agents > 1 {
+                    spans.push(Span::styled(
+                        format!("  ({} agents)", agents),
+                        self.theme.timestamp,
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        StatefulWidget::render(list, inner, buf, &mut state.list_state);
+    }
+}
@@ -0,0 +1,239 @@
+use chrono::Local;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+    },
+};
+
+use super::search::highlight_line;
+use super::styles::Theme;
+use crate::logs::SearchHit;
+
+/// Hits collected before a search stops, from the most recently modified logs
+pub const GLOBAL_SEARCH_LIMIT: usize = 500;
+
+/// State of the cross-project search overlay
+pub struct GlobalSearchState {
+    /// Whether the overlay is shown
+    pub open: bool,
+    /// Whether keys edit the query (true) or navigate the results (false)
+    pub editing: bool,
+    /// Query being typed
+    pub input: String,
+    /// Query the current results (or the running search) are for
+    pub query: String,
+    pub results: Vec<SearchHit>,
+    pub list_state: ListState,
+    /// True while a background search is running
+    pub is_searching: bool,
+}
+
+impl GlobalSearchState {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            editing: true,
+            input: String::new(),
+            query: String::new(),
+            results: Vec::new(),
+            list_state: ListState::default(),
+            is_searching: false,
+        }
+    }
+
+    /// Show the overlay with the query prompt focused
+    pub fn show(&mut self) {
+        self.open = true;
+        self.editing = true;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn selected_hit(&self) -> Option<&SearchHit> {
+        self.list_state.selected().and_then(|i| self.results.get(i))
+    }
+
+    pub fn next(&mut self) {
+        if self.results.is_empty() {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(i) if i + 1 < self.results.len() => i + 1,
+            Some(_) => 0,
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        if self.results.is_empty() {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(0) | None => self.results.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn first(&mut self) {
+        if !self.results.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    pub fn last(&mut self) {
+        if !self.results.is_empty() {
+            self.list_state.select(Some(self.results.len() - 1));
+        }
+    }
+
+    /// Replace the results, selecting the first hit
+    pub fn set_results(&mut self, results: Vec<SearchHit>) {
+        self.results = results;
+        self.list_state = ListState::default();
+        if !self.results.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+}
+
+impl Default for GlobalSearchState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Centered overlay with a query prompt and a list of search hits
+pub struct GlobalSearchView<'a> {
+    theme: &'a Theme,
+}
+
+impl<'a> GlobalSearchView<'a> {
+    pub fn new(theme: &'a Theme) -> Self {
+        Self { theme }
+    }
+}
+
+impl<'a> StatefulWidget for GlobalSearchView<'a> {
+    type State = GlobalSearchState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let width = area.width.saturating_sub(8).max(20).min(area.width);
+        let height = area.height.saturating_sub(4).max(8).min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        Clear.render(popup, buf);
+
+        let title = if state.is_searching {
+            " Search all sessions (searching...) ".to_string()
+        } else if state.query.is_empty() {
+            " Search all sessions ".to_string()
+        } else if state.results.len() >= GLOBAL_SEARCH_LIMIT {
+            format!(
+                " Search all sessions (newest {} results) ",
+                state.results.len()
+            )
+        } else {
+            format!(" Search all sessions ({} results) ", state.results.len())
+        };
+        let block = Block::default()
+            .title(Span::styled(title, self.theme.title_focused))
+            .borders(Borders::ALL)
+            .border_style(self.theme.border_focused);
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1), // Query prompt
+                Constraint::Length(1), // Separator
+                Constraint::Min(1),    // Results
+            ])
+            .split(inner);
+
+        let prompt_style = if state.editing {
+            self.theme.title_focused
+        } else {
+            self.theme.title
+        };
+        let mut prompt = vec![
+            Span::styled(" / ", prompt_style),
+            Span::styled(state.input.clone(), self.theme.user_message),
+        ];
+        if state.editing {
+            prompt.push(Span::styled("_", self.theme.key_hint));
+        }
+        Paragraph::new(Line::from(prompt)).render(rows[0], buf);
+        Paragraph::new(Line::from(Span::styled(
+            "─".repeat(rows[1].width as usize),
+            self.theme.border,
+        )))
+        .render(rows[1], buf);
+
+        if state.results.is_empty() {
+            let message = if state.is_searching {
+                "Searching..."
+            } else if state.query.is_empty() {
+                "Type a query and press Enter"
+            } else {
+                "No matches"
+            };
+            Paragraph::new(Line::from(Span::styled(
+                format!(" {}", message),
+                self.theme.thinking_collapsed,
+            )))
+            .render(rows[2], buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = state
+            .results
+            .iter()
+            .map(|hit| {
+                let timestamp = hit
+                    .timestamp
+                    .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                let session = if hit.session_id.len() > 8 {
+                    &hit.session_id[..8]
+                } else {
+                    &hit.session_id
+                };
+                let header = Line::from(vec![
+                    Span::styled(format!("{} ", timestamp), self.theme.timestamp),
+                    Span::styled(hit.project_name.clone(), self.theme.tool_input),
+                    Span::styled(" > ", self.theme.border),
+                    Span::styled(session.to_string(), self.theme.assistant_text),
+                    Span::styled(" > ", self.theme.border),
+                    Span::styled(hit.agent_name.clone(), self.theme.tool_name),
+                ]);
+                let snippet = highlight_line(
+                    Line::from(Span::styled(
+                        format!("  {}", hit.snippet),
+                        self.theme.tool_result,
+                    )),
+                    &state.query,
+                    self.theme.search_match,
+                );
+                ListItem::new(vec![header, snippet])
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        StatefulWidget::render(list, rows[2], buf, &mut state.list_state);
+    }
+}
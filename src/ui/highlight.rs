@@ -0,0 +1,186 @@
+use std::sync::LazyLock;
+
+use ratatui::style::Style;
+use ratatui::text::Span;
+use syntect::easy::ScopeRegionIterator;
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxReference, SyntaxSet};
+
+use super::styles::Theme;
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+/// Token classes, following the Base16 styling guidelines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenClass {
+    Comment,
+    Keyword,
+    String,
+    Constant,
+    Type,
+    Function,
+    Variable,
+    Special,
+}
+
+/// Scope prefixes and the class they map to. The innermost scope that matches wins.
+static CLASSES: LazyLock<Vec<(Scope, TokenClass)>> = LazyLock::new(|| {
+    [
+        ("comment", TokenClass::Comment),
+        ("constant.character.escape", TokenClass::Special),
+        ("string.regexp", TokenClass::Special),
+        ("string", TokenClass::String),
+        ("constant", TokenClass::Constant),
+        ("keyword.operator", TokenClass::Special),
+        ("keyword", TokenClass::Keyword),
+        ("storage.type.function", TokenClass::Keyword),
+        ("storage.type", TokenClass::Type),
+        ("storage", TokenClass::Keyword),
+        ("entity.name.function", TokenClass::Function),
+        ("support.function", TokenClass::Function),
+        ("variable.function", TokenClass::Function),
+        ("entity.name.tag", TokenClass::Variable),
+        ("entity.name", TokenClass::Type),
+        ("support.type", TokenClass::Type),
+        ("support.class", TokenClass::Type),
+        ("entity.other.attribute-name", TokenClass::Function),
+        ("variable.parameter", TokenClass::Variable),
+        ("variable.language", TokenClass::Variable),
+        ("variable.other.readwrite", TokenClass::Variable),
+        ("support", TokenClass::Special),
+    ]
+    .into_iter()
+    .filter_map(|(scope, class)| Scope::new(scope).ok().map(|scope| (scope, class)))
+    .collect()
+});
+
+/// Finds a grammar for a file path (by extension, or by name for files like `Makefile`)
+pub fn syntax_for_path(path: &str) -> Option<&'static SyntaxReference> {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let extension = file_name.rsplit_once('.').map(|(_, ext)| ext);
+    extension
+        .and_then(|ext| SYNTAXES.find_syntax_by_extension(ext))
+        .or_else(|| SYNTAXES.find_syntax_by_extension(file_name))
+}
+
+/// Finds a grammar for a code fence info string such as `rust`, `py` or `shell`
+pub fn syntax_for_token(token: &str) -> Option<&'static SyntaxReference> {
+    SYNTAXES.find_syntax_by_token(token)
+}
+
+/// Grammar for shell commands run by the Bash tool
+pub fn shell_syntax() -> Option<&'static SyntaxReference> {
+    SYNTAXES.find_syntax_by_extension("sh")
+}
+
+/// Highlights consecutive lines of a file, carrying parser state from one line to the next
+/// so that multi-line strings and comments are colored correctly
+pub struct Highlighter<'t> {
+    parse_state: ParseState,
+    scopes: ScopeStack,
+    theme: &'t Theme,
+    default_style: Style,
+}
+
+impl<'t> Highlighter<'t> {
+    pub fn new(syntax: &SyntaxReference, theme: &'t Theme, default_style: Style) -> Self {
+        Self {
+            parse_state: ParseState::new(syntax),
+            scopes: ScopeStack::new(),
+            theme,
+            default_style,
+        }
+    }
+
+    /// Splits one line into styled spans. Falls back to the default style if parsing fails.
+    pub fn highlight_line(&mut self, line: &str) -> Vec<Span<'static>> {
+        // The bundled grammars expect each line to end with a newline
+        let with_newline = format!("{}\n", line);
+        let Ok(ops) = self.parse_state.parse_line(&with_newline, &SYNTAXES) else {
+            return vec![Span::styled(line.to_string(), self.default_style)];
+        };
+
+        let mut spans: Vec<Span<'static>> = Vec::new();
+        for (text, op) in ScopeRegionIterator::new(&ops, &with_newline) {
+            if self.scopes.apply(op).is_err() {
+                return vec![Span::styled(line.to_string(), self.default_style)];
+            }
+            let text = text.trim_end_matches('\n');
+            if text.is_empty() {
+                continue;
+            }
+            let style = self.style_for_scopes();
+            // Merge runs with the same style to keep the span count down
+            match spans.last_mut() {
+                Some(last) if last.style == style => last.content.to_mut().push_str(text),
+                _ => spans.push(Span::styled(text.to_string(), style)),
+            }
+        }
+        spans
+    }
+
+    fn style_for_scopes(&self) -> Style {
+        let class = self.scopes.as_slice().iter().rev().find_map(|scope| {
+            CLASSES
+                .iter()
+                .find(|(prefix, _)| prefix.is_prefix_of(*scope))
+                .map(|(_, class)| *class)
+        });
+        match class {
+            Some(TokenClass::Comment) => self.theme.syntax_comment,
+            Some(TokenClass::Keyword) => self.theme.syntax_keyword,
+            Some(TokenClass::String) => self.theme.syntax_string,
+            Some(TokenClass::Constant) => self.theme.syntax_constant,
+            Some(TokenClass::Type) => self.theme.syntax_type,
+            Some(TokenClass::Function) => self.theme.syntax_function,
+            Some(TokenClass::Variable) => self.theme.syntax_variable,
+            Some(TokenClass::Special) => self.theme.syntax_special,
+            None => self.default_style,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlights_rust_tokens() {
+        let theme = Theme::default();
+        let syntax = syntax_for_path("/src/main.rs").unwrap();
+        let mut highlighter = Highlighter::new(syntax, &theme, theme.tool_input);
+        let spans = highlighter.highlight_line("fn main() { let s = \"hi\"; } // done");
+
+        let style_of = |text: &str| {
+            spans
+                .iter()
+                .find(|span| span.content.contains(text))
+                .map(|span| span.style)
+        };
+        assert_eq!(style_of("fn"), Some(theme.syntax_keyword));
+        assert_eq!(style_of("main"), Some(theme.syntax_function));
+        assert_eq!(style_of("hi"), Some(theme.syntax_string));
+        assert_eq!(style_of("done"), Some(theme.syntax_comment));
+        let text: String = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(text, "fn main() { let s = \"hi\"; } // done");
+    }
+
+    #[test]
+    fn test_state_carries_across_lines() {
+        let theme = Theme::default();
+        let syntax = syntax_for_path("a.py").unwrap();
+        let mut highlighter = Highlighter::new(syntax, &theme, theme.tool_input);
+        highlighter.highlight_line("x = \"\"\"start");
+        let spans = highlighter.highlight_line("still in the string");
+        assert_eq!(spans[0].style, theme.syntax_string);
+    }
+
+    #[test]
+    fn test_syntax_lookup() {
+        assert_eq!(
+            syntax_for_path("Makefile").map(|s| s.name.as_str()),
+            Some("Makefile")
+        );
+        assert!(syntax_for_path("/tmp/notes.unknownext").is_none());
+        assert!(shell_syntax().is_some());
+    }
+}
@@ -1,11 +1,12 @@
-use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
 
-/// Minimum width for collapsed columns (just "P", "S", or "A" with borders)
-const COLLAPSED_WIDTH: u16 = 3;
+/// Default width for collapsed columns (just "P", "S", or "A" with borders)
+pub const COLLAPSED_WIDTH: u16 = 3;
 
 /// Padding for expanded columns (border + space on each side)
 const COLUMN_PADDING: u16 = 4;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AppLayout {
     pub header: Rect,
     pub projects: Rect,
@@ -28,6 +29,8 @@ pub struct LayoutConfig {
     pub max_project_width: u16,
     pub max_session_width: u16,
     pub max_agent_width: u16,
+    /// Width of the list columns that aren't focused
+    pub collapsed_width: u16,
 }
 
 impl AppLayout {
@@ -47,25 +50,26 @@ impl AppLayout {
         let status_bar = vertical[2];
 
         // Calculate column widths based on focus
+        let collapsed = config.collapsed_width;
         let (projects_width, sessions_width, agents_width) = match config.focused_pane {
             FocusedPane::Projects => {
                 // Projects expanded, others collapsed
                 let proj_width = (config.max_project_width + COLUMN_PADDING).min(main.width / 3);
-                (proj_width, COLLAPSED_WIDTH, COLLAPSED_WIDTH)
+                (proj_width, collapsed, collapsed)
             }
             FocusedPane::Sessions => {
                 // Sessions expanded, others collapsed
                 let sess_width = (config.max_session_width + COLUMN_PADDING).min(main.width / 2);
-                (COLLAPSED_WIDTH, sess_width, COLLAPSED_WIDTH)
+                (collapsed, sess_width, collapsed)
             }
             FocusedPane::Agents => {
                 // Agents expanded, others collapsed
                 let agent_width = (config.max_agent_width + COLUMN_PADDING).min(main.width / 3);
-                (COLLAPSED_WIDTH, COLLAPSED_WIDTH, agent_width)
+                (collapsed, collapsed, agent_width)
             }
             FocusedPane::Conversation => {
                 // All collapsed
-                (COLLAPSED_WIDTH, COLLAPSED_WIDTH, COLLAPSED_WIDTH)
+                (collapsed, collapsed, collapsed)
             }
         };
 
@@ -89,4 +93,50 @@ impl AppLayout {
             status_bar,
         }
     }
+
+    /// The pane under a screen position, if any
+    pub fn pane_at(&self, column: u16, row: u16) -> Option<FocusedPane> {
+        let position = Position::new(column, row);
+        [
+            (self.projects, FocusedPane::Projects),
+            (self.sessions, FocusedPane::Sessions),
+            (self.agents, FocusedPane::Agents),
+            (self.conversation, FocusedPane::Conversation),
+        ]
+        .into_iter()
+        .find(|(area, _)| area.contains(position))
+        .map(|(_, pane)| pane)
+    }
+}
+
+/// Row of a screen position within a bordered pane's content, or None on the border
+pub fn content_row(area: Rect, row: u16) -> Option<usize> {
+    (row > area.y && row + 1 < area.bottom()).then(|| (row - area.y - 1) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pane_at() {
+        let layout = AppLayout::new(
+            Rect::new(0, 0, 100, 30),
+            LayoutConfig {
+                focused_pane: FocusedPane::Projects,
+                max_project_width: 16,
+                max_session_width: 10,
+                max_agent_width: 10,
+                collapsed_width: COLLAPSED_WIDTH,
+            },
+        );
+        // Header, projects (20 wide), collapsed sessions and agents, then the conversation
+        assert_eq!(layout.pane_at(5, 0), None);
+        assert_eq!(layout.pane_at(5, 10), Some(FocusedPane::Projects));
+        assert_eq!(layout.pane_at(21, 10), Some(FocusedPane::Sessions));
+        assert_eq!(layout.pane_at(24, 10), Some(FocusedPane::Agents));
+        assert_eq!(layout.pane_at(60, 10), Some(FocusedPane::Conversation));
+        assert_eq!(content_row(layout.projects, 1), None);
+        assert_eq!(content_row(layout.projects, 3), Some(1));
+    }
 }
@@ -0,0 +1,652 @@
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::conversation::truncate_spans;
+use super::highlight::{Highlighter, syntax_for_token};
+use super::styles::Theme;
+
+/// Lays out Markdown as styled lines no wider than `width` columns.
+///
+/// With `highlight` false, code blocks are not syntax highlighted but the lines are otherwise
+/// identical, so line counts can be computed without paying for highlighting.
+pub fn render_markdown(
+    text: &str,
+    width: usize,
+    theme: &Theme,
+    base: Style,
+    highlight: bool,
+) -> Vec<Line<'static>> {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut renderer = Renderer {
+        width,
+        theme,
+        base,
+        highlight,
+        lines: Vec::new(),
+        inline: Vec::new(),
+        inline_styles: Vec::new(),
+        heading: None,
+        containers: Vec::new(),
+        code: None,
+        table: None,
+        blank_pending: false,
+    };
+    for event in Parser::new_ext(text, options) {
+        renderer.event(event);
+    }
+    renderer.flush_inline();
+    renderer.lines
+}
+
+/// Block containers that add a prefix to every line inside them
+enum Container {
+    Quote,
+    List { next_number: Option<u64> },
+    Item { marker: String, first_line: bool },
+}
+
+struct Table {
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<String>>,
+    header_rows: usize,
+}
+
+struct Renderer<'t> {
+    width: usize,
+    theme: &'t Theme,
+    base: Style,
+    highlight: bool,
+    lines: Vec<Line<'static>>,
+    /// Text of the paragraph, heading or list item being built
+    inline: Vec<Span<'static>>,
+    inline_styles: Vec<Style>,
+    heading: Option<HeadingLevel>,
+    containers: Vec<Container>,
+    /// Language and text of the code block being built
+    code: Option<(String, String)>,
+    table: Option<Table>,
+    /// A blank line goes before the next block
+    blank_pending: bool,
+}
+
+impl Renderer<'_> {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => {
+                if let Some((_, code)) = &mut self.code {
+                    code.push_str(&text);
+                } else {
+                    self.push_text(&text, self.inline_style());
+                }
+            }
+            Event::Code(text) => {
+                let style = self.inline_style().patch(self.theme.markdown_code);
+                self.push_text(&text, style);
+            }
+            Event::Html(text) | Event::InlineHtml(text) => {
+                self.push_text(&text, self.inline_style())
+            }
+            Event::InlineMath(text) | Event::DisplayMath(text) => {
+                self.push_text(&text, self.inline_style())
+            }
+            Event::FootnoteReference(label) => {
+                self.push_text(&format!("[^{}]", label), self.inline_style())
+            }
+            Event::SoftBreak => self.push_text(" ", self.inline_style()),
+            Event::HardBreak => self.push_text("\n", self.inline_style()),
+            Event::Rule => {
+                self.start_block();
+                let width = self.available_width();
+                self.emit_line(vec![Span::styled("─".repeat(width), self.theme.border)]);
+                self.blank_pending = true;
+            }
+            Event::TaskListMarker(done) => {
+                let marker = if done { "[x] " } else { "[ ] " };
+                self.push_text(marker, self.base);
+            }
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph | Tag::HtmlBlock => self.start_block(),
+            Tag::Heading { level, .. } => {
+                self.start_block();
+                self.heading = Some(level);
+            }
+            Tag::BlockQuote(_) => {
+                self.start_block();
+                self.containers.push(Container::Quote);
+            }
+            Tag::CodeBlock(kind) => {
+                self.start_block();
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().unwrap_or("").to_string()
+                    }
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((language, String::new()));
+            }
+            Tag::List(first_number) => {
+                // A nested list continues its parent item without a gap
+                if self.in_item() {
+                    self.flush_inline();
+                } else {
+                    self.start_block();
+                }
+                self.containers.push(Container::List {
+                    next_number: first_number,
+                });
+            }
+            Tag::Item => {
+                self.start_block();
+                let marker = match self.containers.last_mut() {
+                    Some(Container::List {
+                        next_number: Some(n),
+                    }) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.containers.push(Container::Item {
+                    marker,
+                    first_line: true,
+                });
+            }
+            Tag::Table(alignments) => {
+                self.start_block();
+                self.table = Some(Table {
+                    alignments,
+                    rows: Vec::new(),
+                    header_rows: 0,
+                });
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(table) = &mut self.table {
+                    table.rows.push(Vec::new());
+                }
+            }
+            Tag::TableCell => {
+                if let Some(row) = self.table.as_mut().and_then(|t| t.rows.last_mut()) {
+                    row.push(String::new());
+                }
+            }
+            Tag::Emphasis => self
+                .inline_styles
+                .push(Style::default().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self
+                .inline_styles
+                .push(Style::default().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self
+                .inline_styles
+                .push(Style::default().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { .. } | Tag::Image { .. } => {
+                self.inline_styles.push(self.theme.markdown_link)
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph | TagEnd::HtmlBlock => {
+                self.flush_inline();
+                self.blank_pending = true;
+            }
+            TagEnd::Heading(_) => {
+                self.flush_inline();
+                self.heading = None;
+                self.blank_pending = true;
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush_inline();
+                self.containers.pop();
+                self.blank_pending = true;
+            }
+            TagEnd::CodeBlock => {
+                if let Some((language, code)) = self.code.take() {
+                    self.render_code(&language, &code);
+                }
+                self.blank_pending = true;
+            }
+            TagEnd::List(_) => {
+                self.flush_inline();
+                self.containers.pop();
+                if !self.in_item() {
+                    self.blank_pending = true;
+                }
+            }
+            TagEnd::Item => {
+                self.flush_inline();
+                // An empty item still shows its marker
+                if let Some(Container::Item {
+                    first_line: true, ..
+                }) = self.containers.last()
+                {
+                    self.emit_line(Vec::new());
+                }
+                self.containers.pop();
+            }
+            TagEnd::TableHead => {
+                if let Some(table) = &mut self.table {
+                    table.header_rows = table.rows.len();
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.render_table(table);
+                }
+                self.blank_pending = true;
+            }
+            TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Link
+            | TagEnd::Image => {
+                self.inline_styles.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn in_item(&self) -> bool {
+        self.containers
+            .iter()
+            .any(|c| matches!(c, Container::Item { .. }))
+    }
+
+    /// Style for inline text: block style, then quote, heading and emphasis on top
+    fn inline_style(&self) -> Style {
+        let mut style = self.base;
+        if self
+            .containers
+            .iter()
+            .any(|c| matches!(c, Container::Quote))
+        {
+            style = style.patch(self.theme.markdown_quote);
+        }
+        if let Some(level) = self.heading {
+            style = style.patch(self.theme.markdown_heading);
+            if level == HeadingLevel::H1 {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+        }
+        self.inline_styles
+            .iter()
+            .fold(style, |style, patch| style.patch(*patch))
+    }
+
+    fn push_text(&mut self, text: &str, style: Style) {
+        if let Some(row) = self.table.as_mut().and_then(|t| t.rows.last_mut()) {
+            if let Some(cell) = row.last_mut() {
+                cell.push_str(text);
+            }
+            return;
+        }
+        self.inline.push(Span::styled(text.to_string(), style));
+    }
+
+    /// Finishes any pending text and separates the next block from the previous one
+    fn start_block(&mut self) {
+        self.flush_inline();
+        if self.blank_pending && !self.lines.is_empty() {
+            let prefix = self.quote_prefix();
+            self.lines.push(Line::from(prefix));
+        }
+        self.blank_pending = false;
+    }
+
+    fn flush_inline(&mut self) {
+        if self.inline.is_empty() {
+            return;
+        }
+        let spans = std::mem::take(&mut self.inline);
+        for line in wrap_spans(spans, self.available_width()) {
+            self.emit_line(line);
+        }
+    }
+
+    fn quote_prefix(&self) -> Vec<Span<'static>> {
+        self.containers
+            .iter()
+            .filter(|c| matches!(c, Container::Quote))
+            .map(|_| Span::styled("│ ", self.theme.markdown_quote))
+            .collect()
+    }
+
+    fn prefix_width(&self) -> usize {
+        self.containers
+            .iter()
+            .map(|c| match c {
+                Container::Quote => 2,
+                Container::List { .. } => 0,
+                Container::Item { marker, .. } => marker.width(),
+            })
+            .sum()
+    }
+
+    fn available_width(&self) -> usize {
+        self.width.saturating_sub(self.prefix_width()).max(1)
+    }
+
+    /// Adds a line with the prefixes of the enclosing quotes and list items
+    fn emit_line(&mut self, spans: Vec<Span<'static>>) {
+        let mut line = Vec::new();
+        for container in &mut self.containers {
+            match container {
+                Container::Quote => line.push(Span::styled("│ ", self.theme.markdown_quote)),
+                Container::List { .. } => {}
+                Container::Item { marker, first_line } => {
+                    if *first_line {
+                        line.push(Span::styled(marker.clone(), self.theme.key_hint));
+                        *first_line = false;
+                    } else {
+                        line.push(Span::raw(" ".repeat(marker.width())));
+                    }
+                }
+            }
+        }
+        line.extend(spans);
+        self.lines.push(Line::from(line));
+    }
+
+    fn render_code(&mut self, language: &str, code: &str) {
+        let style = self.theme.markdown_code;
+        let width = self.available_width().saturating_sub(2).max(1);
+        let mut highlighter = syntax_for_token(language)
+            .filter(|_| self.highlight && !language.is_empty())
+            .map(|syntax| Highlighter::new(syntax, self.theme, style));
+        for line in code.trim_end_matches('\n').split('\n') {
+            let line = line.trim_end_matches('\r').replace('\t', "    ");
+            let spans = match highlighter.as_mut() {
+                Some(highlighter) => highlighter.highlight_line(&line),
+                None => vec![Span::styled(line, style)],
+            };
+            for chunk in split_spans(spans, width) {
+                let mut spans = vec![Span::raw("  ")];
+                spans.extend(chunk);
+                self.emit_line(spans);
+            }
+        }
+    }
+
+    fn render_table(&mut self, table: Table) {
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let mut widths = vec![1; columns];
+        for row in &table.rows {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(cell.trim().width());
+            }
+        }
+        // Shrink the widest columns until the table fits, separators included
+        let available = self.available_width();
+        let separators = 3 * (columns - 1);
+        while widths.iter().sum::<usize>() + separators > available {
+            let Some(widest) = (0..columns)
+                .filter(|&i| widths[i] > 1)
+                .max_by_key(|&i| widths[i])
+            else {
+                break;
+            };
+            widths[widest] -= 1;
+        }
+
+        let border = self.theme.border;
+        for (row_index, row) in table.rows.iter().enumerate() {
+            let style = if row_index < table.header_rows {
+                self.base.add_modifier(Modifier::BOLD)
+            } else {
+                self.base
+            };
+            let mut spans = Vec::new();
+            for (i, width) in widths.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(" │ ", border));
+                }
+                let cell = row.get(i).map(|c| c.trim()).unwrap_or("");
+                let alignment = table.alignments.get(i).copied().unwrap_or(Alignment::None);
+                spans.extend(table_cell(cell, *width, alignment, style));
+            }
+            self.emit_line(truncate_spans(spans, available, border));
+
+            if row_index + 1 == table.header_rows {
+                let rule: Vec<String> = widths.iter().map(|w| "─".repeat(*w)).collect();
+                let rule = truncate_spans(
+                    vec![Span::styled(rule.join("─┼─"), border)],
+                    available,
+                    border,
+                );
+                self.emit_line(rule);
+            }
+        }
+    }
+}
+
+/// A table cell padded or truncated to exactly `width` columns
+fn table_cell(text: &str, width: usize, alignment: Alignment, style: Style) -> Vec<Span<'static>> {
+    let mut spans = truncate_spans(vec![Span::styled(text.to_string(), style)], width, style);
+    let padding = width.saturating_sub(spans.iter().map(|s| s.content.width()).sum());
+    let (left, right) = match alignment {
+        Alignment::Right => (padding, 0),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+        Alignment::Left | Alignment::None => (0, padding),
+    };
+    if left > 0 {
+        spans.insert(0, Span::raw(" ".repeat(left)));
+    }
+    if right > 0 {
+        spans.push(Span::raw(" ".repeat(right)));
+    }
+    spans
+}
+
+/// A word, possibly made of several differently styled pieces (e.g. `**bold**,`)
+enum Token {
+    Word(Vec<Span<'static>>),
+    Space,
+    Newline,
+}
+
+fn tokenize(spans: Vec<Span<'static>>) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word: Vec<Span<'static>> = Vec::new();
+    for span in spans {
+        let mut piece = String::new();
+        for c in span.content.chars() {
+            if c.is_whitespace() {
+                if !piece.is_empty() {
+                    word.push(Span::styled(std::mem::take(&mut piece), span.style));
+                }
+                if !word.is_empty() {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                }
+                tokens.push(if c == '\n' {
+                    Token::Newline
+                } else {
+                    Token::Space
+                });
+            } else {
+                piece.push(c);
+            }
+        }
+        if !piece.is_empty() {
+            word.push(Span::styled(piece, span.style));
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(Token::Word(word));
+    }
+    tokens
+}
+
+/// Word-wraps styled text to `width` columns, collapsing runs of spaces. Words longer
+/// than a line are split.
+fn wrap_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Vec<Span<'static>>> {
+    let mut lines = Vec::new();
+    let mut current: Vec<Span<'static>> = Vec::new();
+    let mut current_width = 0;
+    let mut space_pending = false;
+
+    for token in tokenize(spans) {
+        match token {
+            Token::Newline => {
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+                space_pending = false;
+            }
+            Token::Space => space_pending = current_width > 0,
+            Token::Word(pieces) => {
+                let word_width: usize = pieces.iter().map(|p| p.content.width()).sum();
+                if current_width > 0 && current_width + 1 + word_width > width {
+                    lines.push(std::mem::take(&mut current));
+                    current_width = 0;
+                    space_pending = false;
+                }
+                if space_pending {
+                    push_merged(&mut current, Span::raw(" "));
+                    current_width += 1;
+                    space_pending = false;
+                }
+                if word_width <= width.saturating_sub(current_width) {
+                    for piece in pieces {
+                        push_merged(&mut current, piece);
+                    }
+                    current_width += word_width;
+                } else {
+                    let mut chunks = split_spans(pieces, width).into_iter();
+                    if let Some(first) = chunks.next() {
+                        current.extend(first);
+                    }
+                    for chunk in chunks {
+                        lines.push(std::mem::replace(&mut current, chunk));
+                    }
+                    current_width = current.iter().map(|s| s.content.width()).sum();
+                }
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Splits styled text into chunks of at most `width` columns, keeping whitespace as is.
+/// Always returns at least one (possibly empty) chunk.
+pub(super) fn split_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Vec<Span<'static>>> {
+    let mut chunks = Vec::new();
+    let mut current: Vec<Span<'static>> = Vec::new();
+    let mut current_width = 0;
+    for span in spans {
+        let mut piece = String::new();
+        for c in span.content.chars() {
+            let char_width = c.width().unwrap_or(0);
+            if current_width + char_width > width && current_width > 0 {
+                if !piece.is_empty() {
+                    current.push(Span::styled(std::mem::take(&mut piece), span.style));
+                }
+                chunks.push(std::mem::take(&mut current));
+                current_width = 0;
+            }
+            piece.push(c);
+            current_width += char_width;
+        }
+        if !piece.is_empty() {
+            current.push(Span::styled(piece, span.style));
+        }
+    }
+    chunks.push(current);
+    chunks
+}
+
+/// Appends a span, merging it into the previous one when the styles match
+fn push_merged(spans: &mut Vec<Span<'static>>, span: Span<'static>) {
+    match spans.last_mut() {
+        Some(last) if last.style == span.style => last.content.to_mut().push_str(&span.content),
+        _ => spans.push(span),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    fn render(markdown: &str, width: usize) -> Vec<String> {
+        let theme = Theme::default();
+        render_markdown(markdown, width, &theme, Style::default(), true)
+            .iter()
+            .map(text)
+            .collect()
+    }
+
+    #[test]
+    fn test_inline_styles_drop_markup() {
+        let theme = Theme::default();
+        let lines = render_markdown(
+            "# Title\n\nSome **bold** and `code`.",
+            40,
+            &theme,
+            Style::default(),
+            false,
+        );
+        let texts: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(texts, vec!["Title", "", "Some bold and code."]);
+        let bold = lines[2].spans.iter().find(|s| s.content == "bold").unwrap();
+        assert!(bold.style.add_modifier.contains(Modifier::BOLD));
+        let code = lines[2].spans.iter().find(|s| s.content == "code").unwrap();
+        assert_eq!(code.style.fg, theme.markdown_code.fg);
+    }
+
+    #[test]
+    fn test_lists_wrap_with_hanging_indent() {
+        assert_eq!(
+            render("- one two three four\n- five\n  1. six", 12),
+            vec!["• one two", "  three four", "• five", "  1. six"]
+        );
+    }
+
+    #[test]
+    fn test_table_fits_width() {
+        let lines = render(
+            "| Name | Description |\n|---|--:|\n| a | a very long description |",
+            20,
+        );
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|l| l.width() <= 20), "{:?}", lines);
+        assert!(lines[1].starts_with("────"));
+        assert!(lines[2].ends_with('…'));
+    }
+
+    #[test]
+    fn test_code_block_lines_match_without_highlighting() {
+        let markdown = "Intro\n\n```rust\nfn main() {\n    println!(\"a long line that will need to wrap\");\n}\n```\nAfter";
+        let theme = Theme::default();
+        let plain = render_markdown(markdown, 24, &theme, Style::default(), false);
+        let highlighted = render_markdown(markdown, 24, &theme, Style::default(), true);
+        assert_eq!(
+            plain.iter().map(text).collect::<Vec<_>>(),
+            highlighted.iter().map(text).collect::<Vec<_>>()
+        );
+        assert!(highlighted.iter().all(|l| l.width() <= 24));
+        assert_eq!(text(&highlighted[2]), "  fn main() {");
+    }
+
+    #[test]
+    fn test_long_words_are_split() {
+        assert_eq!(render("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    }
+}
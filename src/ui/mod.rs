@@ -1,13 +1,27 @@
 pub mod agent_list;
+pub mod branch_picker;
 pub mod conversation;
+pub mod file_index;
+pub mod global_search;
+pub mod highlight;
 pub mod layout;
+pub mod markdown;
 pub mod project_list;
+pub mod search;
 pub mod session_list;
+pub mod slowest_tools;
+pub mod stats;
 pub mod styles;
 
 pub use agent_list::{AgentList, AgentListState};
-pub use conversation::{ConversationState, ConversationView};
-pub use layout::{AppLayout, FocusedPane, LayoutConfig};
+pub use branch_picker::{BranchPickerState, BranchPickerView};
+pub use conversation::{ConversationState, ConversationView, is_expandable};
+pub use file_index::{FileIndexState, FileIndexView};
+pub use global_search::{GLOBAL_SEARCH_LIMIT, GlobalSearchState, GlobalSearchView};
+pub use layout::{AppLayout, FocusedPane, LayoutConfig, content_row};
 pub use project_list::{ProjectList, ProjectListState};
+pub use search::{SearchDirection, SearchState};
 pub use session_list::{SessionList, SessionListState};
+pub use slowest_tools::{SLOWEST_TOOLS_LIMIT, SlowestToolsState, SlowestToolsView};
+pub use stats::StatsView;
 pub use styles::Theme;
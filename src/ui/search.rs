@@ -0,0 +1,371 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+
+use crate::logs::DisplayEntry;
+use crate::logs::search::{entry_matches, is_case_sensitive};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchDirection {
+    Forward,
+    Backward,
+}
+
+impl SearchDirection {
+    pub fn reversed(self) -> Self {
+        match self {
+            SearchDirection::Forward => SearchDirection::Backward,
+            SearchDirection::Backward => SearchDirection::Forward,
+        }
+    }
+
+    /// Prompt character shown in the status bar ("/" or "?")
+    pub fn prompt_char(self) -> char {
+        match self {
+            SearchDirection::Forward => '/',
+            SearchDirection::Backward => '?',
+        }
+    }
+}
+
+/// Vim-style incremental search over the loaded conversation.
+///
+/// Matches are tracked per entry (index into the conversation), so jumping only needs
+/// the entry's start line, which `ConversationView` resolves at render time.
+pub struct SearchState {
+    /// Query being typed; Some while the search prompt is open
+    pub prompt: Option<String>,
+    /// Direction of the last search, used by `n` (and reversed by `N`)
+    pub direction: SearchDirection,
+    /// Last committed query
+    pub query: String,
+    /// Indices of conversation entries that match the active pattern, ascending
+    pub matches: Vec<usize>,
+    /// Position in `matches` of the current match
+    pub current: Option<usize>,
+    /// Scroll offset and follow mode to restore if the prompt is cancelled
+    origin: Option<(usize, bool)>,
+    /// Entry at the top of the viewport when the prompt opened; incremental matches start here
+    origin_entry: usize,
+}
+
+impl SearchState {
+    pub fn new() -> Self {
+        Self {
+            prompt: None,
+            direction: SearchDirection::Forward,
+            query: String::new(),
+            matches: Vec::new(),
+            current: None,
+            origin: None,
+            origin_entry: 0,
+        }
+    }
+
+    pub fn is_prompting(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    /// The pattern currently in effect: the prompt text while typing, else the last query
+    pub fn pattern(&self) -> &str {
+        self.prompt.as_deref().unwrap_or(&self.query)
+    }
+
+    /// Open the prompt, remembering where to return to if it is cancelled
+    pub fn open(
+        &mut self,
+        direction: SearchDirection,
+        scroll_offset: usize,
+        follow_mode: bool,
+        top_entry: usize,
+    ) {
+        self.prompt = Some(String::new());
+        self.direction = direction;
+        self.origin = Some((scroll_offset, follow_mode));
+        self.origin_entry = top_entry;
+        self.matches.clear();
+        self.current = None;
+    }
+
+    /// Scroll offset and follow mode from when the prompt was opened
+    pub fn origin(&self) -> Option<(usize, bool)> {
+        self.origin
+    }
+
+    /// Close the prompt and keep the typed query. An empty prompt reuses the last query.
+    pub fn commit(&mut self) {
+        if let Some(prompt) = self.prompt.take()
+            && !prompt.is_empty()
+        {
+            self.query = prompt;
+        }
+        self.origin = None;
+    }
+
+    /// Close the prompt, returning the scroll position to restore
+    pub fn cancel(&mut self) -> Option<(usize, bool)> {
+        self.prompt = None;
+        self.matches.clear();
+        self.current = None;
+        self.origin.take()
+    }
+
+    /// Forget the active query and its highlights
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.matches.clear();
+        self.current = None;
+    }
+
+    /// Recompute matches against `entries`, keeping the current match on the same
+    /// entry if it still matches. `shift` is how many entries were dropped from the
+    /// front since the last update.
+    pub fn update_matches(&mut self, entries: &VecDeque<DisplayEntry>, shift: usize) {
+        let current_entry = self
+            .current
+            .and_then(|i| self.matches.get(i))
+            .and_then(|idx| idx.checked_sub(shift));
+
+        let pattern = self.pattern().to_string();
+        self.matches = if pattern.is_empty() {
+            Vec::new()
+        } else {
+            entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry_matches(entry, &pattern))
+                .map(|(idx, _)| idx)
+                .collect()
+        };
+
+        self.current = current_entry.and_then(|idx| self.matches.iter().position(|&m| m == idx));
+    }
+
+    /// Select the incremental match for the prompt, starting from the entry that was
+    /// at the top of the viewport when the prompt opened. Returns the entry to show.
+    pub fn select_incremental(&mut self) -> Option<usize> {
+        self.current = None;
+        self.step_from(self.origin_entry, self.direction, true)
+    }
+
+    /// Move to the next match in `direction` (wrapping). Without a current match the
+    /// search starts from `from_entry`. Returns the entry to show.
+    pub fn step(&mut self, direction: SearchDirection, from_entry: usize) -> Option<usize> {
+        match self.current {
+            Some(current) => {
+                let len = self.matches.len();
+                if len == 0 {
+                    return None;
+                }
+                let next = match direction {
+                    SearchDirection::Forward => (current + 1) % len,
+                    SearchDirection::Backward => (current + len - 1) % len,
+                };
+                self.current = Some(next);
+                self.matches.get(next).copied()
+            }
+            None => self.step_from(from_entry, direction, false),
+        }
+    }
+
+    fn step_from(
+        &mut self,
+        from_entry: usize,
+        direction: SearchDirection,
+        inclusive: bool,
+    ) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let pos = match direction {
+            SearchDirection::Forward => self
+                .matches
+                .iter()
+                .position(|&m| m > from_entry || (inclusive && m == from_entry))
+                .unwrap_or(0),
+            SearchDirection::Backward => self
+                .matches
+                .iter()
+                .rposition(|&m| m < from_entry || (inclusive && m == from_entry))
+                .unwrap_or(self.matches.len() - 1),
+        };
+        self.current = Some(pos);
+        self.matches.get(pos).copied()
+    }
+
+    /// Entry index of the current match
+    pub fn current_entry(&self) -> Option<usize> {
+        self.current.and_then(|i| self.matches.get(i)).copied()
+    }
+
+    /// Status text such as "match 3/17", or None when there is no active pattern
+    pub fn status(&self) -> Option<String> {
+        if self.pattern().is_empty() {
+            return None;
+        }
+        Some(match (self.current, self.matches.len()) {
+            (_, 0) => format!("no matches for \"{}\"", self.pattern()),
+            (Some(current), total) => format!("match {}/{}", current + 1, total),
+            (None, total) => format!("{} matches", total),
+        })
+    }
+}
+
+impl Default for SearchState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Byte ranges of `pattern` within `text`. ASCII case folding keeps byte offsets
+/// identical between the folded and original text.
+fn match_ranges(text: &str, pattern: &str) -> Vec<(usize, usize)> {
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+    let (haystack, needle) = if is_case_sensitive(pattern) {
+        (text.to_string(), pattern.to_string())
+    } else {
+        (text.to_ascii_lowercase(), pattern.to_ascii_lowercase())
+    };
+    haystack
+        .match_indices(&needle)
+        .map(|(start, m)| (start, start + m.len()))
+        .collect()
+}
+
+/// Re-style the parts of `line` that match `pattern`, splitting spans as needed
+pub fn highlight_line<'a>(line: Line<'a>, pattern: &str, style: Style) -> Line<'a> {
+    if pattern.is_empty() {
+        return line;
+    }
+
+    let mut spans = Vec::with_capacity(line.spans.len());
+    for span in line.spans {
+        let ranges = match_ranges(&span.content, pattern);
+        if ranges.is_empty() {
+            spans.push(span);
+            continue;
+        }
+        let content = span.content.as_ref();
+        let mut pos = 0;
+        for (start, end) in ranges {
+            if start > pos {
+                spans.push(Span::styled(content[pos..start].to_string(), span.style));
+            }
+            spans.push(Span::styled(
+                content[start..end].to_string(),
+                span.style.patch(style),
+            ));
+            pos = end;
+        }
+        if pos < content.len() {
+            spans.push(Span::styled(content[pos..].to_string(), span.style));
+        }
+    }
+
+    Line {
+        spans,
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> DisplayEntry {
+        DisplayEntry::AssistantText {
+            text: s.to_string(),
+            timestamp: None,
+        }
+    }
+
+    fn conversation(texts: &[&str]) -> VecDeque<DisplayEntry> {
+        texts.iter().map(|t| text(t)).collect()
+    }
+
+    #[test]
+    fn test_smartcase_matching() {
+        assert!(entry_matches(&text("Hello World"), "hello"));
+        assert!(entry_matches(&text("Hello World"), "World"));
+        assert!(!entry_matches(&text("hello world"), "World"));
+    }
+
+    #[test]
+    fn test_step_wraps_and_counts() {
+        let entries = conversation(&["foo", "bar", "foo", "baz", "foo"]);
+        let mut search = SearchState::new();
+        search.open(SearchDirection::Forward, 0, false, 0);
+        search.prompt = Some("foo".to_string());
+        search.update_matches(&entries, 0);
+        assert_eq!(search.select_incremental(), Some(0));
+        search.commit();
+
+        assert_eq!(search.step(SearchDirection::Forward, 0), Some(2));
+        assert_eq!(search.step(SearchDirection::Forward, 0), Some(4));
+        assert_eq!(search.step(SearchDirection::Forward, 0), Some(0));
+        assert_eq!(search.step(SearchDirection::Backward, 0), Some(4));
+        assert_eq!(search.status().as_deref(), Some("match 3/3"));
+    }
+
+    #[test]
+    fn test_backward_search_starts_above_viewport() {
+        let entries = conversation(&["foo", "bar", "foo", "baz"]);
+        let mut search = SearchState::new();
+        search.open(SearchDirection::Backward, 10, false, 3);
+        search.prompt = Some("foo".to_string());
+        search.update_matches(&entries, 0);
+        assert_eq!(search.select_incremental(), Some(2));
+    }
+
+    #[test]
+    fn test_update_matches_follows_dropped_entries() {
+        let mut entries = conversation(&["foo", "bar", "foo"]);
+        let mut search = SearchState::new();
+        search.query = "foo".to_string();
+        search.update_matches(&entries, 0);
+        search.step(SearchDirection::Forward, 1);
+        assert_eq!(search.current_entry(), Some(2));
+
+        entries.pop_front();
+        search.update_matches(&entries, 1);
+        assert_eq!(search.current_entry(), Some(1));
+        assert_eq!(search.status().as_deref(), Some("match 1/1"));
+    }
+
+    #[test]
+    fn test_cancel_restores_origin() {
+        let mut search = SearchState::new();
+        search.open(SearchDirection::Forward, 42, true, 5);
+        search.prompt = Some("x".to_string());
+        assert_eq!(search.cancel(), Some((42, true)));
+        assert!(!search.is_prompting());
+        assert_eq!(search.status(), None);
+    }
+
+    #[test]
+    fn test_highlight_line_splits_spans() {
+        let line = Line::from(vec![Span::raw("say Hello to hello")]);
+        let style = Style::default().bg(ratatui::style::Color::Yellow);
+        let highlighted = highlight_line(line, "hello", style);
+        let parts: Vec<_> = highlighted
+            .spans
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style == style))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("say ", false),
+                ("Hello", true),
+                (" to ", false),
+                ("hello", true)
+            ]
+        );
+    }
+}
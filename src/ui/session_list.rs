@@ -9,18 +9,28 @@ use unicode_width::UnicodeWidthStr;
 
 use super::styles::Theme;
 use crate::logs::Session;
+use crate::pricing::format_cost;
 
 pub struct SessionList<'a> {
     sessions: &'a [Session],
+    /// Estimated cost per session, aligned with `sessions`
+    costs: &'a [Option<f64>],
     focused: bool,
     collapsed: bool,
     theme: &'a Theme,
 }
 
 impl<'a> SessionList<'a> {
-    pub fn new(sessions: &'a [Session], focused: bool, collapsed: bool, theme: &'a Theme) -> Self {
+    pub fn new(
+        sessions: &'a [Session],
+        costs: &'a [Option<f64>],
+        focused: bool,
+        collapsed: bool,
+        theme: &'a Theme,
+    ) -> Self {
         Self {
             sessions,
+            costs,
             focused,
             collapsed,
             theme,
@@ -28,10 +38,19 @@ impl<'a> SessionList<'a> {
     }
 
     /// Calculate the maximum display width needed for the session list
-    pub fn max_content_width(sessions: &[Session]) -> u16 {
+    pub fn max_content_width(sessions: &[Session], costs: &[Option<f64>]) -> u16 {
         sessions
             .iter()
-            .map(|s| s.display_name().width() + 2) // +2 for "> " prefix
+            .enumerate()
+            .map(|(i, s)| {
+                let cost_width = costs
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .map(|c| format_cost(c).width() + 1) // +1 for separating space
+                    .unwrap_or(0);
+                s.display_name().width() + cost_width + 2 // +2 for "> " prefix
+            })
             .max()
             .unwrap_or(15) as u16
     }
@@ -73,14 +92,23 @@ impl<'a> StatefulWidget for SessionList<'a> {
                     ratatui::style::Style::default()
                 };
 
-                // Build multi-span line with right-aligned timestamp
+                // Build multi-span line with right-aligned cost and timestamp
                 let label = format!("{}{}", prefix, session.display_name_without_timestamp());
+                let cost = self
+                    .costs
+                    .get(i)
+                    .copied()
+                    .flatten()
+                    .map(|c| format!("{} ", format_cost(c)))
+                    .unwrap_or_default();
                 let timestamp = format!("({})", session.timestamp_str());
 
                 let label_width = label.width();
+                let cost_width = cost.width();
                 let timestamp_width = timestamp.width();
                 let padding_width = available_width
                     .saturating_sub(label_width as u16)
+                    .saturating_sub(cost_width as u16)
                     .saturating_sub(timestamp_width as u16);
 
                 ListItem::new(Line::from(vec![
@@ -89,6 +117,7 @@ impl<'a> StatefulWidget for SessionList<'a> {
                         " ".repeat(padding_width as usize),
                         ratatui::style::Style::default(),
                     ),
+                    Span::styled(cost, self.theme.tool_result),
                     Span::styled(timestamp, self.theme.timestamp),
                 ]))
             })
@@ -0,0 +1,149 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, StatefulWidget, Widget},
+};
+
+use super::styles::Theme;
+use crate::logs::{ToolTiming, format_duration};
+
+/// Number of calls listed in the slowest-tools overlay
+pub const SLOWEST_TOOLS_LIMIT: usize = 20;
+
+/// State of the slowest-tools overlay
+pub struct SlowestToolsState {
+    /// Whether the overlay is shown
+    pub open: bool,
+    /// Slowest calls of the loaded conversation, slowest first
+    pub timings: Vec<ToolTiming>,
+    pub list_state: ListState,
+}
+
+impl SlowestToolsState {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            timings: Vec::new(),
+            list_state: ListState::default(),
+        }
+    }
+
+    /// Show the overlay with the slowest call selected
+    pub fn show(&mut self, timings: Vec<ToolTiming>) {
+        self.list_state = ListState::default();
+        self.list_state
+            .select(if timings.is_empty() { None } else { Some(0) });
+        self.timings = timings;
+        self.open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Conversation index of the selected call
+    pub fn selected_entry(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.timings.get(i))
+            .map(|timing| timing.entry_index)
+    }
+
+    pub fn next(&mut self) {
+        if self.timings.is_empty() {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(i) if i + 1 < self.timings.len() => i + 1,
+            Some(_) => 0,
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        if self.timings.is_empty() {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(0) | None => self.timings.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.list_state.select(Some(i));
+    }
+}
+
+impl Default for SlowestToolsState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Centered overlay listing the tool calls that took the longest, with their durations
+pub struct SlowestToolsView<'a> {
+    theme: &'a Theme,
+}
+
+impl<'a> SlowestToolsView<'a> {
+    pub fn new(theme: &'a Theme) -> Self {
+        Self { theme }
+    }
+}
+
+impl<'a> StatefulWidget for SlowestToolsView<'a> {
+    type State = SlowestToolsState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let width = area.width.saturating_sub(8).clamp(20, 100).min(area.width);
+        let height = (state.timings.len() as u16 + 2)
+            .max(5)
+            .min(area.height.saturating_sub(4))
+            .min(area.height);
+        let popup = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+        Clear.render(popup, buf);
+
+        let title = format!(" Slowest tool calls ({}) ", state.timings.len());
+        let block = Block::default()
+            .title(Span::styled(title, self.theme.title_focused))
+            .borders(Borders::ALL)
+            .border_style(self.theme.border_focused);
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+
+        if state.timings.is_empty() {
+            Line::from(Span::styled(
+                "No timed tool calls in this conversation",
+                self.theme.timestamp,
+            ))
+            .render(inner, buf);
+            return;
+        }
+
+        let items: Vec<ListItem> = state
+            .timings
+            .iter()
+            .map(|timing| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:>8}  ", format_duration(timing.duration)),
+                        self.theme.timestamp,
+                    ),
+                    Span::styled(format!("{:<10} ", timing.name), self.theme.tool_name),
+                    Span::styled(timing.detail.clone(), self.theme.tool_input),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        StatefulWidget::render(list, inner, buf, &mut state.list_state);
+    }
+}
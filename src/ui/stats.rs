@@ -0,0 +1,177 @@
+use chrono::Local;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Row, Sparkline, Table, Widget},
+};
+
+use super::styles::Theme;
+use crate::logs::{SessionStats, format_duration, format_token_count};
+
+/// Statistics for the loaded conversation, shown in place of the conversation pane
+pub struct StatsView<'a> {
+    stats: &'a SessionStats,
+    focused: bool,
+    theme: &'a Theme,
+    /// Entries dropped from the front of the conversation, which the statistics don't count
+    entries_truncated: usize,
+}
+
+impl<'a> StatsView<'a> {
+    pub fn new(
+        stats: &'a SessionStats,
+        focused: bool,
+        theme: &'a Theme,
+        entries_truncated: usize,
+    ) -> Self {
+        Self {
+            stats,
+            focused,
+            theme,
+            entries_truncated,
+        }
+    }
+
+    fn summary(&self) -> Vec<Line<'a>> {
+        let stats = self.stats;
+        let label = |text: &'static str| Span::styled(text, self.theme.tool_name);
+        let value = |text: String| Span::styled(text, self.theme.assistant_text);
+
+        let span = match (stats.started(), stats.duration()) {
+            (Some(started), Some(duration)) => format!(
+                "{} (from {})",
+                format_duration(duration),
+                started.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            ),
+            _ => "-".to_string(),
+        };
+        let usage = stats.usage.total();
+        let cached = usage.cache_read_input_tokens + usage.cache_creation_input_tokens;
+
+        vec![
+            Line::from(vec![label("Duration    "), value(span)]),
+            Line::from(vec![
+                label("Tokens      "),
+                value(format!(
+                    "{} ({} in / {} out / {} cache) over {} messages",
+                    format_token_count(usage.total()),
+                    format_token_count(usage.input_tokens),
+                    format_token_count(usage.output_tokens),
+                    format_token_count(cached),
+                    stats.usage.messages(),
+                )),
+            ]),
+            Line::from(vec![
+                label("Activity    "),
+                value(format!(
+                    "{} prompts · {} tool calls ({} errors) · {} sub-agents",
+                    stats.prompts,
+                    stats.tool_calls(),
+                    stats.tool_errors(),
+                    stats.sub_agents,
+                )),
+            ]),
+            Line::from(vec![
+                label("Files       "),
+                value(format!(
+                    "{} read · {} written · {} edited",
+                    stats.files_read, stats.files_written, stats.files_edited,
+                )),
+            ]),
+        ]
+    }
+}
+
+impl Widget for StatsView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (border_style, title_style) = if self.focused {
+            (self.theme.border_focused, self.theme.title_focused)
+        } else {
+            (self.theme.border, self.theme.title)
+        };
+        let title = if self.entries_truncated > 0 {
+            format!(
+                " Statistics (partial, {}+ earliest entries not loaded) ",
+                self.entries_truncated
+            )
+        } else {
+            " Statistics ".to_string()
+        };
+        let block = Block::default()
+            .title(Span::styled(title, title_style))
+            .borders(Borders::ALL)
+            .border_style(border_style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [summary, activity, tools] = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(5),
+                Constraint::Length(7),
+                Constraint::Min(3),
+            ])
+            .areas(inner);
+
+        Paragraph::new(self.summary()).render(summary, buf);
+
+        // Activity over the session, one bar per column
+        let mut activity_block = Block::default()
+            .title(Span::styled(" Activity ", self.theme.title))
+            .borders(Borders::ALL)
+            .border_style(self.theme.border);
+        if let (Some(started), Some(ended)) = (self.stats.started(), self.stats.ended()) {
+            let format = |t: chrono::DateTime<chrono::Utc>| {
+                t.with_timezone(&Local).format(" %H:%M ").to_string()
+            };
+            activity_block = activity_block
+                .title_bottom(Line::styled(format(started), self.theme.timestamp).left_aligned())
+                .title_bottom(Line::styled(format(ended), self.theme.timestamp).right_aligned());
+        }
+        let data = self
+            .stats
+            .activity(activity_block.inner(activity).width as usize);
+        Sparkline::default()
+            .block(activity_block)
+            .data(&data)
+            .style(self.theme.tool_name)
+            .render(activity, buf);
+
+        // Calls and error rate per tool
+        let header = Row::new([
+            Line::from("Tool"),
+            Line::from("Calls").right_aligned(),
+            Line::from("Errors").right_aligned(),
+            Line::from("Error rate").right_aligned(),
+        ])
+        .style(self.theme.title.add_modifier(Modifier::BOLD));
+        let rows = self.stats.tools.iter().map(|tool| {
+            let error_style = if tool.errors > 0 {
+                self.theme.tool_error
+            } else {
+                self.theme.timestamp
+            };
+            Row::new([
+                Line::styled(tool.name.clone(), self.theme.tool_name),
+                Line::styled(tool.calls.to_string(), self.theme.assistant_text).right_aligned(),
+                Line::styled(tool.errors.to_string(), error_style).right_aligned(),
+                Line::styled(format!("{:.0}%", tool.error_rate() * 100.0), error_style)
+                    .right_aligned(),
+            ])
+        });
+        Table::new(
+            rows,
+            [
+                Constraint::Length(24),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Length(12),
+            ],
+        )
+        .header(header)
+        .render(tools, buf);
+    }
+}
@@ -1,6 +1,9 @@
 use ratatui::style::{Color, Modifier, Style};
 
 pub struct Theme {
+    /// Page background and default text. The TUI keeps the terminal's own background;
+    /// this is used where there is no terminal, such as HTML export.
+    pub background: Style,
     pub border: Style,
     pub border_focused: Style,
     pub title: Style,
@@ -18,14 +21,38 @@ pub struct Theme {
     pub thinking_collapsed: Style,
     pub hook_event: Style,
     pub agent_spawn: Style,
+    /// System entries: context compaction, API errors, local commands and other notices
+    pub compact_boundary: Style,
+    pub api_error: Style,
+    pub local_command: Style,
+    pub system_notice: Style,
+    /// Working directory, git branch and version changes
+    pub metadata: Style,
     pub status_bar: Style,
     pub key_hint: Style,
     pub timestamp: Style,
+    pub search_match: Style,
+    pub search_current: Style,
+    /// Syntax highlighting for code in tool previews, by token class
+    pub syntax_comment: Style,
+    pub syntax_keyword: Style,
+    pub syntax_string: Style,
+    pub syntax_constant: Style,
+    pub syntax_type: Style,
+    pub syntax_function: Style,
+    pub syntax_variable: Style,
+    pub syntax_special: Style,
+    /// Markdown in user and assistant messages
+    pub markdown_heading: Style,
+    pub markdown_code: Style,
+    pub markdown_link: Style,
+    pub markdown_quote: Style,
 }
 
 impl Default for Theme {
     fn default() -> Self {
         Self {
+            background: Style::default().bg(Color::Black).fg(Color::White),
             border: Style::default().fg(Color::DarkGray),
             border_focused: Style::default().fg(Color::Cyan),
             title: Style::default().fg(Color::White),
@@ -55,9 +82,43 @@ impl Default for Theme {
                 .add_modifier(Modifier::ITALIC),
             hook_event: Style::default().fg(Color::Blue),
             agent_spawn: Style::default().fg(Color::LightMagenta),
+            compact_boundary: Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            api_error: Style::default().fg(Color::LightRed),
+            local_command: Style::default().fg(Color::LightBlue),
+            system_notice: Style::default().fg(Color::Gray),
+            metadata: Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
             status_bar: Style::default().bg(Color::DarkGray).fg(Color::White),
             key_hint: Style::default().fg(Color::Cyan),
             timestamp: Style::default().fg(Color::DarkGray),
+            search_match: Style::default().bg(Color::Yellow).fg(Color::Black),
+            search_current: Style::default()
+                .bg(Color::LightRed)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            syntax_comment: Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+            syntax_keyword: Style::default().fg(Color::Magenta),
+            syntax_string: Style::default().fg(Color::Green),
+            syntax_constant: Style::default().fg(Color::LightRed),
+            syntax_type: Style::default().fg(Color::Yellow),
+            syntax_function: Style::default().fg(Color::Blue),
+            syntax_variable: Style::default().fg(Color::Red),
+            syntax_special: Style::default().fg(Color::Cyan),
+            markdown_heading: Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+            markdown_code: Style::default().fg(Color::Green),
+            markdown_link: Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::UNDERLINED),
+            markdown_quote: Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
         }
     }
 }